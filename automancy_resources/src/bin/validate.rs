@@ -0,0 +1,75 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use automancy_resources::kira::manager::backend::mock::MockBackend;
+use automancy_resources::kira::manager::{AudioManager, AudioManagerSettings};
+use automancy_resources::kira::track::TrackBuilder;
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
+use automancy_resources::validate::{load_and_validate, Severity};
+use automancy_resources::{ResourceManager, RESOURCES_PATH};
+
+static USAGE: &str = "usage: automancy_validate [--strict] [--language LANGUAGE] [RESOURCES_PATH]
+
+Loads every namespace in RESOURCES_PATH (default: resources) and reports dangling ids, unused resources and missing translations.
+Exits with a non-zero code if any errors were found, or any warnings with --strict.";
+
+fn main() -> ExitCode {
+    let mut strict = false;
+    let mut language = DEFAULT_LANGUAGE.to_string();
+    let mut path = PathBuf::from(RESOURCES_PATH);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--strict" => strict = true,
+            "--language" => match args.next() {
+                Some(v) => language = v,
+                None => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => path = PathBuf::from(arg),
+        }
+    }
+
+    let mut audio_man = match AudioManager::<MockBackend>::new(AudioManagerSettings::default()) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("could not create the audio backend: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    let track = match audio_man.add_sub_track(TrackBuilder::new()) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("could not create the audio track: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut resource_man = ResourceManager::new(track);
+    let issues = load_and_validate(&mut resource_man, &path, &language);
+
+    let errors = issues
+        .iter()
+        .filter(|v| v.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+
+    for issue in &issues {
+        println!("{issue}");
+    }
+    println!("{errors} errors, {warnings} warnings");
+
+    if errors > 0 || (strict && warnings > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
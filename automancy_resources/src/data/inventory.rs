@@ -8,7 +8,7 @@ use automancy_defs::id::{Id, IdRaw, Interner};
 use crate::data::item::Item;
 use crate::data::stack::ItemAmount;
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Inventory(BTreeMap<Id, ItemAmount>);
 
 impl Deref for Inventory {
@@ -96,9 +96,15 @@ impl Inventory {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct InventoryRaw(Vec<(IdRaw, ItemAmount)>);
 
+impl From<Vec<(IdRaw, ItemAmount)>> for InventoryRaw {
+    fn from(value: Vec<(IdRaw, ItemAmount)>) -> Self {
+        Self(value)
+    }
+}
+
 impl InventoryRaw {
     pub fn into_inner(self) -> Vec<(IdRaw, ItemAmount)> {
         self.0
@@ -5,7 +5,7 @@ use rhai::Dynamic;
 use automancy_defs::id::Id;
 
 use crate::data::stack::ItemStack;
-use crate::{ResourceManager, RESOURCE_MAN};
+use crate::ResourceManager;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct Item {
@@ -37,8 +37,8 @@ pub fn item_match(resource_man: &ResourceManager, id: Id, other: Id) -> bool {
     false
 }
 
-pub fn rhai_item_match(id: Id, other: Id) -> bool {
-    item_match(RESOURCE_MAN.read().unwrap().as_ref().unwrap(), id, other)
+pub fn rhai_item_match(resource_man: &ResourceManager, id: Id, other: Id) -> bool {
+    item_match(resource_man, id, other)
 }
 
 pub fn item_matches(
@@ -57,9 +57,9 @@ pub fn item_stack_matches(
     others.find(|&other| item_match(resource_man, id, other.item.id))
 }
 
-pub fn rhai_item_matches(id: Id, others: rhai::Array) -> Dynamic {
+pub fn rhai_item_matches(resource_man: &ResourceManager, id: Id, others: rhai::Array) -> Dynamic {
     match item_matches(
-        RESOURCE_MAN.read().unwrap().as_ref().unwrap(),
+        resource_man,
         id,
         others.into_iter().map(|v| v.cast::<Item>()),
     ) {
@@ -68,9 +68,13 @@ pub fn rhai_item_matches(id: Id, others: rhai::Array) -> Dynamic {
     }
 }
 
-pub fn rhai_item_stack_matches(id: Id, others: rhai::Array) -> Dynamic {
+pub fn rhai_item_stack_matches(
+    resource_man: &ResourceManager,
+    id: Id,
+    others: rhai::Array,
+) -> Dynamic {
     match item_stack_matches(
-        RESOURCE_MAN.read().unwrap().as_ref().unwrap(),
+        resource_man,
         id,
         others.into_iter().map(|v| v.cast::<ItemStack>()),
     ) {
@@ -88,11 +92,7 @@ pub fn item_match_str(resource_man: &ResourceManager, id: Id, other: &str) -> bo
     }
 }
 
-pub fn rhai_item_match_str(id: Id, other: ImmutableString) -> bool {
-    item_match_str(
-        RESOURCE_MAN.read().unwrap().as_ref().unwrap(),
-        id,
-        other.as_str(),
-    )
+pub fn rhai_item_match_str(resource_man: &ResourceManager, id: Id, other: ImmutableString) -> bool {
+    item_match_str(resource_man, id, other.as_str())
 }
  */
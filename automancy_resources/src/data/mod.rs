@@ -14,10 +14,11 @@ use crate::data::stack::ItemAmount;
 
 pub mod inventory;
 pub mod item;
+pub mod slots;
 pub mod stack;
 
 /// Represents the data a tile entity holds. This data is given to functions.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Data {
     Inventory(Inventory),
     Coord(TileCoord),
@@ -26,6 +27,7 @@ pub enum Data {
     VecId(Vec<Id>),
     SetId(HashSet<Id>),
     Amount(ItemAmount),
+    VecAmount(Vec<ItemAmount>),
     Bool(bool),
 }
 
@@ -97,6 +99,13 @@ impl Data {
         None
     }
 
+    pub fn as_vec_amount_mut(&mut self) -> Option<&mut Vec<ItemAmount>> {
+        if let Self::VecAmount(v) = self {
+            return Some(v);
+        }
+        None
+    }
+
     pub fn as_inventory(&self) -> Option<&Inventory> {
         if let Self::Inventory(v) = self {
             return Some(v);
@@ -153,6 +162,13 @@ impl Data {
         None
     }
 
+    pub fn as_vec_amount(&self) -> Option<&Vec<ItemAmount>> {
+        if let Self::VecAmount(v) = self {
+            return Some(v);
+        }
+        None
+    }
+
     pub fn rhai_value(self) -> Dynamic {
         match self {
             Data::Inventory(v) => Dynamic::from(v),
@@ -162,6 +178,7 @@ impl Data {
             Data::VecId(v) => Dynamic::from_iter(v),
             Data::SetId(v) => Dynamic::from(v),
             Data::Amount(v) => Dynamic::from_int(v),
+            Data::VecAmount(v) => Dynamic::from_iter(v),
             Data::Bool(v) => Dynamic::from_bool(v),
         }
     }
@@ -185,6 +202,8 @@ impl TryFrom<Dynamic> for Data {
             Ok(Data::VecId(value.cast()))
         } else if ty == TypeId::of::<ItemAmount>() {
             Ok(Data::Amount(value.cast()))
+        } else if ty == TypeId::of::<Vec<ItemAmount>>() {
+            Ok(Data::VecAmount(value.cast()))
         } else if ty == TypeId::of::<bool>() {
             Ok(Data::Bool(value.cast()))
         } else {
@@ -194,7 +213,7 @@ impl TryFrom<Dynamic> for Data {
 }
 
 impl Data {
-    fn try_to_raw(&self, interner: &Interner) -> Option<DataRaw> {
+    pub fn try_to_raw(&self, interner: &Interner) -> Option<DataRaw> {
         Some(match self {
             Data::Inventory(v) => DataRaw::Inventory(v.to_raw(interner)),
             Data::Coord(v) => DataRaw::Coord(*v),
@@ -211,12 +230,13 @@ impl Data {
                     .collect(),
             ),
             Data::Amount(v) => DataRaw::Amount(*v),
+            Data::VecAmount(v) => DataRaw::VecAmount(v.clone()),
             Data::Bool(v) => DataRaw::Bool(*v),
         })
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct DataMap(BTreeMap<Id, Data>);
 
 impl From<DataMap> for BTreeMap<Id, Data> {
@@ -297,7 +317,7 @@ impl DataMap {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DataRaw {
     Inventory(InventoryRaw),
     Coord(TileCoord),
@@ -306,6 +326,7 @@ pub enum DataRaw {
     VecId(Vec<IdRaw>),
     SetId(Vec<IdRaw>),
     Amount(ItemAmount),
+    VecAmount(Vec<ItemAmount>),
     Bool(bool),
 }
 
@@ -327,12 +348,13 @@ impl DataRaw {
                     .collect(),
             ),
             DataRaw::Amount(v) => Data::Amount(*v),
+            DataRaw::VecAmount(v) => Data::VecAmount(v.clone()),
             DataRaw::Bool(v) => Data::Bool(*v),
         })
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DataMapRaw(HashMap<String, DataRaw>);
 
 impl From<DataMapRaw> for HashMap<String, DataRaw> {
@@ -375,6 +397,7 @@ impl DataMapRaw {
                                     .collect(),
                             ),
                             DataRaw::Amount(v) => Data::Amount(*v),
+                            DataRaw::VecAmount(v) => Data::VecAmount(v.clone()),
                             DataRaw::Bool(v) => Data::Bool(*v),
                         },
                     )
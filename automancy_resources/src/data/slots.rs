@@ -0,0 +1,170 @@
+use rhai::{Dynamic, INT};
+
+use automancy_defs::id::Id;
+
+use crate::data::inventory::Inventory;
+use crate::data::item::item_match;
+use crate::data::stack::{ItemAmount, ItemStack};
+use crate::ResourceManager;
+
+/// A slot of a storage, which holds up to its capacity of a single item, that matches its filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    /// The item or tag the slot takes.
+    pub filter: Id,
+    pub capacity: ItemAmount,
+}
+
+impl Slot {
+    /// Pairs up the filters and capacities of a storage's slots. Slots missing either are left out.
+    pub fn from_lists(filters: &[Id], capacities: &[ItemAmount]) -> Vec<Slot> {
+        filters
+            .iter()
+            .zip(capacities)
+            .map(|(filter, capacity)| Slot {
+                filter: *filter,
+                capacity: *capacity,
+            })
+            .collect()
+    }
+
+    pub fn accepts(&self, resource_man: &ResourceManager, id: Id) -> bool {
+        self.filter == resource_man.registry.any || item_match(resource_man, id, self.filter)
+    }
+
+    /// Does the slot filter for one exact item, rather than a tag?
+    pub fn is_exact(&self, resource_man: &ResourceManager) -> bool {
+        resource_man.registry.items.contains_key(&self.filter)
+    }
+}
+
+/// What a storage's slots hold.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Allocation {
+    /// The item and amount in each slot, or nothing if it is empty.
+    pub slots: Vec<Option<(Id, ItemAmount)>>,
+    /// What didn't fit into any slot, such as after a slot was removed.
+    pub overflow: Inventory,
+}
+
+/// Puts the items of a storage's buffer into its slots. The slots for an exact item are filled first, then the tag slots in order, each with the first item that is still left.
+///
+/// The slots are worked out from the buffer every time, so that it stays the only thing a storage has to keep.
+pub fn allocate(resource_man: &ResourceManager, slots: &[Slot], buffer: &Inventory) -> Allocation {
+    let mut left = buffer.clone();
+    left.retain(|_, amount| *amount > 0);
+
+    let mut allocated = vec![None; slots.len()];
+
+    for exact in [true, false] {
+        for (slot, allocated) in slots.iter().zip(allocated.iter_mut()) {
+            if slot.is_exact(resource_man) != exact {
+                continue;
+            }
+
+            let Some((id, amount)) = left
+                .iter_mut()
+                .find(|(id, amount)| **amount > 0 && slot.accepts(resource_man, **id))
+            else {
+                continue;
+            };
+
+            let taking = (*amount).min(slot.capacity);
+            *amount -= taking;
+
+            if taking > 0 {
+                *allocated = Some((*id, taking));
+            }
+        }
+    }
+
+    left.retain(|_, amount| *amount > 0);
+
+    Allocation {
+        slots: allocated,
+        overflow: left,
+    }
+}
+
+/// Gets how much more of an item fits into the slots, without pushing out anything already in them.
+pub fn room(
+    resource_man: &ResourceManager,
+    slots: &[Slot],
+    buffer: &Inventory,
+    id: Id,
+) -> ItemAmount {
+    let overflow = allocate(resource_man, slots, buffer).overflow;
+
+    let fits = |amount: ItemAmount| {
+        let mut buffer = buffer.clone();
+        buffer.add(id, amount);
+
+        allocate(resource_man, slots, &buffer).overflow == overflow
+    };
+
+    // adding an item can move the others between the tag slots, so the room is searched for
+    let mut low = 0;
+    let mut high = slots
+        .iter()
+        .map(|slot| slot.capacity.max(0))
+        .sum::<ItemAmount>();
+
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+
+        if fits(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    low
+}
+
+fn rhai_slots(filters: rhai::Array, capacities: rhai::Array) -> Vec<Slot> {
+    let filters = filters
+        .into_iter()
+        .flat_map(|v| v.try_cast::<Id>())
+        .collect::<Vec<_>>();
+    let capacities = capacities
+        .into_iter()
+        .flat_map(|v| v.as_int().ok())
+        .collect::<Vec<_>>();
+
+    Slot::from_lists(&filters, &capacities)
+}
+
+pub fn rhai_slot_room(
+    resource_man: &ResourceManager,
+    filters: rhai::Array,
+    capacities: rhai::Array,
+    buffer: Inventory,
+    id: Id,
+) -> INT {
+    room(resource_man, &rhai_slots(filters, capacities), &buffer, id)
+}
+
+/// Gets the stack in each slot, or `()` for the empty ones.
+pub fn rhai_slot_contents(
+    resource_man: &ResourceManager,
+    filters: rhai::Array,
+    capacities: rhai::Array,
+    buffer: Inventory,
+) -> rhai::Array {
+    allocate(resource_man, &rhai_slots(filters, capacities), &buffer)
+        .slots
+        .into_iter()
+        .map(|slot| {
+            slot.and_then(|(id, amount)| {
+                resource_man.registry.item(id).map(|item| {
+                    Dynamic::from(ItemStack {
+                        item: *item,
+                        amount,
+                    })
+                })
+            })
+            .unwrap_or(Dynamic::UNIT)
+        })
+        .collect()
+}
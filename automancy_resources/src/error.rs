@@ -1,26 +1,83 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
+use rhai::EvalAltResult;
+
 use automancy_defs::id::Id;
 use automancy_defs::log;
 
-use crate::{format, ResourceManager};
+use crate::format::{format, FormatArgs};
+use crate::ResourceManager;
 
-/// An ErrorManager contains a queue of errors to be displayed.
+/// An ErrorManager contains a queue of errors to be displayed, in the order they were pushed.
 #[derive(Default)]
 pub struct ErrorManager {
-    queue: Arc<RwLock<Vec<GameError>>>,
+    queue: Arc<RwLock<VecDeque<GameError>>>,
+}
+
+pub type GameError = (Id, FormatArgs);
+
+/// An error from loading a single resource file. The file is skipped, and loading goes on with the rest.
+#[derive(Debug, Clone)]
+pub struct LoadError {
+    pub path: PathBuf,
+    /// The line and column the error is at, if known.
+    pub position: Option<(usize, usize)>,
+    pub message: String,
 }
 
-pub type GameError = (Id, Vec<String>);
+impl LoadError {
+    pub fn new(path: &Path, error: anyhow::Error) -> Self {
+        if let Some(error) = error.downcast_ref::<ron::error::SpannedError>() {
+            return Self {
+                path: path.to_path_buf(),
+                position: Some((error.position.line, error.position.col)),
+                message: error.code.to_string(),
+            };
+        }
+
+        if let Some(error) = error.downcast_ref::<Box<EvalAltResult>>() {
+            let position = error.position();
+
+            if let Some((line, col)) = position.line().zip(position.position()) {
+                return Self {
+                    path: path.to_path_buf(),
+                    position: Some((line, col)),
+                    message: error.unwrap_inner().to_string(),
+                };
+            }
+        }
+
+        Self {
+            path: path.to_path_buf(),
+            position: None,
+            message: format!("{error:#}"),
+        }
+    }
+
+    /// Converts the error into one that can be shown through the [`ErrorManager`].
+    pub fn to_game_error(&self, resource_man: &ResourceManager) -> GameError {
+        let args = FormatArgs::new()
+            .with("path", self.path.display().to_string())
+            .with("message", self.message.as_str());
+
+        match self.position {
+            Some((line, column)) => (
+                resource_man.registry.err_ids.invalid_resource_at,
+                args.with("line", line).with("column", column),
+            ),
+            None => (resource_man.registry.err_ids.invalid_resource, args),
+        }
+    }
+}
 
 /// Gets the ID of an error along with its arguments and converts it into a human-readable string.
 pub fn error_to_string((id, args): &GameError, resource_man: &ResourceManager) -> String {
     format(
-        resource_man.translates.error[id].as_str(),
-        args.iter()
-            .map(String::as_str)
-            .collect::<Vec<_>>()
-            .as_slice(),
+        &resource_man.translates.language,
+        resource_man.error_str(id),
+        args,
     )
 }
 
@@ -30,26 +87,29 @@ pub fn error_to_key((id, ..): &GameError, resource_man: &ResourceManager) -> Str
 }
 
 impl ErrorManager {
-    /// Adds a new error to the queue.
+    /// Adds a new error to the back of the queue.
     pub fn push(&self, error: GameError, resource_man: &ResourceManager) {
         log::error!("ERR: {}", error_to_key(&error, resource_man));
         self.queue
             .write()
             .expect("Could not write error")
-            .push(error);
+            .push_back(error);
     }
 
-    /// Removes the top error off of the stack and returns it or None if the queue is empty.
+    /// Removes the error at the front of the queue and returns it, or None if the queue is empty.
     pub fn pop(&self) -> Option<GameError> {
-        self.queue.write().expect("Could not write error").pop()
+        self.queue
+            .write()
+            .expect("Could not write error")
+            .pop_front()
     }
 
-    /// Copies the top error of the queue and returns it, or None if the queue is empty.
+    /// Copies the error at the front of the queue and returns it, or None if the queue is empty.
     pub fn peek(&self) -> Option<GameError> {
         self.queue
             .read()
             .expect("Could not read error")
-            .last()
+            .front()
             .cloned()
     }
 
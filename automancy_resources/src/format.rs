@@ -0,0 +1,332 @@
+use std::time::SystemTime;
+
+use automancy_defs::hashbrown::HashMap;
+
+use crate::format_time;
+
+static DEFAULT_TIME_FMT: &str = "%x %X";
+
+/// A value that can be put into a translated string.
+#[derive(Debug, Clone)]
+pub enum FormatArg {
+    Str(String),
+    Number(i64),
+    Time(SystemTime),
+}
+
+impl From<&str> for FormatArg {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for FormatArg {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<i64> for FormatArg {
+    fn from(value: i64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i32> for FormatArg {
+    fn from(value: i32) -> Self {
+        Self::Number(value as i64)
+    }
+}
+
+impl From<usize> for FormatArg {
+    fn from(value: usize) -> Self {
+        Self::Number(value as i64)
+    }
+}
+
+impl From<SystemTime> for FormatArg {
+    fn from(value: SystemTime) -> Self {
+        Self::Time(value)
+    }
+}
+
+/// The named arguments of a translated string.
+///
+/// # Examples
+///
+/// ```
+/// use automancy_resources::format::{format, FormatArgs};
+///
+/// let args = FormatArgs::new().with("count", 3).with("name", "test");
+///
+/// assert_eq!(
+///     format("en_US", "{name}: {count, plural, one {# map} other {# maps}}", &args),
+///     "test: 3 maps"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FormatArgs(HashMap<String, FormatArg>);
+
+impl FormatArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named argument.
+    pub fn with(mut self, name: &str, value: impl Into<FormatArg>) -> Self {
+        self.0.insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FormatArg> {
+        self.0.get(name)
+    }
+}
+
+/// Gets the language part of a language code, e.g. `fr` for `fr_CA`.
+fn language_prefix(language: &str) -> &str {
+    language
+        .split_once('_')
+        .map_or(language, |(prefix, _)| prefix)
+}
+
+/// Gets the thousands separator of a language. Languages it doesn't know of use `,`.
+fn thousands_separator(language: &str) -> char {
+    match language {
+        "de_CH" => '\'',
+        _ => match language_prefix(language) {
+            "de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" => '.',
+            "fr" => '\u{202f}',
+            "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "et" | "hu" | "bg" => {
+                '\u{a0}'
+            }
+            _ => ',',
+        },
+    }
+}
+
+/// Formats a number with the thousands separators of the language.
+pub fn format_number(language: &str, number: i64) -> String {
+    let separator = thousands_separator(language);
+    let digits = number.unsigned_abs().to_string();
+
+    let mut result = String::new();
+    if number < 0 {
+        result.push('-');
+    }
+
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(separator);
+        }
+        result.push(digit);
+    }
+
+    result
+}
+
+/// Picks the plural category of a number by the rules of the language, out of `zero`, `one`, `two`, `few`, `many` and `other`. Languages it doesn't know of use the english rules.
+pub fn plural_category(language: &str, number: i64) -> &'static str {
+    let n = number.unsigned_abs();
+    let (ones, tens) = (n % 10, n % 100);
+
+    match language_prefix(language) {
+        // no plural forms
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" => "other",
+        "fr" => {
+            if n <= 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "pt" if language != "pt_PT" => {
+            if n <= 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" => {
+            if ones == 1 && tens != 11 {
+                "one"
+            } else if (2..=4).contains(&ones) && !(12..=14).contains(&tens) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "pl" => {
+            if n == 1 {
+                "one"
+            } else if (2..=4).contains(&ones) && !(12..=14).contains(&tens) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "cs" | "sk" => match n {
+            1 => "one",
+            2..=4 => "few",
+            _ => "other",
+        },
+        "ar" => match n {
+            0 => "zero",
+            1 => "one",
+            2 => "two",
+            _ if (3..=10).contains(&tens) => "few",
+            _ if (11..=99).contains(&tens) => "many",
+            _ => "other",
+        },
+        _ => {
+            if n == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+}
+
+fn format_arg(arg: &FormatArg) -> String {
+    match arg {
+        FormatArg::Str(v) => v.clone(),
+        FormatArg::Number(v) => v.to_string(),
+        FormatArg::Time(v) => format_time(*v, DEFAULT_TIME_FMT),
+    }
+}
+
+/// Splits the branches of a plural placeholder, e.g. `=0 {none} one {# item} other {# items}`.
+fn plural_branches(body: &str) -> Vec<(&str, &str)> {
+    let mut branches = vec![];
+    let mut rest = body.trim();
+
+    while let Some(start) = rest.find('{') {
+        let selector = rest[..start].trim();
+
+        let mut depth = 0;
+        let mut end = None;
+        for (i, c) in rest[start..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(start + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let Some(end) = end else {
+            break;
+        };
+
+        branches.push((selector, &rest[start + 1..end]));
+        rest = rest[end + 1..].trim_start();
+    }
+
+    branches
+}
+
+/// Formats a single placeholder, without the surrounding braces. Returns None if the argument is missing or the placeholder is malformed.
+fn format_placeholder(language: &str, placeholder: &str, args: &FormatArgs) -> Option<String> {
+    let mut parts = placeholder.splitn(3, ',');
+
+    let name = parts.next()?.trim();
+    let kind = parts.next().map(str::trim);
+    let rest = parts.next().map(str::trim);
+
+    let arg = args.get(name)?;
+
+    match (kind, arg) {
+        (None, arg) => Some(format_arg(arg)),
+        (Some("number"), FormatArg::Number(v)) => Some(format_number(language, *v)),
+        (Some("time"), FormatArg::Time(v)) => {
+            Some(format_time(*v, rest.unwrap_or(DEFAULT_TIME_FMT)))
+        }
+        (Some("plural"), FormatArg::Number(v)) => {
+            let branches = plural_branches(rest?);
+
+            let exact = format!("={v}");
+            let category = plural_category(language, *v);
+
+            let (_, message) = branches
+                .iter()
+                .find(|(selector, _)| *selector == exact)
+                .or_else(|| branches.iter().find(|(selector, _)| *selector == category))
+                .or_else(|| branches.iter().find(|(selector, _)| *selector == "other"))?;
+
+            Some(format(
+                language,
+                &message.replace('#', &format_number(language, *v)),
+                args,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Formats a translated string with named arguments, by the rules of the language it is in.
+///
+/// Supported placeholders are:
+/// - `{name}` - the argument as is
+/// - `{name, number}` - a number with the language's thousands separators
+/// - `{name, time, FMT}` - a time, using the given strftime format string
+/// - `{name, plural, =0 {...} one {...} few {...} other {...}}` - picks a message by the number and the language's plural rules, in which `#` is replaced with the number
+///
+/// `{{` and `}}` produce literal braces. Placeholders that can't be formatted are kept as they are.
+pub fn format(language: &str, format: &str, args: &FormatArgs) -> String {
+    let mut result = String::new();
+    let mut chars = format.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek().map(|(_, c)| *c) == Some('}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut depth = 1;
+                let mut end = None;
+
+                for (j, c) in chars.by_ref() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = Some(j);
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                match end {
+                    Some(end) => {
+                        let placeholder = &format[i + 1..end];
+
+                        match format_placeholder(language, placeholder, args) {
+                            Some(v) => result.push_str(&v),
+                            None => result.push_str(&format[i..=end]),
+                        }
+                    }
+                    None => {
+                        result.push_str(&format[i..]);
+                    }
+                }
+            }
+            c => result.push(c),
+        }
+    }
+
+    result
+}
@@ -1,12 +1,13 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsStr;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::ops::{Add, Neg, Sub};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, Weak};
 use std::time::SystemTime;
 
+use anyhow::Context;
 pub use chrono;
 use chrono::{DateTime, Local};
 pub use kira;
@@ -22,13 +23,15 @@ use automancy_defs::hashbrown::HashMap;
 use automancy_defs::hexagon_tiles::traits::HexRotate;
 use automancy_defs::id;
 use automancy_defs::id::{id_static, Id, Interner};
-use automancy_defs::rendering::{Animation, Model};
+use automancy_defs::log;
+use automancy_defs::rendering::{Animation, Model, Vertex};
 
 use crate::data::inventory::Inventory;
 use crate::data::item::{rhai_item_match, rhai_item_matches, rhai_item_stack_matches, Item};
+use crate::data::slots::{rhai_slot_contents, rhai_slot_room};
 use crate::data::stack::{ItemAmount, ItemStack};
 use crate::data::DataMap;
-use crate::error::ErrorManager;
+use crate::error::{ErrorManager, LoadError};
 use crate::registry::{DataIds, ErrorIds, GuiIds, ModelIds, Registry};
 use crate::types::font::Font;
 use crate::types::model::IndexRange;
@@ -36,13 +39,17 @@ use crate::types::script::{Instructions, Script};
 use crate::types::tag::Tag;
 use crate::types::tile::Tile;
 use crate::types::translate::Translate;
+use crate::validate::Sources;
 pub mod data;
 pub mod error;
+pub mod format;
 
 pub mod registry;
 
 pub mod types;
 
+pub mod validate;
+
 static COULD_NOT_GET_FILE_STEM: &str = "could not get file stem";
 
 #[derive(Error, Debug)]
@@ -51,18 +58,22 @@ pub enum LoadResourceError {
     InvalidFileError(PathBuf, &'static str),
     #[error("could not convert OsString to String")]
     OsStringError(PathBuf),
+    #[error("the tag includes itself: {0}")]
+    TagCycle(String),
 }
 
-pub static RESOURCE_MAN: RwLock<Option<Arc<ResourceManager>>> = RwLock::new(None);
-
-// TODO this fucking sucks
-/// like format!, but does not require the format string to be static.
-pub fn format(format: &str, args: &[&str]) -> String {
-    let mut string = format.to_string();
-    for arg in args {
-        string = string.replacen("{}", arg, 1);
+/// Lets the functions registered into a resource manager's engine look things up in that resource manager.
+#[derive(Clone, Default)]
+pub struct ResourceContext(Arc<OnceLock<Weak<ResourceManager>>>);
+
+impl ResourceContext {
+    /// Gets the resource manager. Panics if it was never shared with [`ResourceManager::into_shared`].
+    pub fn get(&self) -> Arc<ResourceManager> {
+        self.0
+            .get()
+            .and_then(Weak::upgrade)
+            .expect("The resource manager is not shared yet")
     }
-    string
 }
 
 /// Converts a UTC Unix timestamp into a formatted time string, using the given strftime format string.
@@ -98,10 +109,14 @@ pub struct ResourceManager {
     pub track: TrackHandle,
     pub error_man: ErrorManager,
     pub engine: Engine,
+    /// What the engine's functions use to get to this resource manager.
+    pub context: ResourceContext,
 
     pub registry: Registry,
 
     pub translates: Translate,
+    /// Every language that at least one namespace has translations for.
+    pub languages: BTreeSet<SharedStr>,
     pub audio: HashMap<SharedStr, StaticSoundData>,
     pub shaders: HashMap<SharedStr, String>,
     pub functions: HashMap<Id, (AST, Scope<'static>)>,
@@ -111,6 +126,72 @@ pub struct ResourceManager {
     pub ordered_items: Vec<Id>,
     pub all_models: HashMap<Id, (HashMap<usize, Model>, Vec<Animation>)>,
     pub all_index_ranges: HashMap<Id, HashMap<usize, IndexRange>>,
+
+    pub sources: Sources,
+    /// The errors of every resource file that failed to load.
+    pub load_errors: Vec<LoadError>,
+}
+
+impl ResourceManager {
+    /// Records the error of a resource file that failed to load. The file is skipped, so the rest can still be loaded.
+    pub(crate) fn file_error(&mut self, file: &Path, error: anyhow::Error) {
+        log::error!("Error loading {file:?}: {error:?}");
+
+        self.load_errors.push(LoadError::new(file, error));
+    }
+
+    /// Loads every kind of resource in the namespace directory. Files that fail to load are skipped, and their errors are collected into `load_errors`.
+    pub fn load_namespace(&mut self, dir: &Path, language: &str) {
+        let results = [
+            self.load_models(dir).context("Error loading models"),
+            self.load_audio(dir).context("Error loading audio"),
+            self.load_tiles(dir).context("Error loading tiles"),
+            self.load_items(dir).context("Error loading items"),
+            self.load_tags(dir).context("Error loading tags"),
+            self.load_scripts(dir).context("Error loading scripts"),
+            self.load_translates(dir, language)
+                .context("Error loading translates"),
+            self.load_shaders(dir).context("Error loading shaders"),
+            self.load_fonts(dir).context("Error loading fonts"),
+            self.load_functions(dir).context("Error loading functions"),
+            self.load_researches(dir)
+                .context("Error loading researches"),
+            self.load_scenarios(dir).context("Error loading scenarios"),
+            self.load_deposits(dir).context("Error loading deposits"),
+        ];
+
+        for error in results.into_iter().filter_map(Result::err) {
+            self.file_error(dir, error);
+        }
+    }
+
+    /// Loads all the namespaces in the resources directory. Only fails if the directory can't be read.
+    pub fn load_all(&mut self, path: &Path, language: &str) -> anyhow::Result<()> {
+        for dir in std::fs::read_dir(path)?.flatten().map(|v| v.path()) {
+            let namespace = dir
+                .file_name()
+                .and_then(OsStr::to_str)
+                .unwrap_or_default()
+                .to_string();
+            log::info!("Loading namespace {namespace}...");
+
+            self.load_namespace(&dir, language);
+
+            log::info!("Loaded namespace {namespace}.");
+        }
+
+        Ok(())
+    }
+
+    /// Compiles everything that needs all the namespaces to be loaded first. Returns the vertices and indices of all the models.
+    pub fn compile(&mut self) -> (Vec<Vertex>, Vec<u16>) {
+        self.compile_researches();
+        self.compile_tags();
+        self.report_missing_translates();
+        self.ordered_items();
+
+        self.compile_models()
+    }
 }
 
 impl Debug for ResourceManager {
@@ -129,9 +210,44 @@ impl ResourceManager {
         engine.set_max_expr_depths(0, 0);
         engine.set_fast_operators(false);
 
-        engine.register_fn("item_match", rhai_item_match);
-        engine.register_fn("item_matches", rhai_item_matches);
-        engine.register_fn("item_matches", rhai_item_stack_matches);
+        let context = ResourceContext::default();
+
+        {
+            let context = context.clone();
+            engine.register_fn("item_match", move |id: Id, other: Id| {
+                rhai_item_match(&context.get(), id, other)
+            });
+        }
+        {
+            let context = context.clone();
+            engine.register_fn("item_matches", move |id: Id, others: rhai::Array| {
+                rhai_item_matches(&context.get(), id, others)
+            });
+        }
+        {
+            let context = context.clone();
+            engine.register_fn("item_matches", move |id: Id, others: rhai::Array| {
+                rhai_item_stack_matches(&context.get(), id, others)
+            });
+        }
+        {
+            let context = context.clone();
+            engine.register_fn(
+                "slot_room",
+                move |filters: rhai::Array, capacities: rhai::Array, buffer: Inventory, id: Id| {
+                    rhai_slot_room(&context.get(), filters, capacities, buffer, id)
+                },
+            );
+        }
+        {
+            let context = context.clone();
+            engine.register_fn(
+                "slot_contents",
+                move |filters: rhai::Array, capacities: rhai::Array, buffer: Inventory| {
+                    rhai_slot_contents(&context.get(), filters, capacities, buffer)
+                },
+            );
+        }
 
         {
             let mut module = Module::new();
@@ -174,8 +290,12 @@ impl ResourceManager {
                 .register_fn("take", Inventory::take_with_item)
                 .register_fn("add", Inventory::add)
                 .register_fn("add", Inventory::add_with_item)
+                .register_fn("is_empty", |v: &mut Inventory| {
+                    v.values().all(|amount| *amount <= 0)
+                })
                 .register_indexer_get_set(Inventory::get, Inventory::insert)
-                .register_indexer_get_set(Inventory::get_with_item, Inventory::insert_with_item);
+                .register_indexer_get_set(Inventory::get_with_item, Inventory::insert_with_item)
+                .register_indexer_get(|v: &mut Inventory, id: INT| v.get(Id::from(id)));
             engine
                 .register_type_with_name::<Id>("Id")
                 .register_iterator::<Vec<Id>>();
@@ -188,7 +308,14 @@ impl ResourceManager {
                     Some(v) => Dynamic::from_iter(v.clone()),
                     None => Dynamic::UNIT,
                 })
-                .register_get("outputs", |v: &mut Instructions| v.outputs.clone());
+                .register_get("catalysts", |v: &mut Instructions| {
+                    Dynamic::from_iter(v.catalysts.clone())
+                })
+                .register_get("outputs", |v: &mut Instructions| v.outputs.clone())
+                .register_get("duration", |v: &mut Instructions| v.duration)
+                .register_fn("roll", |v: &mut Instructions, random: INT| {
+                    Dynamic::from_iter(v.roll(random))
+                });
             engine.register_type_with_name::<Tile>("Tile");
             engine
                 .register_type_with_name::<Item>("Item")
@@ -210,58 +337,36 @@ impl ResourceManager {
         }
 
         {
-            engine.register_fn("as_script", |id: INT| {
-                match RESOURCE_MAN
-                    .read()
-                    .unwrap()
-                    .as_ref()
-                    .unwrap()
-                    .registry
-                    .script(Id::from(id))
-                    .cloned()
-                {
+            let context = context.clone();
+            engine.register_fn("as_script", move |id: INT| {
+                match context.get().registry.script(Id::from(id)).cloned() {
                     Some(v) => Dynamic::from(v),
                     None => Dynamic::UNIT,
                 }
             });
-            engine.register_fn("as_tile", |id: INT| {
-                match RESOURCE_MAN
-                    .read()
-                    .unwrap()
-                    .as_ref()
-                    .unwrap()
-                    .registry
-                    .tile(Id::from(id))
-                    .cloned()
-                {
+        }
+        {
+            let context = context.clone();
+            engine.register_fn("as_tile", move |id: INT| {
+                match context.get().registry.tile(Id::from(id)).cloned() {
                     Some(v) => Dynamic::from(v),
                     None => Dynamic::UNIT,
                 }
             });
-            engine.register_fn("as_item", |id: INT| {
-                match RESOURCE_MAN
-                    .read()
-                    .unwrap()
-                    .as_ref()
-                    .unwrap()
-                    .registry
-                    .item(Id::from(id))
-                    .cloned()
-                {
+        }
+        {
+            let context = context.clone();
+            engine.register_fn("as_item", move |id: INT| {
+                match context.get().registry.item(Id::from(id)).cloned() {
                     Some(v) => Dynamic::from(v),
                     None => Dynamic::UNIT,
                 }
             });
-            engine.register_fn("as_tag", |id: INT| {
-                match RESOURCE_MAN
-                    .read()
-                    .unwrap()
-                    .clone()
-                    .unwrap()
-                    .registry
-                    .tag(Id::from(id))
-                    .cloned()
-                {
+        }
+        {
+            let context = context.clone();
+            engine.register_fn("as_tag", move |id: INT| {
+                match context.get().registry.tag(Id::from(id)).cloned() {
                     Some(v) => Dynamic::from(v),
                     None => Dynamic::UNIT,
                 }
@@ -278,6 +383,7 @@ impl ResourceManager {
             track,
             error_man: Default::default(),
             engine,
+            context,
 
             registry: Registry {
                 tiles: Default::default(),
@@ -287,6 +393,8 @@ impl ResourceManager {
                 researches: Default::default(),
                 researches_id_map: Default::default(),
                 researches_unlock_map: Default::default(),
+                scenarios: Default::default(),
+                deposits: Default::default(),
 
                 none,
                 any,
@@ -298,6 +406,7 @@ impl ResourceManager {
             },
 
             translates: Default::default(),
+            languages: Default::default(),
             audio: Default::default(),
             shaders: Default::default(),
             functions: Default::default(),
@@ -307,6 +416,18 @@ impl ResourceManager {
             ordered_items: vec![],
             all_index_ranges: Default::default(),
             all_models: Default::default(),
+
+            sources: Default::default(),
+            load_errors: vec![],
         }
     }
+
+    /// Shares the resource manager once it is done loading, which lets the engine's functions get to it.
+    pub fn into_shared(self) -> Arc<Self> {
+        Arc::new_cyclic(|weak| {
+            _ = self.context.0.set(weak.clone());
+
+            self
+        })
+    }
 }
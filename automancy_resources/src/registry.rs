@@ -6,7 +6,9 @@ use automancy_macros::IdReg;
 
 use crate::data::item::Item;
 use crate::data::Data;
+use crate::types::deposit::Deposit;
 use crate::types::research::Research;
+use crate::types::scenario::Scenario;
 use crate::types::script::Script;
 use crate::types::tag::Tag;
 use crate::types::tile::Tile;
@@ -21,6 +23,8 @@ pub struct Registry {
     pub researches: StableDiGraph<Research, ()>,
     pub researches_id_map: HashMap<Id, NodeIndex>,
     pub researches_unlock_map: HashMap<Id, NodeIndex>,
+    pub scenarios: HashMap<Id, Scenario>,
+    pub deposits: HashMap<Id, Deposit>,
 
     pub none: Id,
     pub any: Id,
@@ -51,6 +55,14 @@ impl Registry {
     pub fn item(&self, id: Id) -> Option<&Item> {
         self.items.get(&id)
     }
+
+    pub fn scenario(&self, id: Id) -> Option<&Scenario> {
+        self.scenarios.get(&id)
+    }
+
+    pub fn deposit(&self, id: Id) -> Option<&Deposit> {
+        self.deposits.get(&id)
+    }
 }
 
 #[derive(Copy, Clone, IdReg)]
@@ -58,12 +70,14 @@ pub struct DataIds {
     pub script: Id,
     pub scripts: Id,
     pub buffer: Id,
+    pub output_buffer: Id,
     pub item: Id,
     pub item_type: Id,
     pub amount: Id,
     pub target: Id,
     pub link: Id,
     pub player_inventory: Id,
+    pub progress: Id,
 
     pub storage_takeable: Id,
     pub inactive_model: Id,
@@ -73,6 +87,35 @@ pub struct DataIds {
     pub linking: Id,
     pub default_tile: Id,
     pub unlocked_researches: Id,
+    pub scenario: Id,
+    pub objective_progress: Id,
+    pub scenario_completed: Id,
+    pub scenario_acknowledged: Id,
+    pub world_seed: Id,
+    pub remaining: Id,
+    pub exhausted_tile: Id,
+    pub conditional: Id,
+    pub condition_signal: Id,
+    pub condition_comparison: Id,
+    pub condition_value: Id,
+    pub slots: Id,
+    pub slot_amounts: Id,
+    pub max_slots: Id,
+    pub extract_index: Id,
+    pub logistics: Id,
+    pub weighted: Id,
+    pub split_weights: Id,
+    pub round_robin: Id,
+    pub split_index: Id,
+    pub priority: Id,
+    pub max_filters: Id,
+    pub sort_filters: Id,
+    pub sort_outputs: Id,
+    pub overflow: Id,
+    pub retry_in: Id,
+    pub retry_delay: Id,
+    pub in_flight: Id,
+    pub in_flight_ticks: Id,
 }
 
 #[derive(Copy, Clone, IdReg)]
@@ -96,19 +139,52 @@ pub struct GuiIds {
     pub tile_config_script: Id,
     pub tile_config_item: Id,
     pub tile_config_target: Id,
+    pub tile_config_slots: Id,
+    pub tile_config_filters: Id,
     pub error_popup: Id,
     pub debug_menu: Id,
     pub load_map: Id,
     pub delete_map: Id,
+    pub add_deposits: Id,
     pub create_map: Id,
     pub invalid_name: Id,
     pub options: Id,
+    pub objectives: Id,
+    pub scenario_complete: Id,
+    pub statistics: Id,
+    pub logistics: Id,
 
     pub lbl_amount: Id,
     pub lbl_link_destination: Id,
     pub lbl_maps_loaded: Id,
     pub lbl_pick_another_name: Id,
     pub lbl_delete_map_confirm: Id,
+    pub lbl_add_deposits_confirm: Id,
+    pub lbl_progress: Id,
+    pub lbl_saved_at: Id,
+    pub lbl_scenario: Id,
+    pub lbl_scenario_complete: Id,
+    pub lbl_objective_deliver: Id,
+    pub lbl_objective_place: Id,
+    pub lbl_seed: Id,
+    pub lbl_world_radius: Id,
+    pub lbl_deposit_density: Id,
+    pub lbl_remaining: Id,
+    pub lbl_produced: Id,
+    pub lbl_consumed: Id,
+    pub lbl_stored: Id,
+    pub lbl_last_minute: Id,
+    pub lbl_last_ten_minutes: Id,
+    pub lbl_last_hour: Id,
+    pub lbl_condition: Id,
+    pub lbl_requests: Id,
+    pub lbl_network_contents: Id,
+    pub lbl_no_networks: Id,
+    pub lbl_weights: Id,
+    pub lbl_round_robin: Id,
+    pub lbl_priority: Id,
+    pub lbl_overflow: Id,
+    pub lbl_output_held: Id,
 
     pub btn_confirm: Id,
     pub btn_exit: Id,
@@ -122,8 +198,8 @@ pub struct GuiIds {
     pub btn_load: Id,
     pub btn_delete: Id,
     pub btn_new_map: Id,
-
-    pub time_fmt: Id,
+    pub btn_continue: Id,
+    pub btn_randomize: Id,
 }
 
 /// Contains a list of errors that can be displayed.
@@ -133,4 +209,8 @@ pub struct ErrorIds {
     pub invalid_map_data: Id,
     /// This error is displayed when the options cannot be written.
     pub unwritable_options: Id,
+    /// This error is displayed when a resource file cannot be loaded.
+    pub invalid_resource: Id,
+    /// This error is displayed when a resource file cannot be loaded, and the position of the error is known.
+    pub invalid_resource_at: Id,
 }
@@ -10,6 +10,29 @@ use automancy_defs::log;
 use crate::{LoadResourceError, ResourceManager, AUDIO_EXT, COULD_NOT_GET_FILE_STEM};
 
 impl ResourceManager {
+    fn load_sound(&mut self, file: &Path) -> anyhow::Result<()> {
+        log::info!("Loading audio at {file:?}");
+
+        let audio = StaticSoundData::from_file(
+            file,
+            StaticSoundSettings::default().output_destination(&self.track),
+        )?;
+
+        let name = file
+            .file_stem()
+            .ok_or_else(|| {
+                LoadResourceError::InvalidFileError(file.to_path_buf(), COULD_NOT_GET_FILE_STEM)
+            })?
+            .to_str()
+            .ok_or_else(|| LoadResourceError::OsStringError(file.to_path_buf()))?;
+
+        self.audio.insert(name.to_shared_str(), audio);
+
+        log::info!("Registered audio with name {name}");
+
+        Ok(())
+    }
+
     pub fn load_audio(&mut self, dir: &Path) -> anyhow::Result<()> {
         let audio = dir.join("audio");
 
@@ -20,26 +43,8 @@ impl ResourceManager {
                 .map(|v| v.path())
                 .filter(|v| v.extension() == Some(OsStr::new(AUDIO_EXT)))
             {
-                log::info!("Loading audio at {file:?}");
-
-                if let Ok(audio) = StaticSoundData::from_file(
-                    &file,
-                    StaticSoundSettings::default().output_destination(&self.track),
-                ) {
-                    let name = file
-                        .file_stem()
-                        .ok_or_else(|| {
-                            LoadResourceError::InvalidFileError(
-                                file.clone(),
-                                COULD_NOT_GET_FILE_STEM,
-                            )
-                        })?
-                        .to_str()
-                        .ok_or_else(|| LoadResourceError::OsStringError(file.clone()))?;
-
-                    self.audio.insert(name.to_shared_str(), audio);
-
-                    log::info!("Registered audio with name {name}");
+                if let Err(err) = self.load_sound(&file) {
+                    self.file_error(&file, err);
                 }
             }
         }
@@ -0,0 +1,68 @@
+use std::ffi::OsStr;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use rhai::INT;
+use serde::{Deserialize, Serialize};
+
+use automancy_defs::id::{Id, IdRaw};
+use automancy_defs::log;
+
+use crate::{load_recursively, ResourceManager, RON_EXT};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DepositRaw {
+    pub id: IdRaw,
+    /// The tile that is placed for the deposit.
+    pub tile: IdRaw,
+    /// How often the deposit is picked, relative to the other deposits.
+    pub weight: INT,
+    /// The smallest and largest number of tiles in one cluster of the deposit.
+    pub size: (INT, INT),
+}
+
+/// A natural resource that world generation scatters around the map in clusters.
+#[derive(Debug, Clone)]
+pub struct Deposit {
+    pub id: Id,
+    pub tile: Id,
+    pub weight: INT,
+    pub size: (INT, INT),
+}
+
+impl ResourceManager {
+    fn load_deposit(&mut self, file: &Path) -> anyhow::Result<()> {
+        log::info!("Loading deposit at: {file:?}");
+
+        let deposit: DepositRaw = ron::from_str(&read_to_string(file)?)?;
+
+        let id = deposit.id.to_id(&mut self.interner);
+        self.sources.deposits.insert(id, file.to_path_buf());
+
+        let (min, max) = deposit.size;
+
+        self.registry.deposits.insert(
+            id,
+            Deposit {
+                id,
+                tile: deposit.tile.to_id(&mut self.interner),
+                weight: deposit.weight.max(0),
+                size: (min.max(1), max.max(min).max(1)),
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn load_deposits(&mut self, dir: &Path) -> anyhow::Result<()> {
+        let deposits = dir.join("deposits");
+
+        for file in load_recursively(&deposits, OsStr::new(RON_EXT)) {
+            if let Err(err) = self.load_deposit(&file) {
+                self.file_error(&file, err);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -16,6 +16,9 @@ use crate::{load_recursively, ResourceManager, RON_EXT};
 struct ItemRaw {
     id: IdRaw,
     model: IdRaw,
+    /// The tags this item is added to.
+    #[serde(default)]
+    tags: Vec<IdRaw>,
 }
 
 impl ResourceManager {
@@ -25,8 +28,15 @@ impl ResourceManager {
         let item: ItemRaw = ron::from_str(&read_to_string(file)?)?;
 
         let id = item.id.to_id(&mut self.interner);
+        self.sources.items.insert(id, file.to_path_buf());
         let model = item.model.to_id(&mut self.interner);
 
+        for tag in item.tags {
+            let tag = tag.to_id(&mut self.interner);
+
+            self.add_to_tag(tag, id);
+        }
+
         self.registry.items.insert(id, Item { id, model });
 
         Ok(())
@@ -36,7 +46,9 @@ impl ResourceManager {
         let items = dir.join("items");
 
         for file in load_recursively(&items, OsStr::new(RON_EXT)) {
-            self.load_item(&file)?;
+            if let Err(err) = self.load_item(&file) {
+                self.file_error(&file, err);
+            }
         }
 
         Ok(())
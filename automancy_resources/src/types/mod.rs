@@ -1,9 +1,11 @@
 pub mod audio;
+pub mod deposit;
 pub mod font;
 pub mod function;
 pub mod item;
 pub mod model;
 pub mod research;
+pub mod scenario;
 pub mod script;
 pub mod shader;
 pub mod tag;
@@ -49,6 +49,9 @@ impl ResourceManager {
 
         let model: ModelRaw = ron::from_str(&read_to_string(file)?)?;
 
+        let id = model.id.to_id(&mut self.interner);
+        self.sources.models.insert(id, file.to_path_buf());
+
         let file = file
             .parent()
             .unwrap()
@@ -165,8 +168,7 @@ impl ResourceManager {
             }
         }
 
-        self.all_models
-            .insert(model.id.to_id(&mut self.interner), (models, animations));
+        self.all_models.insert(id, (models, animations));
 
         Ok(())
     }
@@ -175,7 +177,9 @@ impl ResourceManager {
         let models = dir.join("models");
 
         for file in load_recursively(&models, OsStr::new(RON_EXT)) {
-            self.load_model(&file)?;
+            if let Err(err) = self.load_model(&file) {
+                self.file_error(&file, err);
+            }
         }
 
         Ok(())
@@ -37,6 +37,7 @@ impl ResourceManager {
         let research: ResearchRaw = ron::from_str(&read_to_string(file)?)?;
 
         let id = research.id.to_id(&mut self.interner);
+        self.sources.researches.insert(id, file.to_path_buf());
         let unlock = research.unlock.to_id(&mut self.interner);
         let icon = research.icon.to_id(&mut self.interner);
         let next = research
@@ -63,7 +64,9 @@ impl ResourceManager {
         let items = dir.join("researches");
 
         for file in load_recursively(&items, OsStr::new(RON_EXT)) {
-            self.load_research(&file)?;
+            if let Err(err) = self.load_research(&file) {
+                self.file_error(&file, err);
+            }
         }
 
         Ok(())
@@ -0,0 +1,219 @@
+use std::ffi::OsStr;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use rhai::INT;
+use serde::{Deserialize, Serialize};
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::{Id, IdRaw};
+use automancy_defs::log;
+
+use crate::data::inventory::{Inventory, InventoryRaw};
+use crate::data::item::item_match;
+use crate::data::stack::ItemAmount;
+use crate::data::{Data, DataMap, DataMapRaw};
+use crate::{load_recursively, ResourceManager, RON_EXT};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScenarioTileRaw {
+    pub coord: TileCoord,
+    pub id: IdRaw,
+    #[serde(default)]
+    pub tile_modifier: INT,
+    #[serde(default)]
+    pub data: DataMapRaw,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum ObjectiveRaw {
+    /// Have the amount of the item stored in tiles of the given type.
+    Deliver {
+        item: IdRaw,
+        amount: ItemAmount,
+        to: IdRaw,
+    },
+    /// Have the amount of tiles of the given type placed.
+    Place { tile: IdRaw, amount: ItemAmount },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScenarioRaw {
+    pub id: IdRaw,
+    pub name: IdRaw,
+    pub description: IdRaw,
+    /// The tiles the map starts with.
+    #[serde(default)]
+    pub tiles: Vec<ScenarioTileRaw>,
+    /// What the player starts with.
+    #[serde(default)]
+    pub inventory: InventoryRaw,
+    /// The only tiles that can be placed, if set.
+    #[serde(default)]
+    pub allowed_tiles: Option<Vec<IdRaw>>,
+    pub objectives: Vec<ObjectiveRaw>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioTile {
+    pub coord: TileCoord,
+    pub id: Id,
+    pub tile_modifier: INT,
+    pub data: DataMap,
+}
+
+/// Something to do to complete a scenario. Items and tiles can also be tags.
+#[derive(Debug, Clone, Copy)]
+pub enum Objective {
+    Deliver {
+        item: Id,
+        amount: ItemAmount,
+        to: Id,
+    },
+    Place {
+        tile: Id,
+        amount: ItemAmount,
+    },
+}
+
+impl Objective {
+    /// Gets the progress needed to complete the objective.
+    pub fn required(&self) -> ItemAmount {
+        match self {
+            Objective::Deliver { amount, .. } => *amount,
+            Objective::Place { amount, .. } => *amount,
+        }
+    }
+
+    /// Counts the progress of the objective, from every tile on the map and its data.
+    pub fn progress<'a>(
+        &self,
+        resource_man: &ResourceManager,
+        tiles: impl Iterator<Item = (Id, &'a DataMap)>,
+    ) -> ItemAmount {
+        match *self {
+            Objective::Deliver { item, to, .. } => tiles
+                .filter(|(id, _)| item_match(resource_man, *id, to))
+                .flat_map(|(_, data)| {
+                    data.get(&resource_man.registry.data_ids.buffer)
+                        .and_then(Data::as_inventory)
+                })
+                .flat_map(|buffer| buffer.iter())
+                .filter(|(id, _)| item_match(resource_man, **id, item))
+                .map(|(_, amount)| *amount)
+                .sum(),
+            Objective::Place { tile, .. } => tiles
+                .filter(|(id, _)| item_match(resource_man, *id, tile))
+                .count() as ItemAmount,
+        }
+    }
+}
+
+/// A map to play, with a starting layout and objectives to complete.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub id: Id,
+    pub name: Id,
+    pub description: Id,
+    pub tiles: Vec<ScenarioTile>,
+    pub inventory: Inventory,
+    pub allowed_tiles: Option<Vec<Id>>,
+    pub objectives: Vec<Objective>,
+}
+
+impl Scenario {
+    /// Checks if a tile can be placed in the scenario. Removing tiles is always allowed.
+    pub fn allows(&self, resource_man: &ResourceManager, tile: Id) -> bool {
+        if tile == resource_man.registry.none {
+            return true;
+        }
+
+        match &self.allowed_tiles {
+            Some(allowed) => allowed
+                .iter()
+                .any(|allowed| item_match(resource_man, tile, *allowed)),
+            None => true,
+        }
+    }
+}
+
+impl ResourceManager {
+    pub fn scenario_name(&self, id: &Id) -> &str {
+        match self.registry.scenario(*id) {
+            Some(scenario) => self.gui_str(&scenario.name),
+            None => &self.translates.unnamed,
+        }
+    }
+
+    fn load_scenario(&mut self, file: &Path) -> anyhow::Result<()> {
+        log::info!("Loading scenario at: {file:?}");
+
+        let scenario: ScenarioRaw = ron::from_str(&read_to_string(file)?)?;
+
+        let id = scenario.id.to_id(&mut self.interner);
+        self.sources.scenarios.insert(id, file.to_path_buf());
+
+        let tiles = scenario
+            .tiles
+            .into_iter()
+            .map(|tile| ScenarioTile {
+                coord: tile.coord,
+                id: tile.id.to_id(&mut self.interner),
+                tile_modifier: tile.tile_modifier,
+                data: tile.data.intern_to_data(&mut self.interner),
+            })
+            .collect();
+
+        let mut inventory = Inventory::default();
+        for (item, amount) in scenario.inventory.into_inner() {
+            inventory.add(item.to_id(&mut self.interner), amount);
+        }
+
+        let allowed_tiles = scenario
+            .allowed_tiles
+            .map(|v| v.iter().map(|id| id.to_id(&mut self.interner)).collect());
+
+        let objectives = scenario
+            .objectives
+            .into_iter()
+            .map(|objective| match objective {
+                ObjectiveRaw::Deliver { item, amount, to } => Objective::Deliver {
+                    item: item.to_id(&mut self.interner),
+                    amount,
+                    to: to.to_id(&mut self.interner),
+                },
+                ObjectiveRaw::Place { tile, amount } => Objective::Place {
+                    tile: tile.to_id(&mut self.interner),
+                    amount,
+                },
+            })
+            .collect();
+
+        self.registry.scenarios.insert(
+            id,
+            Scenario {
+                id,
+                name: scenario.name.to_id(&mut self.interner),
+                description: scenario.description.to_id(&mut self.interner),
+                tiles,
+                inventory,
+                allowed_tiles,
+                objectives,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn load_scenarios(&mut self, dir: &Path) -> anyhow::Result<()> {
+        let scenarios = dir.join("scenarios");
+
+        for file in load_recursively(&scenarios, OsStr::new(RON_EXT)) {
+            if let Err(err) = self.load_scenario(&file) {
+                self.file_error(&file, err);
+            }
+        }
+
+        Ok(())
+    }
+}
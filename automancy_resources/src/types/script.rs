@@ -2,6 +2,7 @@ use std::ffi::OsStr;
 use std::fs::read_to_string;
 use std::path::Path;
 
+use rhai::INT;
 use serde::{Deserialize, Serialize};
 
 use automancy_defs::id::{Id, IdRaw};
@@ -20,7 +21,23 @@ pub struct ScriptRaw {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InstructionsRaw {
     pub inputs: Option<Vec<(IdRaw, ItemAmount)>>,
+    /// Items that need to be present for the script to run, but are never consumed.
+    #[serde(default)]
+    pub catalysts: Vec<(IdRaw, ItemAmount)>,
     pub output: Vec<(IdRaw, ItemAmount)>,
+    /// Extra outputs, each produced with the given chance (out of 100).
+    #[serde(default)]
+    pub byproducts: Vec<(IdRaw, ItemAmount, INT)>,
+    /// Outputs of which exactly one is produced per batch, picked by weight.
+    #[serde(default)]
+    pub weighted: Vec<(IdRaw, ItemAmount, INT)>,
+    /// How many ticks the script takes to process one batch of inputs.
+    #[serde(default = "default_duration")]
+    pub duration: INT,
+}
+
+fn default_duration() -> INT {
+    1
 }
 
 #[derive(Debug, Clone)]
@@ -34,39 +51,119 @@ pub struct Script {
 #[derive(Debug, Clone)]
 pub struct Instructions {
     pub inputs: Option<Vec<ItemStack>>,
+    pub catalysts: Vec<ItemStack>,
     pub outputs: Vec<ItemStack>,
+    pub byproducts: Vec<(ItemStack, INT)>,
+    pub weighted: Vec<(ItemStack, INT)>,
+    pub duration: INT,
+}
+
+impl Instructions {
+    /// Rolls the outputs of one batch. The byproduct chances and the weighted pick are all derived from the given random number.
+    pub fn roll(&self, random: INT) -> Vec<ItemStack> {
+        let mut seed = random as u32 | 1;
+        let mut next = move || {
+            // xorshift32
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+
+        let mut outputs = self.outputs.clone();
+
+        let total_weight = self
+            .weighted
+            .iter()
+            .map(|(_, weight)| (*weight).max(0) as u32)
+            .sum::<u32>();
+
+        if total_weight > 0 {
+            let mut pick = next() % total_weight;
+
+            for (stack, weight) in &self.weighted {
+                let weight = (*weight).max(0) as u32;
+
+                if pick < weight {
+                    outputs.push(*stack);
+                    break;
+                }
+
+                pick -= weight;
+            }
+        }
+
+        for (stack, chance) in &self.byproducts {
+            if ((next() % 100) as INT) < *chance {
+                outputs.push(*stack);
+            }
+        }
+
+        outputs
+    }
 }
 
 impl ResourceManager {
+    fn item_stack(&mut self, file: &Path, id: IdRaw, amount: ItemAmount) -> Option<ItemStack> {
+        let id = id.to_id(&mut self.interner);
+
+        match self.registry.item(id).cloned() {
+            Some(item) => Some(ItemStack { item, amount }),
+            None => {
+                log::warn!(
+                    "Unknown item {} in script at: {file:?}",
+                    self.interner.resolve(id).unwrap_or("")
+                );
+                self.sources.unresolved.push((file.to_path_buf(), id));
+
+                None
+            }
+        }
+    }
+
     fn load_script(&mut self, file: &Path) -> anyhow::Result<()> {
         log::info!("Loading script at: {file:?}");
 
         let script: ScriptRaw = ron::from_str(&read_to_string(file)?)?;
 
         let id = script.id.to_id(&mut self.interner);
+        self.sources.scripts.insert(id, file.to_path_buf());
 
         let instructions = Instructions {
             inputs: script.instructions.inputs.map(|v| {
                 v.into_iter()
-                    .flat_map(|(id, amount)| {
-                        self.registry
-                            .item(id.to_id(&mut self.interner))
-                            .cloned()
-                            .map(|item| ItemStack { item, amount })
-                    })
+                    .flat_map(|(id, amount)| self.item_stack(file, id, amount))
                     .collect()
             }),
+            catalysts: script
+                .instructions
+                .catalysts
+                .into_iter()
+                .flat_map(|(id, amount)| self.item_stack(file, id, amount))
+                .collect(),
             outputs: script
                 .instructions
                 .output
                 .into_iter()
-                .flat_map(|(id, amount)| {
-                    self.registry
-                        .item(id.to_id(&mut self.interner))
-                        .cloned()
-                        .map(|item| ItemStack { item, amount })
+                .flat_map(|(id, amount)| self.item_stack(file, id, amount))
+                .collect(),
+            byproducts: script
+                .instructions
+                .byproducts
+                .into_iter()
+                .flat_map(|(id, amount, chance)| {
+                    self.item_stack(file, id, amount).zip(Some(chance))
+                })
+                .collect(),
+            weighted: script
+                .instructions
+                .weighted
+                .into_iter()
+                .flat_map(|(id, amount, weight)| {
+                    self.item_stack(file, id, amount).zip(Some(weight))
                 })
                 .collect(),
+            duration: script.instructions.duration.max(1),
         };
 
         let adjacent = script.adjacent.map(|id| id.to_id(&mut self.interner));
@@ -86,7 +183,9 @@ impl ResourceManager {
         let scripts = dir.join("scripts");
 
         for file in load_recursively(&scripts, OsStr::new(RON_EXT)) {
-            self.load_script(&file)?;
+            if let Err(err) = self.load_script(&file) {
+                self.file_error(&file, err);
+            }
         }
 
         Ok(())
@@ -8,6 +8,25 @@ use automancy_defs::log;
 use crate::{LoadResourceError, ResourceManager, COULD_NOT_GET_FILE_STEM, SHADER_EXT};
 
 impl ResourceManager {
+    fn load_shader(&mut self, file: &Path) -> anyhow::Result<()> {
+        log::info!("Loading shader at {file:?}");
+
+        let shader = read_to_string(file)?;
+
+        self.shaders.insert(
+            file.file_stem()
+                .ok_or_else(|| {
+                    LoadResourceError::InvalidFileError(file.to_path_buf(), COULD_NOT_GET_FILE_STEM)
+                })?
+                .to_str()
+                .ok_or_else(|| LoadResourceError::OsStringError(file.to_path_buf()))?
+                .to_shared_str(),
+            shader,
+        );
+
+        Ok(())
+    }
+
     pub fn load_shaders(&mut self, dir: &Path) -> anyhow::Result<()> {
         let shaders = dir.join("shaders");
         if let Ok(shaders) = read_dir(shaders) {
@@ -17,22 +36,8 @@ impl ResourceManager {
                 .map(|v| v.path())
                 .filter(|v| v.extension() == Some(OsStr::new(SHADER_EXT)))
             {
-                log::info!("Loading shader at {file:?}");
-
-                if let Ok(shader) = read_to_string(&file) {
-                    self.shaders.insert(
-                        file.file_stem()
-                            .ok_or_else(|| {
-                                LoadResourceError::InvalidFileError(
-                                    file.clone(),
-                                    COULD_NOT_GET_FILE_STEM,
-                                )
-                            })?
-                            .to_str()
-                            .ok_or_else(|| LoadResourceError::OsStringError(file.clone()))?
-                            .to_shared_str(),
-                        shader,
-                    );
+                if let Err(err) = self.load_shader(&file) {
+                    self.file_error(&file, err);
                 }
             }
         }
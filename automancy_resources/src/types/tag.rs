@@ -1,28 +1,41 @@
+use std::collections::VecDeque;
 use std::ffi::OsStr;
 use std::fs::read_to_string;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
-use automancy_defs::hashbrown::HashSet;
+use automancy_defs::hashbrown::{HashMap, HashSet};
 use automancy_defs::id::{Id, IdRaw};
 use automancy_defs::log;
 
 use crate::registry::Registry;
-use crate::{load_recursively, ResourceManager, RON_EXT};
+use crate::{load_recursively, LoadResourceError, ResourceManager, RON_EXT};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TagRaw {
     pub id: IdRaw,
+    /// Item ids, tile ids, or the ids of other tags to include.
     pub entries: Vec<IdRaw>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Tag {
     pub id: Id,
+    /// The ids of every item and tile in this tag. After [`ResourceManager::compile_tags`], nested tags are already flattened into this.
     pub entries: HashSet<Id>,
 }
 
+/// The state of the search for tags that include each other.
+#[derive(Default)]
+struct TagSearch {
+    index: HashMap<Id, usize>,
+    low: HashMap<Id, usize>,
+    stack: Vec<Id>,
+    on_stack: HashSet<Id>,
+    groups: Vec<Vec<Id>>,
+}
+
 impl Tag {
     pub fn of(&self, registry: &Registry, id: Id) -> bool {
         if self.id == registry.any {
@@ -34,23 +47,40 @@ impl Tag {
 }
 
 impl ResourceManager {
+    /// Adds an id to a tag, creating the tag if it doesn't exist yet.
+    pub(crate) fn add_to_tag(&mut self, tag: Id, id: Id) {
+        self.registry
+            .tags
+            .entry(tag)
+            .or_insert_with(|| Tag {
+                id: tag,
+                entries: Default::default(),
+            })
+            .entries
+            .insert(id);
+    }
+
     fn load_tag(&mut self, file: &Path) -> anyhow::Result<()> {
         log::info!("Loading tag at: {file:?}");
 
         let tag: TagRaw = ron::from_str(&read_to_string(file)?)?;
 
         let id = tag.id.to_id(&mut self.interner);
+        self.sources
+            .tags
+            .entry(id)
+            .or_insert_with(|| file.to_path_buf());
 
-        let tag = Tag {
+        self.registry.tags.entry(id).or_insert_with(|| Tag {
             id,
-            entries: tag
-                .entries
-                .into_iter()
-                .map(|id| id.to_id(&mut self.interner))
-                .collect(),
-        };
+            entries: Default::default(),
+        });
 
-        self.registry.tags.insert(id, tag);
+        for entry in tag.entries {
+            let entry = entry.to_id(&mut self.interner);
+
+            self.add_to_tag(id, entry);
+        }
 
         Ok(())
     }
@@ -59,9 +89,214 @@ impl ResourceManager {
         let tags = dir.join("tags");
 
         for file in load_recursively(&tags, OsStr::new(RON_EXT)) {
-            self.load_tag(&file)?;
+            if let Err(err) = self.load_tag(&file) {
+                self.file_error(&file, err);
+            }
         }
 
         Ok(())
     }
+
+    /// Flattens nested tags, so that every tag directly contains all the items and tiles it matches.
+    ///
+    /// Each cycle of tags that include each other is reported once, as an error. The tags on it include each other all the same, so they all match everything any of them does.
+    pub fn compile_tags(&mut self) {
+        let mut compiled = HashMap::<Id, HashSet<Id>>::new();
+
+        for group in self.tag_groups() {
+            if group.len() > 1 || self.registry.tags[&group[0]].entries.contains(&group[0]) {
+                let file = self
+                    .sources
+                    .tags
+                    .get(&group[0])
+                    .cloned()
+                    .unwrap_or_default();
+                let err = LoadResourceError::TagCycle(self.tag_cycle(&group));
+
+                self.file_error(&file, err.into());
+            }
+
+            let mut entries = HashSet::new();
+
+            for tag in &group {
+                for entry in &self.registry.tags[tag].entries {
+                    if group.contains(entry) {
+                        continue;
+                    }
+
+                    // the tags included from outside the group were compiled before it
+                    match compiled.get(entry) {
+                        Some(included) => entries.extend(included.iter().cloned()),
+                        None => {
+                            entries.insert(*entry);
+                        }
+                    }
+                }
+            }
+
+            for tag in group {
+                compiled.insert(tag, entries.clone());
+            }
+        }
+
+        for (id, entries) in compiled {
+            if let Some(tag) = self.registry.tags.get_mut(&id) {
+                tag.entries = entries;
+            }
+        }
+    }
+
+    /// Gets the tags a tag includes directly, sorted by name.
+    fn included_tags(&self, id: Id) -> Vec<Id> {
+        let mut included = self.registry.tags[&id]
+            .entries
+            .iter()
+            .filter(|entry| self.registry.tags.contains_key(*entry))
+            .cloned()
+            .collect::<Vec<_>>();
+        included.sort_by_key(|id| self.interner.resolve(*id).unwrap_or(""));
+
+        included
+    }
+
+    /// Groups the tags that include each other, each group sorted by name, and coming after every group its tags include.
+    fn tag_groups(&self) -> Vec<Vec<Id>> {
+        let mut tags = self.registry.tags.keys().cloned().collect::<Vec<_>>();
+        tags.sort_by_key(|id| self.interner.resolve(*id).unwrap_or(""));
+
+        let mut search = TagSearch::default();
+
+        for tag in tags {
+            if !search.index.contains_key(&tag) {
+                self.search_tag(tag, &mut search);
+            }
+        }
+
+        search.groups
+    }
+
+    /// Visits a tag, and the tags it includes, to find the groups of tags that include each other (with Tarjan's algorithm).
+    fn search_tag(&self, id: Id, search: &mut TagSearch) {
+        let index = search.index.len();
+        search.index.insert(id, index);
+        search.low.insert(id, index);
+        search.stack.push(id);
+        search.on_stack.insert(id);
+
+        for included in self.included_tags(id) {
+            let low = if !search.index.contains_key(&included) {
+                self.search_tag(included, search);
+
+                search.low[&included]
+            } else if search.on_stack.contains(&included) {
+                search.index[&included]
+            } else {
+                continue;
+            };
+
+            if low < search.low[&id] {
+                search.low.insert(id, low);
+            }
+        }
+
+        if search.low[&id] == index {
+            let mut group = vec![];
+
+            while let Some(member) = search.stack.pop() {
+                search.on_stack.remove(&member);
+                group.push(member);
+
+                if member == id {
+                    break;
+                }
+            }
+
+            group.sort_by_key(|id| self.interner.resolve(*id).unwrap_or(""));
+            search.groups.push(group);
+        }
+    }
+
+    /// Gets the shortest way the first tag of a group includes itself again, through the other tags of the group.
+    fn tag_cycle(&self, group: &[Id]) -> String {
+        let start = group[0];
+
+        let mut came_from = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+
+        'search: while let Some(tag) = queue.pop_front() {
+            for included in self.included_tags(tag) {
+                if !group.contains(&included) || came_from.contains_key(&included) {
+                    continue;
+                }
+
+                came_from.insert(included, tag);
+
+                if included == start {
+                    break 'search;
+                }
+
+                queue.push_back(included);
+            }
+        }
+
+        let mut cycle = vec![start];
+        let mut tag = came_from[&start];
+
+        while tag != start {
+            cycle.push(tag);
+            tag = came_from[&tag];
+        }
+
+        cycle.push(start);
+        cycle.reverse();
+
+        cycle
+            .iter()
+            .map(|v| self.interner.resolve(*v).unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
+    /// Gets every item and tile a tag matches, going into the tags it includes. `path` holds the tags being flattened, to find cycles, and `compiled` the tags that already were.
+    pub fn flatten_tag(
+        &self,
+        id: Id,
+        path: &mut Vec<Id>,
+        compiled: &mut HashMap<Id, HashSet<Id>>,
+    ) -> Result<HashSet<Id>, LoadResourceError> {
+        if let Some(entries) = compiled.get(&id) {
+            return Ok(entries.clone());
+        }
+
+        if path.contains(&id) {
+            path.push(id);
+
+            let cycle = path
+                .iter()
+                .skip_while(|v| **v != id)
+                .map(|v| self.interner.resolve(*v).unwrap_or(""))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            return Err(LoadResourceError::TagCycle(cycle));
+        }
+
+        path.push(id);
+
+        let mut entries = HashSet::new();
+
+        for entry in &self.registry.tags[&id].entries {
+            if self.registry.tags.contains_key(entry) {
+                entries.extend(self.flatten_tag(*entry, path, compiled)?);
+            } else {
+                entries.insert(*entry);
+            }
+        }
+
+        path.pop();
+
+        compiled.insert(id, entries.clone());
+
+        Ok(entries)
+    }
 }
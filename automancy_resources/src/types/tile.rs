@@ -17,6 +17,9 @@ pub struct TileRaw {
     pub models: Vec<IdRaw>,
     #[serde(default)]
     pub data: DataMapRaw,
+    /// The tags this tile is added to.
+    #[serde(default)]
+    pub tags: Vec<IdRaw>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,11 +36,18 @@ impl ResourceManager {
         let tile: TileRaw = ron::from_str(&read_to_string(file)?)?;
 
         let id = tile.id.to_id(&mut self.interner);
+        self.sources.tiles.insert(id, file.to_path_buf());
 
         let function = tile.function.map(|v| v.to_id(&mut self.interner));
 
         let data = tile.data.intern_to_data(&mut self.interner);
 
+        for tag in tile.tags {
+            let tag = tag.to_id(&mut self.interner);
+
+            self.add_to_tag(tag, id);
+        }
+
         let models = tile
             .models
             .into_iter()
@@ -59,7 +69,9 @@ impl ResourceManager {
         let tiles = dir.join("tiles");
 
         for file in load_recursively(&tiles, OsStr::new(RON_EXT)) {
-            self.load_tile(&file)?;
+            if let Err(err) = self.load_tile(&file) {
+                self.file_error(&file, err);
+            }
         }
 
         Ok(())
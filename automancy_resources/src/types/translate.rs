@@ -9,12 +9,16 @@ use automancy_defs::hashbrown::HashMap;
 use automancy_defs::id::{Id, IdRaw};
 use automancy_defs::log;
 
+use crate::format::{format, FormatArgs};
 use crate::{ResourceManager, RON_EXT};
 
+pub const DEFAULT_LANGUAGE: &str = "en_US";
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct TranslateRaw {
-    none: String,
-    unnamed: String,
+    none: Option<String>,
+    unnamed: Option<String>,
     items: HashMap<IdRaw, String>,
     tiles: HashMap<IdRaw, String>,
     scripts: HashMap<IdRaw, String>,
@@ -24,6 +28,8 @@ pub struct TranslateRaw {
 
 #[derive(Debug, Default, Clone)]
 pub struct Translate {
+    /// The language that was asked for, which picks the plural rules and separators the strings are formatted with.
+    pub language: SharedStr,
     pub none: SharedStr,
     pub unnamed: SharedStr,
     pub items: HashMap<Id, SharedStr>,
@@ -33,72 +39,166 @@ pub struct Translate {
     pub error: HashMap<Id, SharedStr>,
 }
 
+/// Gets the languages to try, in order, for the given language, out of the ones there are translations for.
+///
+/// After the language itself come the others with the same prefix, the one whose region is the prefix first (e.g. `fr_FR` for `fr`), then the default.
+/// e.g. `en_GB` with `en_US` and `en_AU` loaded -> `en_GB`, `en_AU`, `en_US`. `fr_CA` with `fr_FR` loaded -> `fr_CA`, `fr_FR`, `en_US`.
+pub fn language_chain<'a>(
+    language: &str,
+    available: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let mut chain = vec![language.to_string()];
+
+    let lang = language.split_once('_').map_or(language, |(lang, _)| lang);
+    let main = format!("{lang}_{}", lang.to_uppercase());
+
+    let mut related = available
+        .into_iter()
+        .filter(|v| *v != language && v.split_once('_').map(|(prefix, _)| prefix) == Some(lang))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    // the order of the files on disk isn't fixed, so they are sorted to always pick the same one
+    related.sort_by_key(|v| (*v != main, v.clone()));
+    related.dedup();
+
+    chain.extend(related);
+
+    if !chain.iter().any(|v| v == DEFAULT_LANGUAGE) {
+        chain.push(DEFAULT_LANGUAGE.to_string());
+    }
+
+    chain
+}
+
 impl ResourceManager {
+    /// Merges the translate file into the current translations, overriding existing entries.
     fn load_translate(&mut self, file: &Path) -> anyhow::Result<()> {
         log::info!("Loading translate at: {file:?}");
 
         let translate: TranslateRaw = ron::from_str(&read_to_string(file)?)?;
 
-        let none = translate.none.to_shared_str();
-        let unnamed = translate.unnamed.to_shared_str();
-
-        let items = translate
-            .items
-            .into_iter()
-            .map(|(id, str)| (id.to_id(&mut self.interner), str.into()))
-            .collect();
-        let tiles = translate
-            .tiles
-            .into_iter()
-            .map(|(id, str)| (id.to_id(&mut self.interner), str.into()))
-            .collect();
-        let scripts = translate
-            .scripts
-            .into_iter()
-            .map(|(id, str)| (id.to_id(&mut self.interner), str.into()))
-            .collect();
-        let gui = translate
-            .gui
-            .into_iter()
-            .map(|(id, str)| (id.to_id(&mut self.interner), str.into()))
-            .collect();
-        let error = translate
-            .error
-            .into_iter()
-            .map(|(id, str)| (id.to_id(&mut self.interner), str.into()))
-            .collect();
-
-        self.translates = Translate {
-            none,
-            unnamed,
-            items,
-            tiles,
-            scripts,
-            gui,
-            error,
-        };
+        if let Some(none) = translate.none {
+            self.translates.none = none.to_shared_str();
+        }
+        if let Some(unnamed) = translate.unnamed {
+            self.translates.unnamed = unnamed.to_shared_str();
+        }
+
+        for (id, str) in translate.items {
+            let id = id.to_id(&mut self.interner);
+
+            self.sources.translates.insert(id, file.to_path_buf());
+            self.translates.items.insert(id, str.into());
+        }
+        for (id, str) in translate.tiles {
+            let id = id.to_id(&mut self.interner);
+
+            self.sources.translates.insert(id, file.to_path_buf());
+            self.translates.tiles.insert(id, str.into());
+        }
+        for (id, str) in translate.scripts {
+            let id = id.to_id(&mut self.interner);
+
+            self.sources.translates.insert(id, file.to_path_buf());
+            self.translates.scripts.insert(id, str.into());
+        }
+        for (id, str) in translate.gui {
+            let id = id.to_id(&mut self.interner);
+
+            self.sources.translates.insert(id, file.to_path_buf());
+            self.translates.gui.insert(id, str.into());
+        }
+        for (id, str) in translate.error {
+            let id = id.to_id(&mut self.interner);
+
+            self.sources.translates.insert(id, file.to_path_buf());
+            self.translates.error.insert(id, str.into());
+        }
 
         Ok(())
     }
 
-    pub fn load_translates(&mut self, dir: &Path) -> anyhow::Result<()> {
+    /// Loads the translations of the namespace, with the least preferred language of the chain loaded first so the more specific ones override it.
+    pub fn load_translates(&mut self, dir: &Path, language: &str) -> anyhow::Result<()> {
+        self.translates.language = language.to_shared_str();
+
         let translates = dir.join("translates");
         let translates = read_dir(translates);
 
         if let Ok(translates) = translates {
-            for file in translates
+            let files = translates
                 .into_iter()
                 .flatten()
                 .map(|v| v.path())
                 .filter(|v| v.extension() == Some(OsStr::new(RON_EXT)))
-            {
-                // TODO language selection
-                if file.file_stem() == Some(OsStr::new("en_US")) {
-                    self.load_translate(&file)?;
+                .collect::<Vec<_>>();
+
+            for file in &files {
+                if let Some(stem) = file.file_stem().and_then(OsStr::to_str) {
+                    self.languages.insert(stem.to_shared_str());
+                }
+            }
+
+            let available = files
+                .iter()
+                .flat_map(|file| file.file_stem().and_then(OsStr::to_str));
+
+            for language in language_chain(language, available).iter().rev() {
+                for file in &files {
+                    if file.file_stem() == Some(OsStr::new(language)) {
+                        if let Err(err) = self.load_translate(file) {
+                            self.file_error(file, err);
+                        }
+                    }
                 }
             }
         }
 
         Ok(())
     }
+
+    /// Logs every GUI and error key that has no translation.
+    pub fn report_missing_translates(&self) {
+        let gui = self.registry.gui_ids.all();
+        let error = self.registry.err_ids.all();
+
+        for id in gui {
+            if !self.translates.gui.contains_key(&id) {
+                log::warn!(
+                    "Missing gui translation: {}",
+                    self.interner.resolve(id).unwrap_or("")
+                );
+            }
+        }
+
+        for id in error {
+            if !self.translates.error.contains_key(&id) {
+                log::warn!(
+                    "Missing error translation: {}",
+                    self.interner.resolve(id).unwrap_or("")
+                );
+            }
+        }
+    }
+
+    /// Gets the translation of a GUI key, or the key itself if it is missing.
+    pub fn gui_str(&self, id: &Id) -> &str {
+        match self.translates.gui.get(id) {
+            Some(str) => str.as_str(),
+            None => self.interner.resolve(*id).unwrap_or(""),
+        }
+    }
+
+    /// Formats the translation of a GUI key with named arguments, in the loaded language.
+    pub fn gui_format(&self, id: &Id, args: &FormatArgs) -> String {
+        format(&self.translates.language, self.gui_str(id), args)
+    }
+
+    /// Gets the translation of an error key, or the key itself if it is missing.
+    pub fn error_str(&self, id: &Id) -> &str {
+        match self.translates.error.get(id) {
+            Some(str) => str.as_str(),
+            None => self.interner.resolve(*id).unwrap_or(""),
+        }
+    }
 }
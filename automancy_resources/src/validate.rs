@@ -0,0 +1,629 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use automancy_defs::hashbrown::{HashMap, HashSet};
+use automancy_defs::id::Id;
+
+use crate::data::Data;
+use crate::types::scenario::Objective;
+use crate::ResourceManager;
+
+/// Where each loaded resource came from, so problems can be traced back to files.
+#[derive(Debug, Default)]
+pub struct Sources {
+    pub tiles: HashMap<Id, PathBuf>,
+    pub items: HashMap<Id, PathBuf>,
+    pub scripts: HashMap<Id, PathBuf>,
+    pub tags: HashMap<Id, PathBuf>,
+    pub researches: HashMap<Id, PathBuf>,
+    pub scenarios: HashMap<Id, PathBuf>,
+    pub deposits: HashMap<Id, PathBuf>,
+    pub models: HashMap<Id, PathBuf>,
+    pub functions: HashMap<Id, PathBuf>,
+    pub translates: HashMap<Id, PathBuf>,
+    /// References that could not be resolved while loading, and were dropped.
+    pub unresolved: Vec<(PathBuf, Id)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in the loaded resources.
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match &self.path {
+            Some(path) => write!(f, "{severity}: {}: {}", path.display(), self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+struct Validator<'a> {
+    resource_man: &'a ResourceManager,
+    known: HashSet<Id>,
+    issues: Vec<Issue>,
+}
+
+impl Validator<'_> {
+    fn name(&self, id: Id) -> &str {
+        self.resource_man
+            .interner
+            .resolve(id)
+            .unwrap_or("<unknown>")
+    }
+
+    fn push(&mut self, severity: Severity, path: Option<&PathBuf>, message: String) {
+        self.issues.push(Issue {
+            severity,
+            path: path.cloned(),
+            message,
+        });
+    }
+
+    fn check(&mut self, path: Option<&PathBuf>, what: &str, id: Id, exists: bool) {
+        if !exists {
+            self.push(
+                Severity::Error,
+                path,
+                format!("{what} {} does not exist", self.name(id)),
+            );
+        }
+    }
+
+    fn check_known(&mut self, path: Option<&PathBuf>, what: &str, id: Id) {
+        let exists = self.known.contains(&id);
+
+        self.check(path, what, id, exists);
+    }
+}
+
+/// What [`code_only`] is in the middle of.
+enum Nesting {
+    /// A template string, e.g. `` `${x} items` ``.
+    Template,
+    /// Code interpolated into a template string, and how many braces are open in it.
+    Interpolation(usize),
+}
+
+/// Blanks out the comments and the contents of strings in a function, so that only the code is left. Lines are kept where they are.
+fn code_only(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut nesting = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    // keeps the token boundaries and the lines, without what was skipped
+    let blank = |code: &mut String, c: char| code.push(if c == '\n' { '\n' } else { ' ' });
+
+    while let Some(c) = chars.next() {
+        if let Some(Nesting::Template) = nesting.last() {
+            match c {
+                '\\' => {
+                    blank(&mut code, c);
+                    if let Some(c) = chars.next() {
+                        blank(&mut code, c);
+                    }
+                }
+                '`' => {
+                    nesting.pop();
+                    blank(&mut code, c);
+                }
+                '$' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    nesting.push(Nesting::Interpolation(0));
+                    code.push_str("  ");
+                }
+                c => blank(&mut code, c),
+            }
+
+            continue;
+        }
+
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                blank(&mut code, c);
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    blank(&mut code, c);
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                // block comments can be nested
+                let mut depth = 0;
+                let mut prev = c;
+                blank(&mut code, c);
+
+                for c in chars.by_ref() {
+                    blank(&mut code, c);
+
+                    match (prev, c) {
+                        ('/', '*') => {
+                            depth += 1;
+                            prev = ' ';
+                            continue;
+                        }
+                        ('*', '/') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                            prev = ' ';
+                            continue;
+                        }
+                        _ => {}
+                    }
+
+                    prev = c;
+                }
+            }
+            '"' | '\'' => {
+                blank(&mut code, c);
+
+                while let Some(next) = chars.next() {
+                    blank(&mut code, next);
+
+                    if next == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            blank(&mut code, escaped);
+                        }
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '`' => {
+                nesting.push(Nesting::Template);
+                blank(&mut code, c);
+            }
+            '{' => {
+                if let Some(Nesting::Interpolation(depth)) = nesting.last_mut() {
+                    *depth += 1;
+                }
+                code.push(c);
+            }
+            '}' => match nesting.last_mut() {
+                Some(Nesting::Interpolation(0)) => {
+                    nesting.pop();
+                    blank(&mut code, c);
+                }
+                Some(Nesting::Interpolation(depth)) => {
+                    *depth -= 1;
+                    code.push(c);
+                }
+                _ => code.push(c),
+            },
+            c => code.push(c),
+        }
+    }
+
+    code
+}
+
+/// Gets the uppercase identifiers used in a function, which are expected to be the keys declared in its `id_deps`. Comments and strings are left out, but the code interpolated into template strings isn't.
+///
+/// Names the function declares itself, with `const`, `let` or `for`, or as the parameters of its functions, are left out too.
+pub fn constants_used(source: &str) -> HashSet<String> {
+    let mut constants = HashSet::new();
+    let mut declared = HashSet::new();
+
+    let mut qualified = false;
+    let mut in_params = false;
+    let mut prev = ' ';
+    let mut token = String::new();
+    let mut last = String::new();
+    let mut before_last = String::new();
+
+    for c in code_only(source).chars().chain(Some(' ')) {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            if token.is_empty() {
+                // skip module paths, like TileCoord::LEFT
+                qualified = prev == ':';
+            }
+
+            token.push(c);
+        } else {
+            if !token.is_empty() {
+                if in_params || matches!(last.as_str(), "const" | "let" | "for") {
+                    declared.insert(token.clone());
+                } else if !qualified
+                    && token.len() > 1
+                    && token.starts_with(|c: char| c.is_ascii_uppercase())
+                    && token
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+                {
+                    constants.insert(token.clone());
+                }
+
+                before_last = mem::replace(&mut last, mem::take(&mut token));
+            }
+
+            match c {
+                '(' if before_last == "fn" => in_params = true,
+                ')' => in_params = false,
+                _ => {}
+            }
+        }
+
+        prev = c;
+    }
+
+    constants.retain(|constant| !declared.contains(constant));
+
+    constants
+}
+
+/// Cross-checks every reference between the loaded resources, and reports dangling ids, unused resources and missing translations.
+pub fn validate(resource_man: &ResourceManager) -> Vec<Issue> {
+    let registry = &resource_man.registry;
+    let sources = &resource_man.sources;
+
+    let known = registry
+        .tiles
+        .keys()
+        .chain(registry.items.keys())
+        .chain(registry.scripts.keys())
+        .chain(registry.tags.keys())
+        .chain(resource_man.all_models.keys())
+        .chain(resource_man.functions.keys())
+        .chain(registry.researches_id_map.keys())
+        .cloned()
+        .chain(registry.data_ids.all())
+        .chain(registry.model_ids.all())
+        .chain(registry.gui_ids.all())
+        .chain(registry.err_ids.all())
+        .collect::<HashSet<_>>();
+
+    let mut v = Validator {
+        resource_man,
+        known,
+        issues: vec![],
+    };
+
+    let mut used = HashSet::new();
+    used.extend(registry.model_ids.all());
+
+    for (path, id) in &sources.unresolved {
+        v.check(Some(path), "item", *id, false);
+    }
+
+    let mut tiles = registry.tiles.iter().collect::<Vec<_>>();
+    tiles.sort_by_key(|(id, _)| v.name(**id).to_string());
+
+    for (id, tile) in tiles {
+        let path = sources.tiles.get(id);
+
+        if let Some(function) = tile.function {
+            v.check(
+                path,
+                "function",
+                function,
+                resource_man.functions.contains_key(&function),
+            );
+            used.insert(function);
+        }
+
+        for model in &tile.models {
+            v.check(
+                path,
+                "model",
+                *model,
+                resource_man.all_models.contains_key(model),
+            );
+            used.insert(*model);
+        }
+
+        for (key, data) in tile.data.iter() {
+            let ids = match data {
+                Data::Id(id) => vec![*id],
+                Data::VecId(ids) => ids.clone(),
+                Data::SetId(ids) => ids.iter().cloned().collect(),
+                _ => continue,
+            };
+
+            for id in ids {
+                let what = format!("id in data {}", v.name(*key));
+
+                v.check_known(path, &what, id);
+                used.insert(id);
+            }
+        }
+
+        if !resource_man.translates.tiles.contains_key(id) {
+            v.push(
+                Severity::Warning,
+                path,
+                format!("tile {} has no translation", v.name(*id)),
+            );
+        }
+    }
+
+    for (id, item) in &registry.items {
+        let path = sources.items.get(id);
+
+        v.check(
+            path,
+            "model",
+            item.model,
+            resource_man.all_models.contains_key(&item.model),
+        );
+        used.insert(item.model);
+
+        if !resource_man.translates.items.contains_key(id) {
+            v.push(
+                Severity::Warning,
+                path,
+                format!("item {} has no translation", v.name(*id)),
+            );
+        }
+    }
+
+    for (id, script) in &registry.scripts {
+        let path = sources.scripts.get(id);
+
+        if let Some(adjacent) = script.adjacent {
+            v.check_known(path, "adjacent tile or tag", adjacent);
+            used.insert(adjacent);
+        }
+
+        let instructions = &script.instructions;
+        for stack in instructions
+            .inputs
+            .iter()
+            .flatten()
+            .chain(&instructions.catalysts)
+            .chain(&instructions.outputs)
+            .chain(instructions.byproducts.iter().map(|(stack, _)| stack))
+            .chain(instructions.weighted.iter().map(|(stack, _)| stack))
+        {
+            used.insert(stack.item.id);
+        }
+
+        if !resource_man.translates.scripts.contains_key(id) {
+            v.push(
+                Severity::Warning,
+                path,
+                format!("script {} has no translation", v.name(*id)),
+            );
+        }
+    }
+
+    for (id, path) in &sources.tags {
+        if let Some(tag) = registry.tag(*id) {
+            for entry in &tag.entries {
+                v.check_known(Some(path), "tag entry", *entry);
+                used.insert(*entry);
+            }
+        }
+    }
+
+    for research in registry.researches.node_weights() {
+        let path = sources.researches.get(&research.id);
+
+        v.check(
+            path,
+            "unlocked tile",
+            research.unlock,
+            registry.tiles.contains_key(&research.unlock),
+        );
+        v.check_known(path, "icon", research.icon);
+        used.insert(research.icon);
+
+        for next in research.next.iter().flatten() {
+            v.check(
+                path,
+                "next research",
+                *next,
+                registry.researches_id_map.contains_key(next),
+            );
+        }
+
+        for key in [research.name, research.description] {
+            if !resource_man.translates.gui.contains_key(&key) {
+                v.push(
+                    Severity::Warning,
+                    path,
+                    format!("research key {} has no translation", v.name(key)),
+                );
+            }
+        }
+    }
+
+    for scenario in registry.scenarios.values() {
+        let path = sources.scenarios.get(&scenario.id);
+
+        for tile in &scenario.tiles {
+            v.check(path, "tile", tile.id, registry.tiles.contains_key(&tile.id));
+            used.insert(tile.id);
+        }
+
+        for item in scenario.inventory.keys() {
+            v.check(path, "item", *item, registry.items.contains_key(item));
+            used.insert(*item);
+        }
+
+        for tile in scenario.allowed_tiles.iter().flatten() {
+            v.check_known(path, "allowed tile or tag", *tile);
+        }
+
+        for objective in &scenario.objectives {
+            match *objective {
+                Objective::Deliver { item, to, .. } => {
+                    v.check_known(path, "objective item or tag", item);
+                    v.check_known(path, "objective tile or tag", to);
+                    used.insert(item);
+                }
+                Objective::Place { tile, .. } => {
+                    v.check_known(path, "objective tile or tag", tile);
+                }
+            }
+        }
+
+        for key in [scenario.name, scenario.description] {
+            if !resource_man.translates.gui.contains_key(&key) {
+                v.push(
+                    Severity::Warning,
+                    path,
+                    format!("scenario key {} has no translation", v.name(key)),
+                );
+            }
+        }
+    }
+
+    for deposit in registry.deposits.values() {
+        let path = sources.deposits.get(&deposit.id);
+
+        v.check(
+            path,
+            "deposit tile",
+            deposit.tile,
+            registry.tiles.contains_key(&deposit.tile),
+        );
+
+        if deposit.weight == 0 {
+            v.push(
+                Severity::Warning,
+                path,
+                format!(
+                    "deposit {} has a weight of 0, and is never placed",
+                    v.name(deposit.id)
+                ),
+            );
+        }
+    }
+
+    for (id, (_, scope)) in &resource_man.functions {
+        let path = sources.functions.get(id);
+
+        let mut declared = HashSet::new();
+        for (key, _, value) in scope.iter() {
+            declared.insert(key.to_string());
+
+            if let Some(dep) = value.try_cast::<Id>() {
+                v.check_known(path, "id_deps entry", dep);
+                used.insert(dep);
+            }
+        }
+
+        let Some(source) = path.and_then(|path| read_to_string(path).ok()) else {
+            continue;
+        };
+        let mut constants = constants_used(&source).into_iter().collect::<Vec<_>>();
+        constants.sort();
+
+        for constant in constants {
+            if !declared.contains(&constant) {
+                v.push(
+                    Severity::Error,
+                    path,
+                    format!("{constant} is used but not declared in id_deps"),
+                );
+            }
+        }
+    }
+
+    for id in registry.gui_ids.all() {
+        if !resource_man.translates.gui.contains_key(&id) {
+            v.push(
+                Severity::Warning,
+                None,
+                format!("gui key {} has no translation", v.name(id)),
+            );
+        }
+    }
+
+    for id in registry.err_ids.all() {
+        if !resource_man.translates.error.contains_key(&id) {
+            v.push(
+                Severity::Warning,
+                None,
+                format!("error key {} has no translation", v.name(id)),
+            );
+        }
+    }
+
+    for (id, path) in &sources.functions {
+        if !used.contains(id) {
+            v.push(
+                Severity::Warning,
+                Some(path),
+                format!("function {} is never used by a tile", v.name(*id)),
+            );
+        }
+    }
+
+    for (id, path) in &sources.models {
+        if !used.contains(id) {
+            v.push(
+                Severity::Warning,
+                Some(path),
+                format!("model {} is never used", v.name(*id)),
+            );
+        }
+    }
+
+    for (id, path) in &sources.items {
+        if !used.contains(id) {
+            v.push(
+                Severity::Warning,
+                Some(path),
+                format!("item {} is never used", v.name(*id)),
+            );
+        }
+    }
+
+    let mut issues = v.issues;
+    issues.sort_by(|a, b| a.path.cmp(&b.path).then(a.message.cmp(&b.message)));
+
+    issues
+}
+
+/// Loads all the namespaces in the given resources directory, then validates them. Files that fail to load are reported as errors.
+pub fn load_and_validate(
+    resource_man: &mut ResourceManager,
+    path: &Path,
+    language: &str,
+) -> Vec<Issue> {
+    if let Err(err) = resource_man.load_all(path, language) {
+        return vec![Issue {
+            severity: Severity::Error,
+            path: Some(path.to_path_buf()),
+            message: format!("{err:#}"),
+        }];
+    }
+
+    resource_man.compile();
+
+    let mut issues = resource_man
+        .load_errors
+        .iter()
+        .map(|err| Issue {
+            severity: Severity::Error,
+            path: Some(err.path.clone()),
+            message: match err.position {
+                Some((line, column)) => format!("{line}:{column}: {}", err.message),
+                None => err.message.clone(),
+            },
+        })
+        .collect::<Vec<_>>();
+
+    issues.extend(validate(resource_man));
+
+    issues
+}
@@ -31,8 +31,8 @@ use automancy_resources::data::Data;
 use automancy_resources::kira::tween::Tween;
 
 use crate::gui::{
-    error, info, menu, player, popup, tile_config, tile_selection, GuiState, PopupState, Screen,
-    TextField,
+    error, info, logistics, menu, player, popup, scenario, statistics, tile_config, tile_selection,
+    GuiState, PopupState, Screen, TextField,
 };
 use crate::renderer::Renderer;
 use crate::setup::GameSetup;
@@ -94,6 +94,11 @@ pub fn shutdown_graceful(
     setup: &mut GameSetup,
     control_flow: &mut ControlFlow,
 ) -> anyhow::Result<bool> {
+    #[cfg(unix)]
+    if let Some(control) = setup.control.take() {
+        block_on(control.stop());
+    }
+
     setup.game.send_message(GameMsg::StopTicking)?;
 
     block_on(setup.game.call(
@@ -175,9 +180,21 @@ fn render(
                             player::player(setup, loop_store, &mut item_instances, &gui.context);
                         }
 
+                        if setup.input_handler.key_active(KeyActions::Statistics) {
+                            statistics::statistics(setup, loop_store, &gui.context);
+                        }
+
+                        if setup.input_handler.key_active(KeyActions::Logistics) {
+                            logistics::logistics(setup, &gui.context);
+                        }
+
                         // tile_info
                         info::info(setup, &mut item_instances, &gui.context);
 
+                        // scenario objectives
+                        scenario::objectives(setup, &gui.context, &game_data);
+                        scenario::check_completed(loop_store, &game_data, setup);
+
                         // tile_config
                         tile_config::tile_config(
                             setup,
@@ -284,9 +301,15 @@ fn render(
             PopupState::MapDeleteConfirmation(map_name) => {
                 popup::map_delete_popup(setup, gui, loop_store, &map_name);
             }
+            PopupState::AddDepositsConfirmation => {
+                popup::add_deposits_popup(setup, gui, loop_store);
+            }
             PopupState::InvalidName => {
                 popup::invalid_name_popup(setup, gui, loop_store);
             }
+            PopupState::ScenarioComplete => {
+                scenario::scenario_complete_popup(setup, gui, loop_store);
+            }
         }
 
         tile_tints.insert(setup.camera.pointing_at, colors::RED.with_alpha(0.2));
@@ -534,17 +557,20 @@ pub fn on_event(
                         .unwrap();
 
                         if old.is_some() {
-                            tile_entity.send_message(TileEntityMsg::RemoveData(
-                                resource_man.registry.data_ids.link,
-                            ))?;
+                            setup.game.send_message(GameMsg::SetTileData {
+                                coord: setup.camera.pointing_at,
+                                key: resource_man.registry.data_ids.link,
+                                value: None,
+                            })?;
 
                             setup.audio_man.play(resource_man.audio["click"].clone())?;
                             // TODO click2
                         } else {
-                            tile_entity.send_message(TileEntityMsg::SetDataValue(
-                                resource_man.registry.data_ids.link,
-                                Data::Coord(linking_tile),
-                            ))?;
+                            setup.game.send_message(GameMsg::SetTileData {
+                                coord: setup.camera.pointing_at,
+                                key: resource_man.registry.data_ids.link,
+                                value: Some(Data::Coord(linking_tile)),
+                            })?;
 
                             setup.audio_man.play(resource_man.audio["click"].clone())?;
                         }
@@ -567,6 +593,7 @@ pub fn on_event(
                     .clear();
             } else {
                 loop_store.config_open = Some(setup.camera.pointing_at);
+                loop_store.gui_state.selected_slot = None;
             }
         }
 
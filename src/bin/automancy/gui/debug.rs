@@ -1,7 +1,9 @@
-use egui::{Context, Window};
+use egui::{Context, Grid, ScrollArea, Ui, Window};
 use futures::executor::block_on;
 
 use automancy::game::GameMsg;
+use automancy::profiler::{ProfileColumn, Profiler};
+use automancy_defs::log;
 
 use crate::event::EventLoopStorage;
 use crate::gui::default_frame;
@@ -27,8 +29,14 @@ pub fn debugger(setup: &GameSetup, loop_store: &mut EventLoopStorage, context: &
 
     let tile_count = info.tile_count;
 
+    let profiler = block_on(setup.game.call(GameMsg::GetProfiler, None))
+        .unwrap()
+        .unwrap();
+
     Window::new(
-        setup.resource_man.translates.gui[&resource_man.registry.gui_ids.debug_menu].as_str(),
+        setup
+            .resource_man
+            .gui_str(&resource_man.registry.gui_ids.debug_menu),
     )
     .resizable(false)
     .default_width(600.0)
@@ -41,6 +49,109 @@ pub fn debugger(setup: &GameSetup, loop_store: &mut EventLoopStorage, context: &
         ));
         ui.label(format!(
             "Map \"{map_name}\" ({map_name}.run): {tile_count}T"
-        ))
+        ));
+
+        ui.separator();
+        profile(setup, loop_store, &profiler, ui);
+    });
+}
+
+/// Draws the profiler's controls, a summary of the last ticks, and the table of handler timings.
+fn profile(setup: &GameSetup, loop_store: &mut EventLoopStorage, profiler: &Profiler, ui: &mut Ui) {
+    let gui_state = &mut loop_store.gui_state;
+
+    ui.horizontal(|ui| {
+        let mut enabled = profiler.is_enabled();
+        if ui.checkbox(&mut enabled, "Profile ticks").changed() {
+            profiler.set_enabled(enabled);
+        }
+
+        if ui.button("Reset").clicked() {
+            profiler.reset();
+        }
+
+        if ui.button("Export").clicked() {
+            gui_state.profile_export = Some(match profiler.export(&setup.resource_man) {
+                Ok(path) => format!("Exported to {}", path.display()),
+                Err(err) => {
+                    log::error!("Could not export the profile: {err:#}");
+
+                    format!("Could not export: {err}")
+                }
+            });
+        }
+    });
+
+    if let Some(export) = &gui_state.profile_export {
+        ui.label(export);
+    }
+
+    let mut report = profiler.report(&setup.resource_man);
+
+    if let Some(last) = report.ticks.last() {
+        let count = report.ticks.len() as u64;
+        let handler_us = report.ticks.iter().map(|tick| tick.handler_us).sum::<u64>() / count;
+        let transactions = report
+            .ticks
+            .iter()
+            .map(|tick| tick.transactions)
+            .sum::<u64>()
+            / count;
+        let queue_depth = report
+            .ticks
+            .iter()
+            .map(|tick| tick.queue_depth)
+            .max()
+            .unwrap_or(0);
+
+        ui.label(format!(
+            "Tick {}: {}µs in handlers, {} transactions, {} queued",
+            last.tick, last.handler_us, last.transactions, last.queue_depth
+        ));
+        ui.label(format!(
+            "Last {count} ticks: {handler_us}µs in handlers and {transactions} transactions on average, at most {queue_depth} queued"
+        ));
+    }
+
+    let (column, descending) = &mut gui_state.profile_sort;
+    report.sort_handlers(*column, *descending);
+
+    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+        Grid::new("profile").striped(true).show(ui, |ui| {
+            for header in ProfileColumn::ALL {
+                let selected = *column == header;
+                let text = match (selected, *descending) {
+                    (true, true) => format!("{} ↓", header.name()),
+                    (true, false) => format!("{} ↑", header.name()),
+                    (false, _) => header.name().to_string(),
+                };
+
+                if ui.selectable_label(selected, text).clicked() {
+                    if selected {
+                        *descending = !*descending;
+                    } else {
+                        *column = header;
+                        *descending = true;
+                    }
+                }
+            }
+            ui.end_row();
+
+            for row in &report.handlers {
+                ui.label(row.tile.to_string());
+                ui.label(
+                    row.function
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                );
+                ui.label(row.handler.to_string());
+                ui.label(row.calls.to_string());
+                ui.label(row.total_us.to_string());
+                ui.label(row.mean_us.to_string());
+                ui.label(row.max_us.to_string());
+                ui.end_row();
+            }
+        });
     });
 }
@@ -11,7 +11,9 @@ use crate::setup::GameSetup;
 pub fn error_popup(setup: &mut GameSetup, gui: &mut Gui) {
     if let Some(error) = setup.resource_man.error_man.peek() {
         Window::new(
-            setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.error_popup]
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.error_popup)
                 .to_string(),
         )
         .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
@@ -26,8 +28,9 @@ pub fn error_popup(setup: &mut GameSetup, gui: &mut Gui) {
                 ui.horizontal(|ui| {
                     if ui
                         .button(
-                            setup.resource_man.translates.gui
-                                [&setup.resource_man.registry.gui_ids.btn_confirm]
+                            setup
+                                .resource_man
+                                .gui_str(&setup.resource_man.registry.gui_ids.btn_confirm)
                                 .to_string(),
                         )
                         .clicked()
@@ -6,16 +6,20 @@ use automancy::tile_entity::TileEntityMsg;
 use automancy_defs::colors;
 use automancy_resources::data::stack::ItemStack;
 use automancy_resources::data::Data;
+use automancy_resources::format::FormatArgs;
 
 use crate::gui::default_frame;
 use crate::gui::item::{draw_item, SMALL_ITEM_ICON_SIZE};
+use crate::gui::tile_config::draw_progress;
 use crate::renderer::GuiInstances;
 use crate::setup::GameSetup;
 
 /// Draws the info GUI.
 pub fn info(setup: &GameSetup, item_instances: &mut GuiInstances, context: &Context) {
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.info].as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.info),
     )
     .anchor(Align2::RIGHT_TOP, vec2(-10.0, 10.0))
     .resizable(false)
@@ -45,6 +49,20 @@ pub fn info(setup: &GameSetup, item_instances: &mut GuiInstances, context: &Cont
                 .unwrap()
                 .unwrap();
 
+            draw_progress(ui, &setup.resource_man, &data);
+
+            let registry = &setup.resource_man.registry;
+            if let Some(remaining) = data
+                .get(&registry.data_ids.remaining)
+                .or_else(|| registry.tile_data(id, registry.data_ids.remaining))
+                .and_then(Data::as_amount)
+            {
+                ui.label(setup.resource_man.gui_format(
+                    &registry.gui_ids.lbl_remaining,
+                    &FormatArgs::new().with("amount", *remaining),
+                ));
+            }
+
             if let Some(inventory) = data
                 .get(&setup.resource_man.registry.data_ids.buffer)
                 .and_then(Data::as_inventory)
@@ -0,0 +1,79 @@
+use egui::{CollapsingHeader, Context, Grid, ScrollArea, Window};
+use futures::executor::block_on;
+
+use automancy::game::GameMsg;
+
+use crate::gui::default_frame;
+use crate::setup::GameSetup;
+
+/// Draws the logistics window, with what the storages on each network have and the requests waiting on it.
+pub fn logistics(setup: &GameSetup, context: &Context) {
+    let resource_man = &setup.resource_man;
+    let gui_ids = &resource_man.registry.gui_ids;
+
+    let networks = block_on(setup.game.call(GameMsg::GetLogistics, None))
+        .unwrap()
+        .unwrap();
+
+    Window::new(resource_man.gui_str(&gui_ids.logistics))
+        .frame(default_frame())
+        .resizable(false)
+        .default_width(300.0)
+        .show(context, |ui| {
+            if networks.is_empty() {
+                ui.label(resource_man.gui_str(&gui_ids.lbl_no_networks));
+
+                return;
+            }
+
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (i, network) in networks.iter().enumerate() {
+                    let first = network.tiles[0];
+
+                    CollapsingHeader::new(format!(
+                        "#{} ({}, {}) - {}",
+                        i + 1,
+                        first.q(),
+                        first.r(),
+                        network.tiles.len()
+                    ))
+                    .id_source(("logistics", first.q(), first.r()))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.label(resource_man.gui_str(&gui_ids.lbl_network_contents));
+                        Grid::new(("logistics_contents", i))
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (id, amount) in network.contents.iter() {
+                                    if *amount <= 0 {
+                                        continue;
+                                    }
+
+                                    ui.label(resource_man.item_name(id));
+                                    ui.label(amount.to_string());
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.separator();
+
+                        ui.label(resource_man.gui_str(&gui_ids.lbl_requests));
+                        Grid::new(("logistics_requests", i))
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for request in &network.requests {
+                                    ui.label(format!(
+                                        "({}, {})",
+                                        request.requester.q(),
+                                        request.requester.r()
+                                    ));
+                                    ui.label(resource_man.item_name(&request.item));
+                                    ui.label(request.amount.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+            });
+        });
+}
@@ -14,7 +14,7 @@ use automancy::VERSION;
 use automancy_defs::flexstr::ToSharedStr;
 use automancy_defs::gui::HyperlinkWidget;
 use automancy_defs::log;
-use automancy_resources::{format, format_time};
+use automancy_resources::format::FormatArgs;
 
 use crate::event::{shutdown_graceful, EventLoopStorage};
 use crate::gui::{default_frame, OptionsMenuState, PopupState, Screen, SubState, TextField};
@@ -45,9 +45,9 @@ pub fn main_menu(
                     if ui
                         .button(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_play]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_play),
                             )
                             .heading(),
                         )
@@ -59,9 +59,9 @@ pub fn main_menu(
                     if ui
                         .button(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_options]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_options),
                             )
                             .heading(),
                         )
@@ -73,9 +73,9 @@ pub fn main_menu(
                     ui.add(HyperlinkWidget::new(
                         Button::new(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_fedi]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_fedi),
                             )
                             .heading(),
                         ),
@@ -84,9 +84,9 @@ pub fn main_menu(
                     ui.add(HyperlinkWidget::new(
                         Button::new(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_source]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_source),
                             )
                             .heading(),
                         ),
@@ -95,9 +95,9 @@ pub fn main_menu(
                     if ui
                         .button(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_exit]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_exit),
                             )
                             .heading(),
                         )
@@ -130,9 +130,9 @@ pub fn pause_menu(setup: &GameSetup, context: &Context, loop_store: &mut EventLo
                     if ui
                         .button(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_unpause]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_unpause),
                             )
                             .heading(),
                         )
@@ -143,9 +143,9 @@ pub fn pause_menu(setup: &GameSetup, context: &Context, loop_store: &mut EventLo
                     if ui
                         .button(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_options]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_options),
                             )
                             .heading(),
                         )
@@ -156,9 +156,9 @@ pub fn pause_menu(setup: &GameSetup, context: &Context, loop_store: &mut EventLo
                     if ui
                         .button(
                             RichText::new(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_exit]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_exit),
                             )
                             .heading(),
                         )
@@ -187,7 +187,9 @@ pub fn pause_menu(setup: &GameSetup, context: &Context, loop_store: &mut EventLo
 /// Draws the map loading menu.
 pub fn map_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut EventLoopStorage) {
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.load_map].as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.load_map),
     )
     .resizable(false)
     .collapsible(false)
@@ -257,19 +259,17 @@ pub fn map_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut Event
 
                     ui.horizontal(|ui| {
                         if let Some(save_time) = map_info.save_time {
-                            ui.label(format_time(
-                                save_time,
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.time_fmt]
-                                    .as_str(),
+                            ui.label(setup.resource_man.gui_format(
+                                &setup.resource_man.registry.gui_ids.lbl_saved_at,
+                                &FormatArgs::new().with("time", save_time),
                             ));
                         }
 
                         if ui
                             .button(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_load]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_load),
                             )
                             .clicked()
                         {
@@ -280,14 +280,22 @@ pub fn map_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut Event
                                     map_name.clone(),
                                 ))
                                 .unwrap();
+                            setup.game.send_message(GameMsg::StartRecording).unwrap();
                             loop_store.gui_state.switch_screen(Screen::Ingame);
+
+                            if block_on(setup.game.call(GameMsg::NeedsDeposits, None))
+                                .unwrap()
+                                .unwrap()
+                            {
+                                loop_store.gui_state.popup = PopupState::AddDepositsConfirmation;
+                            }
                         }
 
                         if ui
                             .button(
-                                setup.resource_man.translates.gui
-                                    [&setup.resource_man.registry.gui_ids.btn_delete]
-                                    .as_str(),
+                                setup
+                                    .resource_man
+                                    .gui_str(&setup.resource_man.registry.gui_ids.btn_delete),
                             )
                             .clicked()
                         {
@@ -304,18 +312,17 @@ pub fn map_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut Event
                 setup.refresh_maps();
             }
         });
-        ui.label(format(
-            setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.lbl_maps_loaded]
-                .as_str(),
-            &[setup.maps.len().to_string().as_str()],
+        ui.label(setup.resource_man.gui_format(
+            &setup.resource_man.registry.gui_ids.lbl_maps_loaded,
+            &FormatArgs::new().with("count", setup.maps.len()),
         ));
         ui.horizontal(|ui| {
             if ui
                 .button(
                     RichText::new(
-                        setup.resource_man.translates.gui
-                            [&setup.resource_man.registry.gui_ids.btn_new_map]
-                            .as_str(),
+                        setup
+                            .resource_man
+                            .gui_str(&setup.resource_man.registry.gui_ids.btn_new_map),
                     )
                     .heading(),
                 )
@@ -326,9 +333,9 @@ pub fn map_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut Event
             if ui
                 .button(
                     RichText::new(
-                        setup.resource_man.translates.gui
-                            [&setup.resource_man.registry.gui_ids.btn_cancel]
-                            .as_str(),
+                        setup
+                            .resource_man
+                            .gui_str(&setup.resource_man.registry.gui_ids.btn_cancel),
                     )
                     .heading(),
                 )
@@ -343,7 +350,9 @@ pub fn map_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut Event
 /// Draws the options menu. Returns whether or not the font should be reset (janky but it probably works)
 pub fn options_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut EventLoopStorage) {
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.options].as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.options),
     )
     .resizable(false)
     .collapsible(false)
@@ -489,6 +498,23 @@ pub fn options_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut E
                                         }
                                     });
                             });
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new("Language:"));
+                                ComboBox::from_id_source("language")
+                                    .width(175.0)
+                                    .selected_text(&setup.options.gui.language)
+                                    .show_ui(ui, |ui| {
+                                        for language in &setup.resource_man.languages {
+                                            ui.selectable_value(
+                                                &mut setup.options.gui.language,
+                                                language.to_string(),
+                                                language.as_str(),
+                                            );
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text("Takes effect after restarting the game.");
+                            });
                         });
                     }
                     OptionsMenuState::Controls => {
@@ -499,8 +525,9 @@ pub fn options_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut E
         });
         if ui
             .button(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.btn_confirm]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_confirm),
             )
             .clicked()
         {
@@ -508,7 +535,7 @@ pub fn options_menu(setup: &mut GameSetup, context: &Context, loop_store: &mut E
                 setup.resource_man.error_man.push(
                     (
                         setup.resource_man.registry.err_ids.unwritable_options,
-                        vec![],
+                        FormatArgs::new(),
                     ),
                     &setup.resource_man,
                 );
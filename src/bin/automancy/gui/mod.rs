@@ -4,6 +4,9 @@ use enum_map::{enum_map, Enum, EnumMap};
 use fuse_rust::Fuse;
 use std::sync::Arc;
 
+use automancy::profiler::ProfileColumn;
+use automancy::statistics::StatWindow;
+use automancy::worldgen::WorldGenSettings;
 use automancy_defs::colors;
 use automancy_defs::gui::Gui;
 use automancy_defs::id::Id;
@@ -15,9 +18,12 @@ pub mod debug;
 pub mod error;
 pub mod info;
 pub mod item;
+pub mod logistics;
 pub mod menu;
 pub mod player;
 pub mod popup;
+pub mod scenario;
+pub mod statistics;
 pub mod tile_config;
 pub mod tile_selection;
 
@@ -28,6 +34,20 @@ pub struct GuiState {
     pub show_debugger: bool,
     pub previous: Option<Screen>,
     pub text_field: TextFieldState,
+    /// the scenario picked for the map being created
+    pub selected_scenario: Option<Id>,
+    /// the world generation parameters for the map being created
+    pub worldgen: WorldGenSettings,
+    /// the column the profiler's table is sorted by, and whether it is descending
+    pub profile_sort: (ProfileColumn, bool),
+    /// the outcome of the last profile export
+    pub profile_export: Option<String>,
+    /// the window the statistics are shown over
+    pub stat_window: StatWindow,
+    /// the item the statistics window graphs
+    pub stat_item: Option<Id>,
+    /// the slot or filter of the configured tile whose filter is being picked, or none to add one
+    pub selected_slot: Option<usize>,
 }
 
 /// The state of the main game GUI.
@@ -61,7 +81,9 @@ pub enum PopupState {
     None,
     MapCreate,
     MapDeleteConfirmation(String),
+    AddDepositsConfirmation,
     InvalidName,
+    ScenarioComplete,
 }
 
 /// Creates a default frame.
@@ -85,6 +107,13 @@ impl Default for GuiState {
             show_debugger: false,
             previous: None,
             text_field: Default::default(),
+            selected_scenario: None,
+            worldgen: Default::default(),
+            profile_sort: (ProfileColumn::Total, true),
+            profile_export: None,
+            stat_window: StatWindow::Minute,
+            stat_item: None,
+            selected_slot: None,
         }
     }
 }
@@ -126,6 +155,7 @@ pub enum TextField {
     Filter,
     MapRenaming,
     MapName,
+    Signal,
 }
 
 pub struct TextFieldState {
@@ -142,7 +172,8 @@ impl Default for TextFieldState {
             fields: enum_map! {
                 TextField::Filter => String::new(),
                 TextField::MapName => String::new(),
-                TextField::MapRenaming => String::new()
+                TextField::MapRenaming => String::new(),
+                TextField::Signal => String::new()
             },
         }
     }
@@ -70,17 +70,18 @@ pub fn player(
     context: &Context,
 ) {
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.player_menu]
-            .as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.player_menu),
     )
     .frame(default_frame())
     .resizable(false)
     .collapsible(false)
     .show(context, |ui| {
         ui.label(
-            setup.resource_man.translates.gui
-                [&setup.resource_man.registry.gui_ids.player_inventory]
-                .as_str(),
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.player_inventory),
         );
 
         if let Some(Data::Inventory(inventory)) = block_on(setup.game.call(
@@ -116,9 +117,9 @@ pub fn player(
 
         if ui
             .button(
-                setup.resource_man.translates.gui
-                    [&setup.resource_man.registry.gui_ids.open_research]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.open_research),
             )
             .clicked()
         {
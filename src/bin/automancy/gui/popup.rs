@@ -1,10 +1,12 @@
 use std::fs;
 
-use egui::{vec2, Align2, Window};
+use egui::{vec2, Align2, ComboBox, DragValue, Slider, Window};
+use rand::{thread_rng, Rng};
 
 use automancy::game::GameMsg;
 use automancy::map::Map;
 use automancy_defs::gui::Gui;
+use automancy_defs::id::Id;
 use automancy_defs::log;
 
 use crate::event::EventLoopStorage;
@@ -13,8 +15,9 @@ use crate::setup::GameSetup;
 
 pub fn invalid_name_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut EventLoopStorage) {
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.invalid_name]
-            .as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.invalid_name),
     )
     .resizable(false)
     .collapsible(false)
@@ -23,14 +26,15 @@ pub fn invalid_name_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut Eve
     .frame(default_frame())
     .show(&gui.context, |ui| {
         ui.label(
-            setup.resource_man.translates.gui
-                [&setup.resource_man.registry.gui_ids.lbl_pick_another_name]
-                .as_str(),
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.lbl_pick_another_name),
         );
         if ui
             .button(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.btn_confirm]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_confirm),
             )
             .clicked()
         {
@@ -48,7 +52,9 @@ pub fn map_delete_popup(
     let mut dirty = false;
 
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.delete_map].as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.delete_map),
     )
     .resizable(false)
     .collapsible(false)
@@ -57,14 +63,15 @@ pub fn map_delete_popup(
     .frame(default_frame())
     .show(&gui.context, |ui| {
         ui.label(
-            setup.resource_man.translates.gui
-                [&setup.resource_man.registry.gui_ids.lbl_delete_map_confirm]
-                .as_str(),
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.lbl_delete_map_confirm),
         );
         if ui
             .button(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.btn_confirm]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_confirm),
             )
             .clicked()
         {
@@ -75,8 +82,9 @@ pub fn map_delete_popup(
         }
         if ui
             .button(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.btn_cancel]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_cancel),
             )
             .clicked()
         {
@@ -89,10 +97,54 @@ pub fn map_delete_popup(
     }
 }
 
+/// Draws the popup asking whether to add deposits to a map saved before they existed.
+pub fn add_deposits_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut EventLoopStorage) {
+    Window::new(
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.add_deposits),
+    )
+    .resizable(false)
+    .collapsible(false)
+    .default_width(250.0)
+    .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+    .frame(default_frame())
+    .show(&gui.context, |ui| {
+        ui.label(
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.lbl_add_deposits_confirm),
+        );
+        if ui
+            .button(
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_confirm),
+            )
+            .clicked()
+        {
+            setup.game.send_message(GameMsg::MigrateDeposits).unwrap();
+            loop_store.gui_state.popup = PopupState::None;
+        }
+        if ui
+            .button(
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_cancel),
+            )
+            .clicked()
+        {
+            loop_store.gui_state.popup = PopupState::None
+        }
+    });
+}
+
 /// Draws the map creation popup.
 pub fn map_create_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut EventLoopStorage) {
     Window::new(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.create_map].as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.create_map),
     )
     .resizable(false)
     .collapsible(false)
@@ -104,10 +156,87 @@ pub fn map_create_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut Event
             ui.label("Name:"); //TODO add this to translation
             ui.text_edit_singleline(loop_store.gui_state.text_field.get(TextField::MapName));
         });
+        ui.horizontal(|ui| {
+            ui.label(
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.lbl_scenario),
+            );
+
+            let scenario_name = |id: Option<Id>| match id {
+                Some(id) => setup.resource_man.scenario_name(&id),
+                None => setup.resource_man.translates.none.as_str(),
+            };
+
+            let mut scenarios = setup
+                .resource_man
+                .registry
+                .scenarios
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            scenarios.sort_by_key(|id| scenario_name(Some(*id)));
+
+            ComboBox::from_id_source("scenario")
+                .selected_text(scenario_name(loop_store.gui_state.selected_scenario))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut loop_store.gui_state.selected_scenario,
+                        None,
+                        scenario_name(None),
+                    );
+
+                    for id in scenarios {
+                        ui.selectable_value(
+                            &mut loop_store.gui_state.selected_scenario,
+                            Some(id),
+                            scenario_name(Some(id)),
+                        );
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label(
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.lbl_seed),
+            );
+            ui.add(DragValue::new(&mut loop_store.gui_state.worldgen.seed));
+            if ui
+                .button(
+                    setup
+                        .resource_man
+                        .gui_str(&setup.resource_man.registry.gui_ids.btn_randomize),
+                )
+                .clicked()
+            {
+                loop_store.gui_state.worldgen.seed = thread_rng().gen();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.lbl_world_radius),
+            );
+            ui.add(Slider::new(
+                &mut loop_store.gui_state.worldgen.radius,
+                8..=128,
+            ));
+        });
+        ui.horizontal(|ui| {
+            ui.label(
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.lbl_deposit_density),
+            );
+            ui.add(Slider::new(&mut loop_store.gui_state.worldgen.density, 0..=30).suffix("%"));
+        });
         if ui
             .button(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.btn_confirm]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_confirm),
             )
             .clicked()
         {
@@ -122,6 +251,17 @@ pub fn map_create_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut Event
                 .game
                 .send_message(GameMsg::LoadMap(setup.resource_man.clone(), name))
                 .unwrap();
+            if let Some(scenario) = loop_store.gui_state.selected_scenario.take() {
+                setup
+                    .game
+                    .send_message(GameMsg::StartScenario(scenario))
+                    .unwrap();
+            }
+            setup
+                .game
+                .send_message(GameMsg::GenerateWorld(loop_store.gui_state.worldgen))
+                .unwrap();
+            setup.game.send_message(GameMsg::StartRecording).unwrap();
             loop_store
                 .gui_state
                 .text_field
@@ -132,8 +272,9 @@ pub fn map_create_popup(setup: &GameSetup, gui: &mut Gui, loop_store: &mut Event
         }
         if ui
             .button(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.btn_cancel]
-                    .as_str(),
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.btn_cancel),
             )
             .clicked()
         {
@@ -0,0 +1,153 @@
+use egui::{vec2, Align2, Context, ProgressBar, Window};
+use futures::executor::block_on;
+
+use automancy::game::GameMsg;
+use automancy_defs::gui::Gui;
+use automancy_resources::data::{Data, DataMap};
+use automancy_resources::format::FormatArgs;
+use automancy_resources::types::scenario::Objective;
+use automancy_resources::ResourceManager;
+
+use crate::event::EventLoopStorage;
+use crate::gui::{default_frame, PopupState};
+use crate::setup::GameSetup;
+
+/// Describes an objective, e.g. "Store 100 Philosopher's Stone in Small Storage".
+fn objective_str(resource_man: &ResourceManager, objective: &Objective) -> String {
+    match *objective {
+        Objective::Deliver { item, amount, to } => resource_man.gui_format(
+            &resource_man.registry.gui_ids.lbl_objective_deliver,
+            &FormatArgs::new()
+                .with("amount", amount)
+                .with("item", resource_man.item_name(&item))
+                .with("tile", resource_man.tile_name(&to)),
+        ),
+        Objective::Place { tile, amount } => resource_man.gui_format(
+            &resource_man.registry.gui_ids.lbl_objective_place,
+            &FormatArgs::new()
+                .with("amount", amount)
+                .with("tile", resource_man.tile_name(&tile)),
+        ),
+    }
+}
+
+/// Draws the objectives of the map's scenario, if it has one.
+pub fn objectives(setup: &GameSetup, context: &Context, game_data: &DataMap) {
+    let resource_man = &setup.resource_man;
+    let data_ids = resource_man.registry.data_ids;
+
+    let Some(scenario) = game_data
+        .get(&data_ids.scenario)
+        .and_then(Data::as_id)
+        .and_then(|id| resource_man.registry.scenario(*id))
+    else {
+        return;
+    };
+
+    let progress = game_data
+        .get(&data_ids.objective_progress)
+        .and_then(Data::as_vec_amount)
+        .cloned()
+        .unwrap_or_default();
+
+    Window::new(resource_man.gui_str(&resource_man.registry.gui_ids.objectives))
+        .anchor(Align2::LEFT_TOP, vec2(10.0, 10.0))
+        .resizable(false)
+        .default_width(250.0)
+        .frame(default_frame())
+        .show(context, |ui| {
+            ui.heading(resource_man.gui_str(&scenario.name));
+            ui.label(resource_man.gui_str(&scenario.description));
+            ui.separator();
+
+            for (i, objective) in scenario.objectives.iter().enumerate() {
+                let required = objective.required();
+                let progress = progress.get(i).cloned().unwrap_or(0).min(required);
+
+                ui.label(objective_str(resource_man, objective));
+                ui.add(
+                    ProgressBar::new(progress as f32 / required.max(1) as f32).text(
+                        resource_man.gui_format(
+                            &resource_man.registry.gui_ids.lbl_progress,
+                            &FormatArgs::new()
+                                .with("progress", progress)
+                                .with("duration", required),
+                        ),
+                    ),
+                );
+            }
+        });
+}
+
+/// Checks if the map's scenario was just completed, and shows the completion screen if so.
+pub fn check_completed(loop_store: &mut EventLoopStorage, game_data: &DataMap, setup: &GameSetup) {
+    let data_ids = setup.resource_man.registry.data_ids;
+
+    let completed = game_data
+        .get(&data_ids.scenario_completed)
+        .and_then(Data::as_bool)
+        .cloned()
+        .unwrap_or(false);
+    let acknowledged = game_data
+        .get(&data_ids.scenario_acknowledged)
+        .and_then(Data::as_bool)
+        .cloned()
+        .unwrap_or(false);
+
+    if completed && !acknowledged {
+        loop_store.gui_state.popup = PopupState::ScenarioComplete;
+    }
+}
+
+/// Draws the scenario completion screen.
+pub fn scenario_complete_popup(
+    setup: &GameSetup,
+    gui: &mut Gui,
+    loop_store: &mut EventLoopStorage,
+) {
+    let resource_man = &setup.resource_man;
+
+    let scenario = block_on(setup.game.call(
+        |reply| GameMsg::GetDataValue(resource_man.registry.data_ids.scenario, reply),
+        None,
+    ))
+    .unwrap()
+    .unwrap()
+    .as_ref()
+    .and_then(Data::as_id)
+    .cloned();
+
+    Window::new(resource_man.gui_str(&resource_man.registry.gui_ids.scenario_complete))
+        .resizable(false)
+        .collapsible(false)
+        .default_width(300.0)
+        .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+        .frame(default_frame())
+        .show(&gui.context, |ui| {
+            ui.label(
+                resource_man.gui_format(
+                    &resource_man.registry.gui_ids.lbl_scenario_complete,
+                    &FormatArgs::new().with(
+                        "name",
+                        scenario
+                            .map(|id| resource_man.scenario_name(&id))
+                            .unwrap_or_default(),
+                    ),
+                ),
+            );
+
+            if ui
+                .button(resource_man.gui_str(&resource_man.registry.gui_ids.btn_continue))
+                .clicked()
+            {
+                setup
+                    .game
+                    .send_message(GameMsg::SetData(
+                        resource_man.registry.data_ids.scenario_acknowledged,
+                        Data::Bool(true),
+                    ))
+                    .unwrap();
+                loop_store.gui_state.popup = PopupState::None;
+            }
+        });
+}
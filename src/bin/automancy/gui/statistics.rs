@@ -0,0 +1,137 @@
+use egui::{pos2, vec2, Color32, Context, Grid, ScrollArea, Sense, Shape, Stroke, Ui, Window};
+use futures::executor::block_on;
+
+use automancy::game::GameMsg;
+use automancy::statistics::{ItemCounts, ItemStat, StatWindow, BUCKETS};
+
+use crate::event::EventLoopStorage;
+use crate::gui::default_frame;
+use crate::setup::GameSetup;
+
+fn stat_color(stat: ItemStat) -> Color32 {
+    match stat {
+        ItemStat::Produced => Color32::DARK_GREEN,
+        ItemStat::Consumed => Color32::DARK_RED,
+        ItemStat::Stored => Color32::DARK_BLUE,
+    }
+}
+
+/// Draws how much of an item was produced, consumed and stored in each bucket of a window, as lines. What was stored can be less than nothing, if more was taken out.
+fn graph(ui: &mut Ui, history: &[ItemCounts]) {
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 120.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let counts = || {
+        history
+            .iter()
+            .flat_map(|counts| ItemStat::ALL.map(|stat| counts.get(stat)))
+    };
+    let max = counts().max().unwrap_or(0).max(1);
+    // more can be taken out of storages than is put in
+    let min = counts().min().unwrap_or(0).min(0);
+
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::GRAY));
+
+    for stat in ItemStat::ALL {
+        let points = history
+            .iter()
+            .enumerate()
+            .map(|(i, counts)| {
+                pos2(
+                    rect.left() + rect.width() * i as f32 / (BUCKETS - 1) as f32,
+                    rect.bottom()
+                        - rect.height() * (counts.get(stat) - min) as f32 / (max - min) as f32,
+                )
+            })
+            .collect();
+
+        painter.add(Shape::line(points, Stroke::new(2.0, stat_color(stat))));
+    }
+
+    ui.label(format!("{min} - {max}"));
+}
+
+/// Draws the statistics window, with the items produced, consumed and stored over the picked window, and the graph of the picked item.
+pub fn statistics(setup: &GameSetup, loop_store: &mut EventLoopStorage, context: &Context) {
+    let resource_man = &setup.resource_man;
+    let gui_ids = &resource_man.registry.gui_ids;
+    let gui_state = &mut loop_store.gui_state;
+
+    let statistics = block_on(setup.game.call(GameMsg::GetStatistics, None))
+        .unwrap()
+        .unwrap();
+
+    Window::new(resource_man.gui_str(&gui_ids.statistics))
+        .frame(default_frame())
+        .resizable(false)
+        .default_width(400.0)
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                for (window, label) in [
+                    (StatWindow::Minute, gui_ids.lbl_last_minute),
+                    (StatWindow::TenMinutes, gui_ids.lbl_last_ten_minutes),
+                    (StatWindow::Hour, gui_ids.lbl_last_hour),
+                ] {
+                    if ui
+                        .selectable_label(
+                            gui_state.stat_window == window,
+                            resource_man.gui_str(&label),
+                        )
+                        .clicked()
+                    {
+                        gui_state.stat_window = window;
+                    }
+                }
+            });
+
+            let labels = [
+                (ItemStat::Produced, gui_ids.lbl_produced),
+                (ItemStat::Consumed, gui_ids.lbl_consumed),
+                (ItemStat::Stored, gui_ids.lbl_stored),
+            ];
+
+            if let Some(id) = gui_state.stat_item {
+                ui.label(resource_man.item_name(&id));
+                graph(ui, &statistics.history(gui_state.stat_window, id));
+
+                ui.horizontal(|ui| {
+                    for (stat, label) in labels {
+                        ui.colored_label(stat_color(stat), resource_man.gui_str(&label));
+                    }
+                });
+            }
+
+            let mut totals = statistics
+                .totals(gui_state.stat_window)
+                .into_iter()
+                .collect::<Vec<_>>();
+            totals.sort_by_key(|(id, _)| resource_man.item_name(id));
+
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                Grid::new("statistics").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    for (_, label) in labels {
+                        ui.label(resource_man.gui_str(&label));
+                    }
+                    ui.end_row();
+
+                    for (id, counts) in totals {
+                        if ui
+                            .selectable_label(
+                                gui_state.stat_item == Some(id),
+                                resource_man.item_name(&id),
+                            )
+                            .clicked()
+                        {
+                            gui_state.stat_item = Some(id);
+                        }
+
+                        for (stat, _) in labels {
+                            ui.label(counts.get(stat).to_string());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
@@ -1,11 +1,12 @@
 use std::time::Instant;
 
 use egui::Context;
-use egui::{vec2, DragValue, Margin, Ui, Window};
+use egui::{vec2, ComboBox, DragValue, Margin, ProgressBar, Ui, Window};
 use futures::executor::block_on;
-use ractor::ActorRef;
+use rhai::INT;
 
 use automancy::game::GameMsg;
+use automancy::signal::{Comparison, Condition};
 use automancy::tile_entity::TileEntityMsg;
 use automancy_defs::coord::{TileCoord, TileHex};
 use automancy_defs::hexagon_tiles::traits::HexDirection;
@@ -14,6 +15,7 @@ use automancy_defs::math::Float;
 use automancy_resources::data::inventory::Inventory;
 use automancy_resources::data::stack::ItemStack;
 use automancy_resources::data::{Data, DataMap};
+use automancy_resources::format::FormatArgs;
 use automancy_resources::types::tile::Tile;
 use automancy_resources::ResourceManager;
 
@@ -23,78 +25,228 @@ use crate::gui::TextField;
 use crate::renderer::GuiInstances;
 use crate::setup::GameSetup;
 
-/// Draws the direction selector.
-pub fn add_direction(ui: &mut Ui, target_coord: &mut Option<TileCoord>, n: usize) {
-    let coord = TileHex::NEIGHBORS[(n + 2) % 6];
-    let coord = Some(coord.into());
-
-    ui.selectable_value(
-        target_coord,
-        coord,
-        match n {
-            0 => "↗",
-            1 => "➡",
-            2 => "↘",
-            3 => "↙",
-            4 => "⬅",
-            5 => "↖",
-            _ => "",
-        },
+/// Draws the processing progress of the tile's current script, if there is one.
+pub fn draw_progress(ui: &mut Ui, resource_man: &ResourceManager, data: &DataMap) {
+    let Some(script) = data
+        .get(&resource_man.registry.data_ids.script)
+        .and_then(Data::as_id)
+        .and_then(|id| resource_man.registry.script(*id))
+    else {
+        return;
+    };
+
+    let duration = script.instructions.duration;
+    let progress = data
+        .get(&resource_man.registry.data_ids.progress)
+        .and_then(Data::as_amount)
+        .cloned()
+        .unwrap_or(0)
+        .min(duration);
+
+    ui.add(
+        ProgressBar::new(progress as Float / duration as Float)
+            .desired_width(200.0)
+            .text(
+                resource_man.gui_format(
+                    &resource_man.registry.gui_ids.lbl_progress,
+                    &FormatArgs::new()
+                        .with("progress", progress)
+                        .with("duration", duration),
+                ),
+            ),
     );
+
+    // the machine is holding its output, because where it goes refused it
+    if let Some(retry_in) = data
+        .get(&resource_man.registry.data_ids.retry_in)
+        .and_then(Data::as_amount)
+        .filter(|retry_in| **retry_in > 0)
+    {
+        ui.label(resource_man.gui_format(
+            &resource_man.registry.gui_ids.lbl_output_held,
+            &FormatArgs::new().with("ticks", *retry_in),
+        ));
+    }
 }
 
-fn config_target(
-    ui: &mut Ui,
-    setup: &GameSetup,
-    data: &DataMap,
-    tile_entity: ActorRef<TileEntityMsg>,
-) {
-    let current_target_coord = data
-        .get(&setup.resource_man.registry.data_ids.target)
-        .and_then(Data::as_coord)
-        .cloned();
-    let mut new_target_coord = current_target_coord;
+/// Gets the direction selected by the nth button of the direction selector, clockwise from the top right.
+fn direction(n: usize) -> TileCoord {
+    TileHex::NEIGHBORS[(n + 2) % 6].into()
+}
 
-    ui.label(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.tile_config_target]
-            .as_str(),
-    );
+fn direction_arrow(n: usize) -> &'static str {
+    match n {
+        0 => "↗",
+        1 => "➡",
+        2 => "↘",
+        3 => "↙",
+        4 => "⬅",
+        5 => "↖",
+        _ => "",
+    }
+}
+
+/// Draws the direction selector.
+pub fn add_direction(ui: &mut Ui, target_coord: &mut Option<TileCoord>, n: usize) {
+    ui.selectable_value(target_coord, Some(direction(n)), direction_arrow(n));
+}
 
+/// Draws the buttons of the direction selector around the one for no direction, in a hexagon.
+fn select_direction(ui: &mut Ui, coord: &mut Option<TileCoord>) {
     ui.vertical(|ui| {
         ui.horizontal(|ui| {
             ui.add_space(15.0);
-            add_direction(ui, &mut new_target_coord, 5);
-            add_direction(ui, &mut new_target_coord, 0);
+            add_direction(ui, coord, 5);
+            add_direction(ui, coord, 0);
         });
 
         ui.horizontal(|ui| {
-            add_direction(ui, &mut new_target_coord, 4);
-            ui.selectable_value(&mut new_target_coord, None, "❌");
-            add_direction(ui, &mut new_target_coord, 1);
+            add_direction(ui, coord, 4);
+            ui.selectable_value(coord, None, "❌");
+            add_direction(ui, coord, 1);
         });
 
         ui.horizontal(|ui| {
             ui.add_space(15.0);
-            add_direction(ui, &mut new_target_coord, 3);
-            add_direction(ui, &mut new_target_coord, 2);
+            add_direction(ui, coord, 3);
+            add_direction(ui, coord, 2);
         });
     });
+}
+
+/// Sets a value in the data map of the tile, or removes it if there is none. Goes through the game, so that it is recorded.
+fn set_tile_data(setup: &GameSetup, coord: TileCoord, key: Id, value: Option<Data>) {
+    setup
+        .game
+        .send_message(GameMsg::SetTileData { coord, key, value })
+        .unwrap();
+}
+
+fn config_target(ui: &mut Ui, setup: &GameSetup, data: &DataMap, coord: TileCoord) {
+    let current_target_coord = data
+        .get(&setup.resource_man.registry.data_ids.target)
+        .and_then(Data::as_coord)
+        .cloned();
+    let mut new_target_coord = current_target_coord;
+
+    ui.label(
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.tile_config_target),
+    );
+
+    select_direction(ui, &mut new_target_coord);
 
     if new_target_coord != current_target_coord {
         if let Some(target_coord) = new_target_coord {
-            tile_entity
-                .send_message(TileEntityMsg::SetDataValue(
-                    setup.resource_man.registry.data_ids.target,
-                    Data::Coord(target_coord),
-                ))
-                .unwrap();
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.target,
+                Some(Data::Coord(target_coord)),
+            );
         } else {
-            tile_entity
-                .send_message(TileEntityMsg::RemoveData(
-                    setup.resource_man.registry.data_ids.target,
-                ))
-                .unwrap();
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.target,
+                None,
+            );
         }
+
+        // the output is offered to the new target straight away
+        set_tile_data(
+            setup,
+            coord,
+            setup.resource_man.registry.data_ids.retry_in,
+            None,
+        );
+        set_tile_data(
+            setup,
+            coord,
+            setup.resource_man.registry.data_ids.retry_delay,
+            None,
+        );
+    }
+}
+
+/// Draws the editor of how a splitter shares items between its outputs: by weight, in turns or at random, and with an optional priority output.
+fn config_split(ui: &mut Ui, setup: &GameSetup, data: &DataMap, coord: TileCoord) {
+    let data_ids = &setup.resource_man.registry.data_ids;
+    let gui_ids = &setup.resource_man.registry.gui_ids;
+
+    let current_round_robin = data
+        .get(&data_ids.round_robin)
+        .and_then(Data::as_bool)
+        .cloned()
+        .unwrap_or(false);
+    let mut new_round_robin = current_round_robin;
+
+    ui.checkbox(
+        &mut new_round_robin,
+        setup.resource_man.gui_str(&gui_ids.lbl_round_robin),
+    );
+
+    if new_round_robin != current_round_robin {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.round_robin,
+            Some(Data::Bool(new_round_robin)),
+        );
+        set_tile_data(setup, coord, data_ids.split_index, None);
+    }
+
+    // the weights are kept by direction, in the order of the direction selector
+    let mut current_weights = data
+        .get(&data_ids.split_weights)
+        .and_then(Data::as_vec_amount)
+        .cloned()
+        .unwrap_or_default();
+    current_weights.resize(6, 1);
+    let mut new_weights = current_weights.clone();
+
+    ui.label(setup.resource_man.gui_str(&gui_ids.lbl_weights));
+    ui.vertical(|ui| {
+        for row in [[5, 0], [4, 1], [3, 2]] {
+            ui.horizontal(|ui| {
+                for n in row {
+                    ui.add(
+                        DragValue::new(&mut new_weights[n])
+                            .clamp_range(0..=16)
+                            .speed(0.1)
+                            .prefix(direction_arrow(n)),
+                    );
+                }
+            });
+        }
+    });
+
+    if new_weights != current_weights {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.split_weights,
+            Some(Data::VecAmount(new_weights)),
+        );
+    }
+
+    let current_priority = data
+        .get(&data_ids.priority)
+        .and_then(Data::as_coord)
+        .cloned();
+    let mut new_priority = current_priority;
+
+    ui.label(setup.resource_man.gui_str(&gui_ids.lbl_priority));
+    select_direction(ui, &mut new_priority);
+
+    if new_priority != current_priority {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.priority,
+            new_priority.map(Data::Coord),
+        );
     }
 }
 
@@ -108,8 +260,9 @@ fn config_linking(
 
     if ui
         .button(
-            setup.resource_man.translates.gui
-                [&setup.resource_man.registry.gui_ids.btn_link_network]
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.btn_link_network)
                 .to_string(),
         )
         .clicked()
@@ -117,17 +270,100 @@ fn config_linking(
         loop_store.linking_tile = Some(config_open);
     };
     ui.label(
-        setup.resource_man.translates.gui
-            [&setup.resource_man.registry.gui_ids.lbl_link_destination]
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.lbl_link_destination)
             .to_string(),
     );
 }
 
+/// Draws the editor of the tile's enable condition, which compares a signal on the network the tile is linked to.
+fn config_condition(
+    ui: &mut Ui,
+    setup: &GameSetup,
+    loop_store: &mut EventLoopStorage,
+    data: &DataMap,
+    coord: TileCoord,
+) {
+    let data_ids = setup.resource_man.registry.data_ids;
+
+    let current = Condition::from_data(&data_ids, data);
+    let mut new_signal = current.map(|v| v.signal);
+    let mut new_comparison = current.map_or(Comparison::Greater, |v| v.comparison);
+    let mut new_value = current.map_or(0, |v| v.value);
+
+    ui.horizontal(|ui| {
+        ui.label(
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.lbl_condition),
+        );
+
+        if let Some(signal) = new_signal {
+            ui.label(setup.resource_man.item_name(&signal));
+        }
+
+        ComboBox::from_id_source("condition_comparison")
+            .width(40.0)
+            .selected_text(new_comparison.symbol())
+            .show_ui(ui, |ui| {
+                for comparison in Comparison::ALL {
+                    ui.selectable_value(&mut new_comparison, comparison, comparison.symbol());
+                }
+            });
+
+        ui.add(DragValue::new(&mut new_value).speed(1.0));
+
+        if ui.button("❌").clicked() {
+            new_signal = None;
+        }
+    });
+
+    loop_store.gui_state.text_field.searchable_id(
+        ui,
+        &setup.resource_man,
+        setup.resource_man.ordered_items.as_slice(),
+        &mut new_signal,
+        TextField::Signal,
+        &ResourceManager::item_name,
+    );
+
+    if new_signal != current.map(|v| v.signal) {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.condition_signal,
+            new_signal.map(Data::Id),
+        );
+    }
+
+    if new_signal.is_none() {
+        return;
+    }
+
+    if Some(new_comparison) != current.map(|v| v.comparison) {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.condition_comparison,
+            Some(Data::Amount(new_comparison.to_amount())),
+        );
+    }
+    if Some(new_value) != current.map(|v| v.value) {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.condition_value,
+            Some(Data::Amount(new_value)),
+        );
+    }
+}
+
 fn config_amount(
     ui: &mut Ui,
     setup: &GameSetup,
     data: &DataMap,
-    tile_entity: ActorRef<TileEntityMsg>,
+    coord: TileCoord,
     tile_info: &Tile,
 ) {
     let current_amount = data
@@ -147,20 +383,21 @@ fn config_amount(
                 .clamp_range(0..=max_amount)
                 .speed(1.0)
                 .prefix(
-                    setup.resource_man.translates.gui
-                        [&setup.resource_man.registry.gui_ids.lbl_amount]
+                    setup
+                        .resource_man
+                        .gui_str(&setup.resource_man.registry.gui_ids.lbl_amount)
                         .to_string(),
                 ),
         );
     }
 
     if new_amount != current_amount {
-        tile_entity
-            .send_message(TileEntityMsg::SetDataValue(
-                setup.resource_man.registry.data_ids.amount,
-                Data::Amount(new_amount),
-            ))
-            .unwrap();
+        set_tile_data(
+            setup,
+            coord,
+            setup.resource_man.registry.data_ids.amount,
+            Some(Data::Amount(new_amount)),
+        );
     }
 }
 
@@ -171,8 +408,10 @@ fn takeable_item(
     item_instances: &mut GuiInstances,
     mut buffer: Inventory,
     game_data: &mut DataMap,
-    tile_entity: ActorRef<TileEntityMsg>,
+    coord: TileCoord,
 ) {
+    let mut taken = false;
+
     if let Some(inventory) = game_data
         .entry(setup.resource_man.registry.data_ids.player_inventory)
         .or_insert_with(Data::new_inventory)
@@ -193,6 +432,7 @@ fn takeable_item(
             if response.clicked() {
                 if let Some(amount) = buffer.take(id, amount) {
                     inventory.add(id, amount);
+                    taken = true;
                     loop_store
                         .take_item_animations
                         .entry(item)
@@ -203,12 +443,14 @@ fn takeable_item(
         }
     }
 
-    tile_entity
-        .send_message(TileEntityMsg::SetDataValue(
+    if taken {
+        set_tile_data(
+            setup,
+            coord,
             setup.resource_man.registry.data_ids.buffer, //TODO rename "tile config"
-            Data::Inventory(buffer),
-        ))
-        .unwrap();
+            Some(Data::Inventory(buffer)),
+        );
+    }
 }
 
 fn config_item(
@@ -218,7 +460,7 @@ fn config_item(
     item_instances: &mut GuiInstances,
     data: &DataMap,
     item_type: Id,
-    tile_entity: ActorRef<TileEntityMsg>,
+    coord: TileCoord,
     tile_info: &Tile,
 ) {
     let current_item = data
@@ -236,12 +478,12 @@ fn config_item(
 
     ui.horizontal(|ui| {
         ui.label(
-            setup.resource_man.translates.gui
-                [&setup.resource_man.registry.gui_ids.tile_config_item]
-                .as_str(),
+            setup
+                .resource_man
+                .gui_str(&setup.resource_man.registry.gui_ids.tile_config_item),
         );
 
-        config_amount(ui, setup, data, tile_entity.clone(), tile_info);
+        config_amount(ui, setup, data, coord, tile_info);
     });
 
     if let Some(stack) = current_item
@@ -269,19 +511,301 @@ fn config_item(
 
     if new_item != current_item {
         if let Some(item) = new_item {
-            tile_entity
-                .send_message(TileEntityMsg::SetDataValue(
-                    setup.resource_man.registry.data_ids.item,
-                    Data::Id(item),
-                ))
-                .unwrap();
-            tile_entity
-                .send_message(TileEntityMsg::RemoveData(
-                    setup.resource_man.registry.data_ids.buffer,
-                ))
-                .unwrap();
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.item,
+                Some(Data::Id(item)),
+            );
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.buffer,
+                None,
+            );
+        }
+    }
+}
+
+/// Gets the name of an item, or of a tag by its ID, as tags have no translations.
+fn filter_name<'a>(resource_man: &'a ResourceManager, id: &Id) -> &'a str {
+    if resource_man.registry.items.contains_key(id) {
+        return resource_man.item_name(id);
+    }
+
+    match resource_man.interner.resolve(*id) {
+        Some(name) => name,
+        None => &resource_man.translates.unnamed,
+    }
+}
+
+/// Gets what a filter can be: anything, any of the tags with items, or an item.
+fn filter_options(resource_man: &ResourceManager) -> Vec<Id> {
+    let registry = &resource_man.registry;
+
+    let mut tags = registry
+        .tags
+        .iter()
+        .filter(|(_, tag)| tag.entries.iter().any(|id| registry.items.contains_key(id)))
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    tags.sort_by_key(|id| filter_name(resource_man, id));
+
+    [registry.any]
+        .into_iter()
+        .chain(tags)
+        .chain(resource_man.ordered_items.iter().cloned())
+        .collect()
+}
+
+/// Draws the editor of a sorter's filters, each with an item or tag and the direction the items matching it go, and of where the rest go.
+fn config_filters(
+    ui: &mut Ui,
+    setup: &GameSetup,
+    loop_store: &mut EventLoopStorage,
+    data: &DataMap,
+    coord: TileCoord,
+    tile_info: &Tile,
+) {
+    let resource_man = &setup.resource_man;
+    let registry = &resource_man.registry;
+    let data_ids = registry.data_ids;
+
+    let max_filters = tile_info
+        .data
+        .get(&data_ids.max_filters)
+        .and_then(Data::as_amount)
+        .cloned()
+        .unwrap_or(0) as usize;
+
+    let current_filters = data
+        .get(&data_ids.sort_filters)
+        .and_then(Data::as_vec_id)
+        .cloned()
+        .unwrap_or_default();
+    let current_outputs = data
+        .get(&data_ids.sort_outputs)
+        .and_then(Data::as_vec_coord)
+        .cloned()
+        .unwrap_or_default();
+    let mut filters = current_filters.clone();
+    let mut outputs = current_outputs.clone();
+    outputs.resize(filters.len(), direction(0));
+
+    let mut selected = loop_store
+        .gui_state
+        .selected_slot
+        .filter(|i| *i < filters.len());
+    let mut removed = None;
+
+    ui.label(resource_man.gui_str(&registry.gui_ids.tile_config_filters));
+
+    for (i, (filter, output)) in filters.iter().zip(outputs.iter_mut()).enumerate() {
+        ui.horizontal(|ui| {
+            if ui
+                .selectable_label(selected == Some(i), filter_name(resource_man, filter))
+                .clicked()
+            {
+                selected = if selected == Some(i) { None } else { Some(i) };
+            }
+
+            let current = (0..6).find(|n| direction(*n) == *output).unwrap_or(0);
+
+            ComboBox::from_id_source(("sort_output", i))
+                .width(40.0)
+                .selected_text(direction_arrow(current))
+                .show_ui(ui, |ui| {
+                    for n in 0..6 {
+                        ui.selectable_value(output, direction(n), direction_arrow(n));
+                    }
+                });
+
+            if ui.button("❌").clicked() {
+                removed = Some(i);
+            }
+        });
+    }
+
+    if let Some(i) = removed {
+        filters.remove(i);
+        outputs.remove(i);
+        selected = None;
+    }
+
+    // picking a filter changes the selected one, or adds one going the first way
+    if selected.is_some() || filters.len() < max_filters {
+        let options = filter_options(resource_man);
+        let mut picked = None;
+
+        loop_store.gui_state.text_field.searchable_id(
+            ui,
+            resource_man,
+            options.as_slice(),
+            &mut picked,
+            TextField::Filter,
+            &filter_name,
+        );
+
+        match (selected, picked) {
+            (Some(i), Some(picked)) => filters[i] = picked,
+            (None, Some(picked)) => {
+                filters.push(picked);
+                outputs.push(direction(0));
+            }
+            _ => {}
+        }
+    }
+
+    loop_store.gui_state.selected_slot = selected;
+
+    if filters != current_filters {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.sort_filters,
+            Some(Data::VecId(filters)),
+        );
+    }
+    if outputs != current_outputs {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.sort_outputs,
+            Some(Data::VecCoord(outputs)),
+        );
+    }
+
+    let current_overflow = data
+        .get(&data_ids.overflow)
+        .and_then(Data::as_coord)
+        .cloned();
+    let mut new_overflow = current_overflow;
+
+    ui.label(resource_man.gui_str(&registry.gui_ids.lbl_overflow));
+    select_direction(ui, &mut new_overflow);
+
+    if new_overflow != current_overflow {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.overflow,
+            new_overflow.map(Data::Coord),
+        );
+    }
+}
+
+/// Draws the editor of a storage's slots, each with an item or tag filter, and a capacity.
+fn config_slots(
+    ui: &mut Ui,
+    setup: &GameSetup,
+    loop_store: &mut EventLoopStorage,
+    data: &DataMap,
+    coord: TileCoord,
+    tile_info: &Tile,
+) {
+    let resource_man = &setup.resource_man;
+    let registry = &resource_man.registry;
+    let data_ids = registry.data_ids;
+
+    let max_slots = tile_info
+        .data
+        .get(&data_ids.max_slots)
+        .and_then(Data::as_amount)
+        .cloned()
+        .unwrap_or(0) as usize;
+    let max_amount = tile_info
+        .data
+        .get(&data_ids.max_amount)
+        .and_then(Data::as_amount)
+        .cloned()
+        .unwrap_or(0);
+
+    let current_filters = data
+        .get(&data_ids.slots)
+        .and_then(Data::as_vec_id)
+        .cloned()
+        .unwrap_or_default();
+    let current_amounts = data
+        .get(&data_ids.slot_amounts)
+        .and_then(Data::as_vec_amount)
+        .cloned()
+        .unwrap_or_default();
+    let mut filters = current_filters.clone();
+    let mut amounts = current_amounts.clone();
+    amounts.resize(filters.len(), max_amount);
+
+    let mut selected = loop_store
+        .gui_state
+        .selected_slot
+        .filter(|i| *i < filters.len());
+    let mut removed = None;
+
+    ui.label(resource_man.gui_str(&registry.gui_ids.tile_config_slots));
+
+    for (i, (filter, amount)) in filters.iter().zip(amounts.iter_mut()).enumerate() {
+        ui.horizontal(|ui| {
+            if ui
+                .selectable_label(selected == Some(i), filter_name(resource_man, filter))
+                .clicked()
+            {
+                selected = if selected == Some(i) { None } else { Some(i) };
+            }
+
+            ui.add(
+                DragValue::new(amount)
+                    .clamp_range(0..=max_amount)
+                    .speed(1.0),
+            );
+
+            if ui.button("❌").clicked() {
+                removed = Some(i);
+            }
+        });
+    }
+
+    if let Some(i) = removed {
+        filters.remove(i);
+        amounts.remove(i);
+        selected = None;
+    }
+
+    // picking a filter changes the selected slot's, or adds a slot with it
+    if selected.is_some() || filters.len() < max_slots {
+        let options = filter_options(resource_man);
+        let mut picked = None;
+
+        loop_store.gui_state.text_field.searchable_id(
+            ui,
+            resource_man,
+            options.as_slice(),
+            &mut picked,
+            TextField::Filter,
+            &filter_name,
+        );
+
+        match (selected, picked) {
+            (Some(i), Some(picked)) => filters[i] = picked,
+            (None, Some(picked)) => {
+                filters.push(picked);
+                amounts.push(max_amount);
+            }
+            _ => {}
         }
     }
+
+    loop_store.gui_state.selected_slot = selected;
+
+    if filters != current_filters {
+        set_tile_data(setup, coord, data_ids.slots, Some(Data::VecId(filters)));
+    }
+    if amounts != current_amounts {
+        set_tile_data(
+            setup,
+            coord,
+            data_ids.slot_amounts,
+            Some(Data::VecAmount(amounts)),
+        );
+    }
 }
 
 fn config_script(
@@ -291,7 +815,7 @@ fn config_script(
     item_instances: &mut GuiInstances,
     data: &DataMap,
     scripts: &Vec<Id>,
-    tile_entity: ActorRef<TileEntityMsg>,
+    coord: TileCoord,
 ) {
     let current_script = data
         .get(&setup.resource_man.registry.data_ids.script)
@@ -300,8 +824,9 @@ fn config_script(
     let mut new_script = current_script;
 
     ui.label(
-        setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.tile_config_script]
-            .as_str(),
+        setup
+            .resource_man
+            .gui_str(&setup.resource_man.registry.gui_ids.tile_config_script),
     );
 
     ui.vertical(|ui| {
@@ -321,6 +846,17 @@ fn config_script(
                 }
             }
 
+            for catalyst in &script.instructions.catalysts {
+                draw_item(
+                    &setup.resource_man,
+                    ui,
+                    item_instances,
+                    Some(" * "),
+                    *catalyst,
+                    SMALL_ITEM_ICON_SIZE,
+                );
+            }
+
             for output in &script.instructions.outputs {
                 draw_item(
                     &setup.resource_man,
@@ -331,9 +867,49 @@ fn config_script(
                     SMALL_ITEM_ICON_SIZE,
                 );
             }
+
+            let total_weight = script
+                .instructions
+                .weighted
+                .iter()
+                .map(|(_, weight)| *weight)
+                .sum::<INT>()
+                .max(1);
+
+            for (output, weight) in &script.instructions.weighted {
+                ui.horizontal(|ui| {
+                    draw_item(
+                        &setup.resource_man,
+                        ui,
+                        item_instances,
+                        Some("=> "),
+                        *output,
+                        SMALL_ITEM_ICON_SIZE,
+                    );
+                    ui.label(format!("({}%)", weight * 100 / total_weight));
+                });
+            }
+
+            for (byproduct, chance) in &script.instructions.byproducts {
+                ui.horizontal(|ui| {
+                    draw_item(
+                        &setup.resource_man,
+                        ui,
+                        item_instances,
+                        Some("=> "),
+                        *byproduct,
+                        SMALL_ITEM_ICON_SIZE,
+                    );
+                    ui.label(format!("({chance}%)"));
+                });
+            }
         }
     });
 
+    if new_script == current_script {
+        draw_progress(ui, &setup.resource_man, data);
+    }
+
     loop_store.gui_state.text_field.searchable_id(
         ui,
         &setup.resource_man,
@@ -345,17 +921,30 @@ fn config_script(
 
     if new_script != current_script {
         if let Some(script) = new_script {
-            tile_entity
-                .send_message(TileEntityMsg::SetDataValue(
-                    setup.resource_man.registry.data_ids.script,
-                    Data::Id(script),
-                ))
-                .unwrap();
-            tile_entity
-                .send_message(TileEntityMsg::RemoveData(
-                    setup.resource_man.registry.data_ids.buffer,
-                ))
-                .unwrap();
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.script,
+                Some(Data::Id(script)),
+            );
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.buffer,
+                None,
+            );
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.output_buffer,
+                None,
+            );
+            set_tile_data(
+                setup,
+                coord,
+                setup.resource_man.registry.data_ids.progress,
+                None,
+            );
         }
     }
 }
@@ -391,7 +980,9 @@ pub fn tile_config(
                 .unwrap();
 
             Window::new(
-                setup.resource_man.translates.gui[&setup.resource_man.registry.gui_ids.tile_config]
+                setup
+                    .resource_man
+                    .gui_str(&setup.resource_man.registry.gui_ids.tile_config)
                     .to_string(),
             )
             .resizable(false)
@@ -419,7 +1010,7 @@ pub fn tile_config(
                             item_instances,
                             &data,
                             scripts,
-                            tile_entity.clone(),
+                            config_open,
                         );
                     });
                     ui.add_space(MARGIN);
@@ -447,7 +1038,7 @@ pub fn tile_config(
                                 item_instances,
                                 buffer,
                                 game_data,
-                                tile_entity.clone(),
+                                config_open,
                             );
                         });
                         ui.add_space(MARGIN);
@@ -468,13 +1059,35 @@ pub fn tile_config(
                             item_instances,
                             &data,
                             item_type,
-                            tile_entity.clone(),
+                            config_open,
                             tile_info,
                         );
                     });
                     ui.add_space(MARGIN);
                 }
 
+                if tile_info
+                    .data
+                    .contains_key(&setup.resource_man.registry.data_ids.max_slots)
+                {
+                    ui.add_space(MARGIN);
+                    ui.vertical(|ui| {
+                        config_slots(ui, setup, loop_store, &data, config_open, tile_info);
+                    });
+                    ui.add_space(MARGIN);
+                }
+
+                if tile_info
+                    .data
+                    .contains_key(&setup.resource_man.registry.data_ids.max_filters)
+                {
+                    ui.add_space(MARGIN);
+                    ui.vertical(|ui| {
+                        config_filters(ui, setup, loop_store, &data, config_open, tile_info);
+                    });
+                    ui.add_space(MARGIN);
+                }
+
                 if !setup
                     .resource_man
                     .registry
@@ -485,7 +1098,37 @@ pub fn tile_config(
                 {
                     ui.add_space(MARGIN);
                     ui.vertical(|ui| {
-                        config_target(ui, setup, &data, tile_entity.clone());
+                        config_target(ui, setup, &data, config_open);
+                    });
+                    ui.add_space(MARGIN);
+                }
+
+                if setup
+                    .resource_man
+                    .registry
+                    .tile_data(id, setup.resource_man.registry.data_ids.weighted)
+                    .and_then(Data::as_bool)
+                    .cloned()
+                    .unwrap_or(false)
+                {
+                    ui.add_space(MARGIN);
+                    ui.vertical(|ui| {
+                        config_split(ui, setup, &data, config_open);
+                    });
+                    ui.add_space(MARGIN);
+                }
+
+                if setup
+                    .resource_man
+                    .registry
+                    .tile_data(id, setup.resource_man.registry.data_ids.conditional)
+                    .and_then(Data::as_bool)
+                    .cloned()
+                    .unwrap_or(false)
+                {
+                    ui.add_space(MARGIN);
+                    ui.vertical(|ui| {
+                        config_condition(ui, setup, loop_store, &data, config_open);
                     });
                     ui.add_space(MARGIN);
                 }
@@ -25,11 +25,23 @@ fn draw_tile_selection(
 ) {
     let size = ui.available_height();
 
+    let scenario = game_data
+        .get(&setup.resource_man.registry.data_ids.scenario)
+        .and_then(Data::as_id)
+        .and_then(|id| setup.resource_man.registry.scenario(*id));
+
     setup
         .resource_man
         .ordered_tiles
         .iter()
         .filter(|id| {
+            // the scenario only narrows down the tiles, which still have to be unlocked as usual
+            if let Some(scenario) = scenario {
+                if !scenario.allows(&setup.resource_man, **id) {
+                    return false;
+                }
+            }
+
             if Some(&true)
                 == setup
                     .resource_man
@@ -1,5 +1,7 @@
 use std::fmt::Write;
+use std::fs;
 use std::fs::File;
+use std::net::SocketAddr;
 use std::panic::PanicInfo;
 use std::path::Path;
 use std::{env, panic};
@@ -17,15 +19,19 @@ use winit::event_loop::EventLoop;
 use winit::window::{Fullscreen, Icon, WindowBuilder};
 
 use automancy::camera::Camera;
+use automancy::game::{GameMsg, TICK_INTERVAL};
 use automancy::gpu::Gpu;
 use automancy::input::KeyActions;
+use automancy::map::Map;
+use automancy::net::client::{self, Client, MIRROR_MAP};
+use automancy::replay::{self, Replay, REPLAY_MAP};
 use automancy_defs::flexstr::ToSharedStr;
 use automancy_defs::gui::init_gui;
 use automancy_defs::gui::set_font;
 use automancy_defs::{log, window};
 
 use crate::event::{on_event, EventLoopStorage};
-use crate::gui::init_fonts;
+use crate::gui::{init_fonts, Screen};
 use crate::renderer::Renderer;
 use crate::setup::GameSetup;
 
@@ -74,6 +80,76 @@ fn write_msg<P: AsRef<Path>>(buffer: &mut impl Write, file_path: P) -> std::fmt:
 
     Ok(())
 }
+/// Plays a replay back on a scratch map, at the speed the game normally runs at.
+fn play_replay(setup: &GameSetup, runtime: &Runtime, replay: Replay) {
+    // played back from an empty map, whatever was left behind last time
+    _ = fs::remove_dir_all(Map::path(REPLAY_MAP));
+
+    setup
+        .game
+        .send_message(GameMsg::LoadMap(
+            setup.resource_man.clone(),
+            REPLAY_MAP.to_string(),
+        ))
+        .unwrap();
+
+    let resource_man = setup.resource_man.clone();
+    let game = setup.game.clone();
+
+    runtime.spawn(async move {
+        match replay::play(resource_man, &game, &replay, Some(TICK_INTERVAL)).await {
+            Ok(report) => match report.divergences.first() {
+                Some(first) => log::warn!(
+                    "The replay diverged at tick {}, and {} times in total",
+                    first.tick,
+                    report.divergences.len()
+                ),
+                None => log::info!("The replay played back without diverging"),
+            },
+            Err(err) => log::error!("Could not play back the replay: {err:#}"),
+        }
+    });
+}
+
+/// Joins a server, mirroring its map on a scratch map, which the player builds on as usual.
+fn join(setup: &GameSetup, runtime: &Runtime, addr: SocketAddr, name: String) {
+    // the server sends everything on its map
+    _ = fs::remove_dir_all(Map::path(MIRROR_MAP));
+
+    setup
+        .game
+        .send_message(GameMsg::LoadMap(
+            setup.resource_man.clone(),
+            MIRROR_MAP.to_string(),
+        ))
+        .unwrap();
+
+    let resource_man = setup.resource_man.clone();
+    let game = setup.game.clone();
+
+    runtime.spawn(async move {
+        let client = match Client::connect(addr, &name).await {
+            Ok(client) => client,
+            Err(err) => {
+                log::error!("Could not join {addr}: {err:#}");
+                return;
+            }
+        };
+
+        log::info!("Joined {addr} as player {}", client.player);
+
+        if let Err(err) = client::mirror(resource_man, game, client).await {
+            log::error!("Left {addr}: {err:#}");
+        }
+    });
+}
+
+/// What the game was asked to start with, instead of the main menu.
+enum Launch {
+    Replay(Replay),
+    Join(SocketAddr, String),
+}
+
 fn main() -> eyre::Result<()> {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
@@ -131,6 +207,21 @@ fn main() -> eyre::Result<()> {
         }));
     }
 
+    let launch = {
+        let mut args = env::args().skip(1);
+
+        match (args.next().as_deref(), args.next()) {
+            (Some("--replay"), Some(path)) => Some(Launch::Replay(
+                Replay::load(Path::new(&path)).expect("Could not load the replay"),
+            )),
+            (Some("--join"), Some(addr)) => Some(Launch::Join(
+                addr.parse().expect("Could not read the address to join"),
+                args.next().unwrap_or_else(|| "player".to_string()),
+            )),
+            _ => None,
+        }
+    };
+
     // --- window ---
     let event_loop = EventLoop::new();
 
@@ -175,6 +266,15 @@ fn main() -> eyre::Result<()> {
 
     let mut storage = EventLoopStorage::default();
 
+    if let Some(launch) = launch {
+        match launch {
+            Launch::Replay(replay) => play_replay(&setup, &runtime, replay),
+            Launch::Join(addr, name) => join(&setup, &runtime, addr, name),
+        }
+
+        storage.gui_state.switch_screen(Screen::Ingame);
+    }
+
     let mut closed = false;
 
     event_loop.run(move |event, _, control_flow| {
@@ -1,4 +1,7 @@
+#[cfg(unix)]
+use std::env;
 use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 
 use egui::Frame;
@@ -9,6 +12,8 @@ use automancy::camera::Camera;
 use automancy::game::{Game, GameMsg, TICK_INTERVAL};
 use automancy::input::InputHandler;
 use automancy::map::{Map, MapInfo, MAIN_MENU, MAP_PATH};
+#[cfg(unix)]
+use automancy::net::control::{ControlServer, CONTROL_SOCKET_ENV};
 use automancy::options::Options;
 use automancy_defs::coord::ChunkCoord;
 use automancy_defs::log;
@@ -16,54 +21,30 @@ use automancy_defs::rendering::Vertex;
 use automancy_resources::kira::manager::backend::cpal::CpalBackend;
 use automancy_resources::kira::manager::{AudioManager, AudioManagerSettings};
 use automancy_resources::kira::track::{TrackBuilder, TrackHandle};
-use automancy_resources::{ResourceManager, RESOURCES_PATH, RESOURCE_MAN};
+use automancy_resources::{ResourceManager, RESOURCES_PATH};
 
 use crate::gui;
 
 /// Initialize the Resource Manager system, and loads all the resources in all namespaces.
-fn load_resources(track: TrackHandle) -> (Arc<ResourceManager>, Vec<Vertex>, Vec<u16>) {
+fn load_resources(
+    track: TrackHandle,
+    options: &Options,
+) -> (Arc<ResourceManager>, Vec<Vertex>, Vec<u16>) {
     let mut resource_man = ResourceManager::new(track);
 
-    fs::read_dir(RESOURCES_PATH)
-        .expect("The resources folder doesn't exist- this is very wrong")
-        .flatten()
-        .map(|v| v.path())
-        .for_each(|dir| {
-            let namespace = dir.file_name().unwrap().to_str().unwrap();
-            log::info!("Loading namespace {namespace}...");
-
-            resource_man
-                .load_models(&dir)
-                .expect("Error loading models");
-            resource_man.load_audio(&dir).expect("Error loading audio");
-            resource_man.load_tiles(&dir).expect("Error loading tiles");
-            resource_man.load_items(&dir).expect("Error loading items");
-            resource_man.load_tags(&dir).expect("Error loading tags");
-            resource_man
-                .load_scripts(&dir)
-                .expect("Error loading scripts");
-            resource_man
-                .load_translates(&dir)
-                .expect("Error loading translates");
-            resource_man
-                .load_shaders(&dir)
-                .expect("Error loading shaders");
-            resource_man.load_fonts(&dir).expect("Error loading fonts");
-            resource_man
-                .load_functions(&dir)
-                .expect("Error loading functions");
-            resource_man
-                .load_researches(&dir)
-                .expect("Error loading researches");
-
-            log::info!("Loaded namespace {namespace}.");
-        });
-
-    resource_man.compile_researches();
-    resource_man.ordered_items();
-    let (vertices, indices) = resource_man.compile_models();
-
-    (Arc::new(resource_man), vertices, indices)
+    resource_man
+        .load_all(Path::new(RESOURCES_PATH), &options.gui.language)
+        .expect("The resources folder doesn't exist- this is very wrong");
+
+    let (vertices, indices) = resource_man.compile();
+
+    for error in &resource_man.load_errors {
+        resource_man
+            .error_man
+            .push(error.to_game_error(&resource_man), &resource_man);
+    }
+
+    (resource_man.into_shared(), vertices, indices)
 }
 
 /// Stores what the game initializes on startup.
@@ -76,6 +57,9 @@ pub struct GameSetup {
     pub game: ActorRef<GameMsg>,
     /// the game's async handle, for graceful shutdown
     pub game_handle: Option<JoinHandle<()>>,
+    /// the local control server, if one was asked for
+    #[cfg(unix)]
+    pub control: Option<ControlServer>,
     /// the egui frame
     pub frame: Frame,
     /// the camera
@@ -103,9 +87,12 @@ impl GameSetup {
         })?;
         log::info!("Audio backend initialized");
 
+        log::info!("Loading options...");
+        let options = Options::load()?;
+        log::info!("Loaded options.");
+
         log::info!("Loading resources...");
-        let (resource_man, vertices, indices) = load_resources(track);
-        RESOURCE_MAN.write().unwrap().replace(resource_man.clone());
+        let (resource_man, vertices, indices) = load_resources(track, &options);
 
         log::info!("Loaded resources.");
 
@@ -129,9 +116,15 @@ impl GameSetup {
 
         log::info!("Game created.");
 
-        log::info!("Loading options...");
-        let options = Options::load()?;
-        log::info!("Loaded options.");
+        #[cfg(unix)]
+        let control = match env::var_os(CONTROL_SOCKET_ENV) {
+            Some(path) => Some(ControlServer::start(
+                path,
+                resource_man.clone(),
+                game.clone(),
+            )?),
+            None => None,
+        };
 
         log::info!("Loading completed!");
 
@@ -147,6 +140,8 @@ impl GameSetup {
                 resource_man,
                 game,
                 game_handle: Some(game_handle),
+                #[cfg(unix)]
+                control,
                 frame,
                 camera,
                 camera_chunk_coord: camera_coord.into(),
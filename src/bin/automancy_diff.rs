@@ -0,0 +1,85 @@
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::anyhow;
+use env_logger::Env;
+
+use automancy::map::diff::{diff, SavedMap};
+use automancy::map::Map;
+use automancy_defs::log;
+use automancy_resources::kira::manager::backend::mock::MockBackend;
+use automancy_resources::kira::manager::{AudioManager, AudioManagerSettings};
+use automancy_resources::kira::track::TrackBuilder;
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
+use automancy_resources::{ResourceManager, RESOURCES_PATH};
+
+static USAGE: &str = "usage: automancy_diff [--json] OLD NEW
+
+Compares the saved maps OLD and NEW: the tiles added, removed or changed, and the changes to the data of each tile and of the map.
+With --json, writes the differences as JSON instead.
+Exits with 1 if the maps are different, like diff, and with 2 if a map has tiles that couldn't be read.";
+
+fn main() -> anyhow::Result<ExitCode> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
+
+    let mut json = false;
+    let mut maps = Vec::new();
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ => maps.push(Map::sanitize_name(arg)),
+        }
+    }
+
+    let [old, new] = maps.as_slice() else {
+        eprintln!("{USAGE}");
+        return Ok(ExitCode::from(2));
+    };
+
+    // there is nobody to hear it
+    let mut audio_man = AudioManager::<MockBackend>::new(AudioManagerSettings::default())?;
+    let track = audio_man.add_sub_track(TrackBuilder::new())?;
+
+    let mut resource_man = ResourceManager::new(track);
+    resource_man.load_all(Path::new(RESOURCES_PATH), DEFAULT_LANGUAGE)?;
+
+    for error in &resource_man.load_errors {
+        log::error!("{}: {}", error.path.display(), error.message);
+    }
+
+    let read = |name: &str| {
+        SavedMap::read(&resource_man, name).ok_or_else(|| anyhow!("Could not read the map {name}"))
+    };
+    let (old_map, new_map) = (read(old)?, read(new)?);
+
+    // the tiles that couldn't be read aren't compared, so the maps can't be said to be the same
+    let unknown = [(old, &old_map), (new, &new_map)]
+        .into_iter()
+        .flat_map(|(name, map)| map.unknown.iter().map(move |coord| (name, coord)))
+        .inspect(|(name, coord)| {
+            log::error!("The tile at {coord} in map {name} has an unknown id, and wasn't compared")
+        })
+        .count();
+
+    let diff = diff(&old_map, &new_map);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(if unknown > 0 {
+        ExitCode::from(2)
+    } else if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
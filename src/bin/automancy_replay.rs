@@ -0,0 +1,103 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use env_logger::Env;
+use ractor::Actor;
+
+use automancy::game::Game;
+use automancy::map::Map;
+use automancy::replay::{self, Replay};
+use automancy_defs::log;
+use automancy_resources::kira::manager::backend::mock::MockBackend;
+use automancy_resources::kira::manager::{AudioManager, AudioManagerSettings};
+use automancy_resources::kira::track::TrackBuilder;
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
+use automancy_resources::{ResourceManager, RESOURCES_PATH};
+
+static USAGE: &str = "usage: automancy_replay REPLAY
+
+Plays back REPLAY without a window, and reports where the game diverged from what was recorded.
+REPLAY is either a replay file, or the name of a map to play back its last recorded session.
+Exits non-zero if the replay diverged.";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<ExitCode> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
+
+    let mut replay_path = None;
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ if Path::new(&arg).is_file() => replay_path = Some(PathBuf::from(arg)),
+            _ => replay_path = Some(Replay::path(&Map::sanitize_name(arg))),
+        }
+    }
+
+    let Some(replay_path) = replay_path else {
+        eprintln!("{USAGE}");
+        return Ok(ExitCode::from(2));
+    };
+
+    let replay = Replay::load(&replay_path)?;
+
+    // there is nobody to hear it
+    let mut audio_man = AudioManager::<MockBackend>::new(AudioManagerSettings::default())?;
+    let track = audio_man.add_sub_track(TrackBuilder::new())?;
+
+    let mut resource_man = ResourceManager::new(track);
+    resource_man.load_all(Path::new(RESOURCES_PATH), DEFAULT_LANGUAGE)?;
+    resource_man.compile();
+
+    for error in &resource_man.load_errors {
+        log::error!("{}: {}", error.path.display(), error.message);
+    }
+
+    let resource_man = resource_man.into_shared();
+
+    let (game, game_handle) = Actor::spawn(
+        None,
+        Game {
+            resource_man: resource_man.clone(),
+        },
+        (),
+    )
+    .await?;
+
+    let report = replay::play(resource_man, &game, &replay, None).await?;
+
+    game.stop(None);
+    game_handle.await?;
+
+    println!(
+        "Played back {} ticks of {}, with {} inputs.",
+        report.ticks, replay.map_name, report.applied
+    );
+    for (tick, input) in &report.skipped {
+        println!("Skipped an input at tick {tick}, as it refers to something that doesn't exist: {input:?}");
+    }
+    println!("Compared {} checksums.", report.checked);
+
+    match report.divergences.first() {
+        Some(first) => {
+            println!(
+                "Diverged at tick {} (expected {:016x}, got {:016x}), and {} times in total.",
+                first.tick,
+                first.expected,
+                first.actual,
+                report.divergences.len()
+            );
+
+            Ok(ExitCode::FAILURE)
+        }
+        None => {
+            println!("No divergence.");
+
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::env;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use env_logger::Env;
+use ractor::Actor;
+use tokio::net::TcpListener;
+
+use automancy::game::{Game, GameMsg, TICK_INTERVAL};
+use automancy::map::Map;
+#[cfg(unix)]
+use automancy::net::control::ControlServer;
+use automancy::net::server::Server;
+use automancy::net::DEFAULT_PORT;
+use automancy_defs::log;
+use automancy_resources::kira::manager::backend::mock::MockBackend;
+use automancy_resources::kira::manager::{AudioManager, AudioManagerSettings};
+use automancy_resources::kira::track::TrackBuilder;
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
+use automancy_resources::{ResourceManager, RESOURCES_PATH};
+
+static USAGE: &str = "usage: automancy_server [--address ADDRESS] [--control PATH] [--add-deposits] MAP
+
+Hosts MAP for players to join over TCP, on ADDRESS (default: 0.0.0.0:7878).
+With --control, also accepts JSON requests on a Unix socket at PATH, as described in CONTROL_API.md.
+With --add-deposits, adds deposits to MAP if it was saved before they existed, which is otherwise left as it is.
+The map and the players are saved every few minutes, and when stopped with Ctrl-C.";
+
+/// How often the map is saved while hosting.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[tokio::main]
+async fn main() -> anyhow::Result<ExitCode> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let mut addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, DEFAULT_PORT));
+    let mut map_name = None;
+    let mut control_path = None::<PathBuf>;
+    let mut add_deposits = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => match args.next().map(|v| v.parse()) {
+                Some(Ok(v)) => addr = v,
+                _ => {
+                    eprintln!("{USAGE}");
+                    return Ok(ExitCode::from(2));
+                }
+            },
+            "--control" => match args.next() {
+                Some(v) => control_path = Some(v.into()),
+                None => {
+                    eprintln!("{USAGE}");
+                    return Ok(ExitCode::from(2));
+                }
+            },
+            "--add-deposits" => add_deposits = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ => map_name = Some(Map::sanitize_name(arg)),
+        }
+    }
+
+    let Some(map_name) = map_name else {
+        eprintln!("{USAGE}");
+        return Ok(ExitCode::from(2));
+    };
+
+    // there is nobody to hear it on a server
+    let mut audio_man = AudioManager::<MockBackend>::new(AudioManagerSettings::default())?;
+    let track = audio_man.add_sub_track(TrackBuilder::new())?;
+
+    let mut resource_man = ResourceManager::new(track);
+    resource_man.load_all(Path::new(RESOURCES_PATH), DEFAULT_LANGUAGE)?;
+    resource_man.compile();
+
+    for error in &resource_man.load_errors {
+        log::error!("{}: {}", error.path.display(), error.message);
+    }
+
+    let resource_man = resource_man.into_shared();
+
+    let (game, game_handle) = Actor::spawn(
+        Some("game".to_string()),
+        Game {
+            resource_man: resource_man.clone(),
+        },
+        (),
+    )
+    .await?;
+
+    game.send_message(GameMsg::LoadMap(resource_man.clone(), map_name))?;
+    if add_deposits {
+        game.send_message(GameMsg::MigrateDeposits)?;
+    }
+    game.send_interval(TICK_INTERVAL, || GameMsg::Tick);
+
+    let server = Server::start(
+        TcpListener::bind(addr).await?,
+        resource_man.clone(),
+        game.clone(),
+    )
+    .await?;
+    #[cfg(unix)]
+    let control = match control_path {
+        Some(path) => Some(ControlServer::start(path, resource_man, game.clone())?),
+        None => None,
+    };
+    #[cfg(not(unix))]
+    if control_path.is_some() {
+        anyhow::bail!("The control server is only available on Unix");
+    }
+
+    let mut autosave = tokio::time::interval(AUTOSAVE_INTERVAL);
+    autosave.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = autosave.tick() => {
+                if let Err(err) = server.save().await {
+                    log::error!("Could not save: {err:#}");
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    log::info!("Stopping...");
+    #[cfg(unix)]
+    if let Some(control) = control {
+        control.stop().await;
+    }
+    server.save().await?;
+    server.stop().await;
+
+    game.stop(None);
+    game_handle.await?;
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
 use std::mem;
 use std::ops::Div;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -8,25 +9,34 @@ use arraydeque::{ArrayDeque, Wrapping};
 use ractor::rpc::CallResult;
 use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort, SupervisionEvent};
 use rayon::prelude::*;
+use tokio::sync::{broadcast, mpsc};
 
 use automancy_defs::cgmath::vec3;
 use automancy_defs::coord::{TileCoord, TileHex, TileRange};
-use automancy_defs::hashbrown::HashMap;
+use automancy_defs::hashbrown::{HashMap, HashSet};
 use automancy_defs::hexagon_tiles::traits::HexDirection;
-use automancy_defs::id::Id;
+use automancy_defs::id::{Id, IdRaw};
 use automancy_defs::math::{Float, Matrix4, FAR};
 use automancy_defs::rendering::InstanceData;
 use automancy_defs::{log, math};
+use automancy_resources::data::inventory::Inventory;
 use automancy_resources::data::item::item_match;
-use automancy_resources::data::stack::ItemStack;
+use automancy_resources::data::stack::{ItemAmount, ItemStack};
 use automancy_resources::data::{Data, DataMap};
 use automancy_resources::types::script::Script;
 use automancy_resources::ResourceManager;
 
 use crate::game::GameMsg::*;
-use crate::map::{Map, MapInfo, TileEntities};
-use crate::tile_entity::{TileEntity, TileEntityMsg, TileModifier};
+use crate::logistics::{Logistics, NetworkInfo, Request};
+use crate::map::{Map, MapInfo, TileEntities, Tiles};
+use crate::net::{id_raw, ClientMsg};
+use crate::profiler::Profiler;
+use crate::replay::{self, Recorder, Replay, ReplayInput};
+use crate::signal::{self, Publications, Signals};
+use crate::statistics::{ItemStat, Statistics};
+use crate::tile_entity::{Refusal, RefusalReason, TileEntity, TileEntityMsg, TileModifier};
 use crate::util::actor::multi_call_iter;
+use crate::worldgen::{self, WorldGenSettings};
 
 /// Game ticks per second
 pub const TPS: u64 = 30;
@@ -38,6 +48,9 @@ pub const TAKE_ITEM_ANIMATION_SPEED: Duration = Duration::from_nanos(200_000_000
 
 pub type TickUnit = u16;
 
+/// How many changes to the tiles can be waiting for a watcher before it falls behind.
+const CHANGES_BUFFER_SIZE: usize = 4096;
+
 #[derive(Debug, Clone, Copy)]
 pub struct TransactionRecord {
     pub stack: ItemStack,
@@ -56,22 +69,72 @@ pub type RenderInfo = (HashMap<TileCoord, RenderUnit>, HashMap<TileCoord, DataMa
 pub type TransactionRecords =
     HashMap<(TileCoord, TileCoord), VecDeque<(Instant, TransactionRecord)>>;
 
+/// The number of messages sent to tile entities by the game running that haven't been handled yet.
+/// When it is zero, everything caused by the previous ticks has been processed.
+pub type PendingMessages = Arc<AtomicUsize>;
+
+/// A change to a tile on the map, as told to whatever watches the map.
+#[derive(Debug, Clone)]
+pub enum TileChange {
+    /// A tile was placed, in place of whatever was there.
+    Placed(TileCoord, Id, TileModifier),
+    Removed(TileCoord),
+    /// The data map of a tile changed, to this.
+    Data(TileCoord, DataMap),
+    /// Another map was loaded, so everything on it has to be taken again.
+    Reloaded,
+}
+
+/// Tells whatever watches the map about every change to its tiles, as it happens.
+pub type Changes = broadcast::Sender<TileChange>;
+
+/// Marks a counted message as handled. The count is reset when a map is loaded, so messages from the map before it never take it below zero.
+pub fn mark_handled(pending: &PendingMessages) {
+    _ = pending.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+        Some(pending.saturating_sub(1))
+    });
+}
+
 #[derive(Debug)]
 pub struct GameState {
     /// a count of all the ticks that have happened
     tick_count: TickUnit,
     /// is the game stopped
     stopped: bool,
+    /// are the ticks from the tick interval skipped
+    paused: bool,
 
     /// the tile entities
     tile_entities: TileEntities,
     /// the map
     map: Map,
+    /// a copy of the map's data map while the GUI has taken it, which is read in its place until it is put back
+    taken_data: Option<DataMap>,
 
     /// what to do to undo the last UNDO_CACHE_SIZE user events
     undo_steps: ArrayDeque<Vec<GameMsg>, 16, Wrapping>,
     /// records transactions to be drawn
     transaction_records: Arc<Mutex<TransactionRecords>>,
+    /// the tile entity messages that are yet to be handled
+    pending: PendingMessages,
+    /// measures where the ticks spend their time
+    profiler: Arc<Profiler>,
+    /// are the scenario objectives due to be counted again
+    objectives_due: bool,
+    /// records what the player does, while recording a replay
+    recorder: Option<Recorder>,
+    /// counts the items produced, consumed and stored on the map
+    statistics: Statistics,
+    /// the signals published during the last tick, which the tile entities read during this one
+    signals: Arc<Signals>,
+    /// the last signals each tile published, which the signals read during the next tick are gathered from
+    publications: Publications,
+    /// routes items over the logistics networks
+    logistics: Logistics,
+    /// tells whatever watches the map about the changes to its tiles
+    changes: Changes,
+    /// where what the player does is sent in place of the map, while the map mirrors a server's
+    remote: Option<mpsc::UnboundedSender<ClientMsg>>,
 }
 
 /// Represents a message the game receives
@@ -80,6 +143,20 @@ pub enum GameMsg {
     /// tick the tile once
     Tick,
     StopTicking,
+    /// skip the ticks from the tick interval, or stop skipping them
+    SetPaused(bool),
+    /// tick once, even while paused
+    Step,
+    /// get the number of ticks that have happened
+    GetTickCount(RpcReplyPort<TickUnit>),
+    /// set the tick count, so that a replay plays back from the tick it was recorded at
+    SetTickCount(TickUnit),
+    /// watch the changes to the tiles on the map, from now on
+    WatchChanges(RpcReplyPort<broadcast::Receiver<TileChange>>),
+    /// send what the player does to a server and stop ticking, as the map only mirrors the server's. Or go back to playing the map, if there is none
+    SetRemote(Option<mpsc::UnboundedSender<ClientMsg>>),
+    /// make the changes a server told of, in order, while the map still mirrors one
+    ApplyRemote(Vec<GameMsg>),
     /// send a message to a tile entity
     ForwardMsgToTile(TileCoord, TileEntityMsg),
     /// place a tile at the given position
@@ -93,7 +170,20 @@ pub enum GameMsg {
     },
     MoveTiles(Vec<TileCoord>, TileCoord, bool),
     Undo,
-    /// checks for the adjacent tiles against the script
+    /// set a value in the data map of a tile as the player, or remove it if there is none
+    SetTileData {
+        coord: TileCoord,
+        key: Id,
+        value: Option<Data>,
+    },
+    /// take items out of a tile's buffer as the player, and reply with how many were taken, if any were
+    TakeItems {
+        coord: TileCoord,
+        item: Id,
+        amount: ItemAmount,
+        reply: Option<RpcReplyPort<Option<ItemAmount>>>,
+    },
+    /// checks for the adjacent tiles against the script, and replies with the neighbor that matched
     CheckAdjacent {
         script: Script,
         coord: TileCoord,
@@ -102,17 +192,31 @@ pub enum GameMsg {
 
     /// load a map
     LoadMap(Arc<ResourceManager>, String),
+    /// set up the map with a scenario's starting layout, inventory and objectives
+    StartScenario(Id),
+    /// generate the deposits of a new map
+    GenerateWorld(WorldGenSettings),
+    /// add deposits to a map saved before they existed, once the player agreed to it
+    MigrateDeposits,
     /// take the map
     TakeMap(RpcReplyPort<Map>),
     SaveMap(Arc<ResourceManager>, RpcReplyPort<()>),
     GetMapInfo(RpcReplyPort<(MapInfo, String)>),
+    /// get whether the map was saved before deposits existed, and has none yet
+    NeedsDeposits(RpcReplyPort<bool>),
 
     /// get the tile at the given position
     GetTile(TileCoord, RpcReplyPort<Option<(Id, TileModifier)>>),
     /// get the tile entity at the given position
     GetTileEntity(TileCoord, RpcReplyPort<Option<ActorRef<TileEntityMsg>>>),
+    /// get every tile on the map
+    GetTiles(RpcReplyPort<Tiles>),
+    /// get the data map of every tile entity
+    GetAllData(RpcReplyPort<HashMap<TileCoord, DataMap>>),
 
     TakeDataMap(RpcReplyPort<DataMap>),
+    /// get a copy of the map's data map, without taking it
+    GetDataMap(RpcReplyPort<DataMap>),
     SetDataMap(DataMap),
     GetDataValue(Id, RpcReplyPort<Option<Data>>),
     SetData(Id, Data),
@@ -126,6 +230,38 @@ pub enum GameMsg {
 
     GetRecordedTransactions(RpcReplyPort<Arc<Mutex<TransactionRecords>>>),
     RecordTransaction(ItemStack, TileCoord, TileCoord),
+    /// count items towards the map's statistics
+    RecordItemStat(ItemStat, ItemStack),
+    /// get a copy of the map's statistics
+    GetStatistics(RpcReplyPort<Statistics>),
+    /// publish signals onto the network linked by the tile at the first position, from the tile at the second, during the given tick, for the ones after it
+    PublishSignals(TileCoord, TileCoord, Inventory, TickUnit),
+    /// get the signals the tile entities are reading this tick
+    GetSignals(RpcReplyPort<Arc<Signals>>),
+    /// report the tile a tile on a logistics network is linked to
+    ReportLink(TileCoord, TileCoord),
+    /// advertise what a storage has, on the logistics network of the tile providing it
+    Advertise {
+        provider: TileCoord,
+        storage: TileCoord,
+        contents: Inventory,
+    },
+    /// request items from the logistics network the requester is on
+    PostRequest(Request),
+    /// get every logistics network, with its contents and open requests
+    GetLogistics(RpcReplyPort<Vec<NetworkInfo>>),
+
+    /// get the number of tile entity messages that are yet to be handled
+    GetPendingMessages(RpcReplyPort<usize>),
+    /// get the profiler the tile entities report their timings to
+    GetProfiler(RpcReplyPort<Arc<Profiler>>),
+
+    /// start recording a replay, from the map as it is now
+    StartRecording,
+    /// stop recording, and get the replay if there was one
+    StopRecording(RpcReplyPort<Option<Replay>>),
+    /// get the checksum of the map and everything on it, as recorded in replays
+    GetChecksum(RpcReplyPort<u64>),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -139,6 +275,119 @@ pub struct Game {
     pub resource_man: Arc<ResourceManager>,
 }
 
+impl Game {
+    /// Ticks the game once, and counts the scenario objectives every second.
+    ///
+    /// While recording, the state of the game is checksummed every so often before the tick. It is only checksummed once everything sent during the previous ticks was handled, the same as when it is played back, so the tick waits until then, and is not run if it would have to.
+    async fn step(&self, state: &mut GameState) -> bool {
+        if state
+            .recorder
+            .as_ref()
+            .map_or(false, Recorder::checksum_due)
+        {
+            if state.pending.load(Ordering::SeqCst) != 0 {
+                return false;
+            }
+
+            let checksum = checksum(&self.resource_man, state).await;
+
+            if let Some(recorder) = &mut state.recorder {
+                recorder.record_checksum(checksum);
+            }
+        }
+
+        let seed = map_data(state)
+            .get(&self.resource_man.registry.data_ids.world_seed)
+            .and_then(Data::as_amount)
+            .cloned()
+            .unwrap_or(0);
+
+        tick(state, seed);
+        deliver(state);
+        state.statistics.tick();
+
+        if let Some(recorder) = &mut state.recorder {
+            recorder.tick();
+        }
+
+        if state.tick_count as u64 % TPS == 0 {
+            state.objectives_due = true;
+        }
+
+        if state.objectives_due {
+            update_objectives(&self.resource_man, state).await;
+        }
+
+        true
+    }
+
+    /// Turns what the player did into the command the server is sent in its place, answering what waits on it from the map as it is now. Anything else is given back, to be handled as usual.
+    fn remote_command(
+        &self,
+        state: &GameState,
+        msg: GameMsg,
+    ) -> Result<Option<ClientMsg>, GameMsg> {
+        let resource_man = &self.resource_man;
+        let interner = &resource_man.interner;
+
+        Ok(match msg {
+            PlaceTile {
+                coord,
+                id,
+                tile_modifier,
+                record: true,
+                reply,
+                ..
+            } => {
+                if let Some(reply) = reply {
+                    let response = match state.map.tiles.get(&coord) {
+                        Some(tile) if *tile == (id, tile_modifier) => PlaceTileResponse::Ignored,
+                        Some(_) if id == resource_man.registry.none => PlaceTileResponse::Removed,
+                        None if id == resource_man.registry.none => PlaceTileResponse::Ignored,
+                        _ => PlaceTileResponse::Placed,
+                    };
+
+                    reply.send(response).unwrap();
+                }
+
+                id_raw(resource_man, id).map(|id| ClientMsg::PlaceTile {
+                    coord,
+                    id,
+                    tile_modifier,
+                })
+            }
+            MoveTiles(coords, direction, true) => Some(ClientMsg::MoveTiles { coords, direction }),
+            Undo => Some(ClientMsg::Undo),
+            SetTileData { coord, key, value } => id_raw(resource_man, key).and_then(|key| {
+                let value = match value {
+                    Some(value) => Some(value.try_to_raw(interner)?),
+                    None => None,
+                };
+
+                Some(ClientMsg::SetTileData { coord, key, value })
+            }),
+            TakeItems {
+                coord,
+                item,
+                amount,
+                reply,
+            } => {
+                // what is taken comes back in the player's inventory, from the server
+                if let Some(reply) = reply {
+                    reply.send(None).unwrap();
+                }
+
+                id_raw(resource_man, item).map(|item| ClientMsg::TakeItems {
+                    coord,
+                    item,
+                    amount,
+                })
+            }
+            msg => return Err(msg),
+        })
+    }
+}
+
 #[async_trait::async_trait]
 impl Actor for Game {
     type Msg = GameMsg;
@@ -170,16 +419,54 @@ impl Actor for Game {
                 return Ok(());
             }
             LoadMap(resource_man, name) => {
-                for tile_entity in state.tile_entities.values() {
+                let old = mem::take(&mut state.tile_entities);
+
+                // what was already sent to the tiles is handled first, so that it is still counted as handled
+                _ = multi_call_iter(
+                    old.values(),
+                    old.len(),
+                    TileEntityMsg::GetDataWithCoord,
+                    None,
+                )
+                .await;
+
+                for tile_entity in old.values() {
                     tile_entity.stop(Some("Loading new map".to_string()));
                 }
 
-                let (map, tile_entities) = Map::load(myself.clone(), resource_man, &name).await;
+                // anything still counted belongs to the old map
+                state.pending.store(0, Ordering::SeqCst);
+
+                let (map, tile_entities) = Map::load(
+                    myself.clone(),
+                    state.pending.clone(),
+                    state.profiler.clone(),
+                    state.changes.clone(),
+                    resource_man,
+                    &name,
+                )
+                .await;
 
                 state.map = map;
+                state.taken_data = None;
                 state.tile_entities = tile_entities;
                 state.transaction_records.lock().unwrap().clear();
                 state.undo_steps.clear();
+                state.recorder = None;
+                state.statistics = Statistics::load(&self.resource_man.interner, &name);
+                state.signals = Default::default();
+                state.publications.clear();
+                state.logistics = Default::default();
+                // the map loaded is played on its own, whatever was mirrored before
+                state.remote = None;
+
+                // the map is only changed once the player agrees to it, see MigrateDeposits
+                if needs_deposits(&self.resource_man, state) {
+                    log::warn!("Map {name} was saved before deposits existed, and is left without them until they are added");
+                }
+
+                // nobody might be watching, which is fine
+                _ = state.changes.send(TileChange::Reloaded);
 
                 log::info!("Successfully loaded map {name}!");
                 return Ok(());
@@ -190,8 +477,25 @@ impl Actor for Game {
                     .save(&resource_man.interner, &state.tile_entities)
                     .await;
                 log::info!("Saved map {}", state.map.map_name.clone());
+
+                if let Some(recorder) = &state.recorder {
+                    if let Err(err) = recorder.replay().save(&Replay::path(&state.map.map_name)) {
+                        log::error!("Could not save the replay: {err:#}");
+                    }
+                }
+                if let Err(err) = state
+                    .statistics
+                    .save(&resource_man.interner, &state.map.map_name)
+                {
+                    log::error!("Could not save the statistics: {err:#}");
+                }
                 reply.send(()).unwrap();
             }
+            NeedsDeposits(reply) => {
+                reply
+                    .send(needs_deposits(&self.resource_man, state))
+                    .unwrap();
+            }
             GetMapInfo(reply) => {
                 let tile_count = state.map.tiles.len() as u64;
                 let save_time = state.map.save_time;
@@ -209,15 +513,115 @@ impl Actor for Game {
                 return Ok(());
             }
             TakeDataMap(reply) => {
+                state.taken_data = Some(state.map.data.clone());
+
                 reply.send(mem::take(&mut state.map.data)).unwrap();
 
                 return Ok(());
             }
             SetDataMap(data) => {
+                let taken_data = state.taken_data.take();
+
+                if let Some((recorder, old)) = state.recorder.as_mut().zip(taken_data) {
+                    recorder.record_data_map_changes(&self.resource_man.interner, &old, &data);
+                }
+
                 state.map.data = data;
+
+                if state.objectives_due {
+                    update_objectives(&self.resource_man, state).await;
+                }
             }
             GetDataValue(key, reply) => {
-                reply.send(state.map.data.get(&key).cloned()).unwrap();
+                reply.send(map_data(state).get(&key).cloned()).unwrap();
+
+                return Ok(());
+            }
+            GetDataMap(reply) => {
+                reply.send(map_data(state).clone()).unwrap();
+
+                return Ok(());
+            }
+            GetStatistics(reply) => {
+                reply.send(state.statistics.clone()).unwrap();
+
+                return Ok(());
+            }
+            GetSignals(reply) => {
+                reply.send(state.signals.clone()).unwrap();
+
+                return Ok(());
+            }
+            GetLogistics(reply) => {
+                reply.send(state.logistics.networks()).unwrap();
+
+                return Ok(());
+            }
+            GetTickCount(reply) => {
+                reply.send(state.tick_count).unwrap();
+
+                return Ok(());
+            }
+            SetTickCount(tick_count) => {
+                state.tick_count = tick_count;
+
+                return Ok(());
+            }
+            WatchChanges(reply) => {
+                reply.send(state.changes.subscribe()).unwrap();
+
+                return Ok(());
+            }
+            SetRemote(remote) => {
+                state.remote = remote;
+
+                return Ok(());
+            }
+            ApplyRemote(msgs) => {
+                // a map loaded since is played on its own
+                if state.remote.is_some() {
+                    for msg in msgs {
+                        self.handle(myself.clone(), msg, state).await?;
+                    }
+                }
+
+                return Ok(());
+            }
+            StartRecording => {
+                let data = all_data(state).await;
+
+                state.recorder = Some(Recorder::new(
+                    &self.resource_man.interner,
+                    state.map.map_name.clone(),
+                    state.tick_count,
+                    &state.map.tiles,
+                    &data,
+                    map_data(state),
+                ));
+
+                return Ok(());
+            }
+            StopRecording(reply) => {
+                reply
+                    .send(state.recorder.take().map(Recorder::into_replay))
+                    .unwrap();
+
+                return Ok(());
+            }
+            GetChecksum(reply) => {
+                reply
+                    .send(checksum(&self.resource_man, state).await)
+                    .unwrap();
+
+                return Ok(());
+            }
+            GetPendingMessages(reply) => {
+                reply.send(state.pending.load(Ordering::SeqCst)).unwrap();
+
+                return Ok(());
+            }
+            GetProfiler(reply) => {
+                reply.send(state.profiler.clone()).unwrap();
 
                 return Ok(());
             }
@@ -226,16 +630,119 @@ impl Actor for Game {
                     return Ok(());
                 }
 
+                // while mirroring a server, the map only changes when the server says it did
+                let rest = if let Some(remote) = &state.remote {
+                    match self.remote_command(state, rest) {
+                        Ok(command) => {
+                            if let Some(command) = command {
+                                _ = remote.send(command);
+                            }
+
+                            return Ok(());
+                        }
+                        Err(Tick | Step) => return Ok(()),
+                        Err(rest) => rest,
+                    }
+                } else {
+                    rest
+                };
+
                 match rest {
                     Tick => {
-                        tick(state);
+                        // a tick that has to wait is skipped, and the next one from the tick interval tries again
+                        if !state.paused {
+                            self.step(state).await;
+                        }
+                    }
+                    Step => {
+                        // the step is tried again after the messages that are on their way, which it has to wait for
+                        if !self.step(state).await {
+                            myself.send_message(Step).unwrap();
+                        }
+                    }
+                    SetPaused(paused) => {
+                        state.paused = paused;
+                    }
+                    StartScenario(id) => {
+                        start_scenario(self.resource_man.clone(), myself.clone(), state, id).await;
+                    }
+                    GenerateWorld(settings) => {
+                        generate_world(self.resource_man.clone(), myself.clone(), state, settings)
+                            .await;
+                    }
+                    MigrateDeposits => {
+                        migrate_deposits(self.resource_man.clone(), myself.clone(), state).await;
                     }
                     SetData(key, value) => {
+                        if let Some(recorder) = &mut state.recorder {
+                            recorder.record_map_data(
+                                &self.resource_man.interner,
+                                key,
+                                Some(&value),
+                            );
+                        }
+
                         state.map.data.insert(key, value);
                     }
                     RemoveData(key) => {
+                        if let Some(recorder) = &mut state.recorder {
+                            recorder.record_map_data(&self.resource_man.interner, key, None);
+                        }
+
                         state.map.data.remove(&key);
                     }
+                    SetTileData { coord, key, value } => {
+                        if let Some(recorder) = &mut state.recorder {
+                            let interner = &self.resource_man.interner;
+
+                            if let Some(key) = interner.resolve(key).map(IdRaw::parse) {
+                                recorder.record(ReplayInput::SetTileData {
+                                    coord,
+                                    key,
+                                    value: value.as_ref().and_then(|v| v.try_to_raw(interner)),
+                                });
+                            }
+                        }
+
+                        if let Some(tile_entity) = state.tile_entities.get(&coord) {
+                            let msg = match value {
+                                Some(value) => TileEntityMsg::SetDataValue(key, value),
+                                None => TileEntityMsg::RemoveData(key),
+                            };
+
+                            tile_entity.send_message(msg).unwrap();
+                        }
+                    }
+                    TakeItems {
+                        coord,
+                        item,
+                        amount,
+                        reply,
+                    } => {
+                        if let Some(recorder) = &mut state.recorder {
+                            if let Some(item) =
+                                self.resource_man.interner.resolve(item).map(IdRaw::parse)
+                            {
+                                recorder.record(ReplayInput::TakeItems {
+                                    coord,
+                                    item,
+                                    amount,
+                                });
+                            }
+                        }
+
+                        if let Some(tile_entity) = state.tile_entities.get(&coord) {
+                            tile_entity
+                                .send_message(TileEntityMsg::TakeItems {
+                                    item,
+                                    amount,
+                                    reply,
+                                })
+                                .unwrap();
+                        } else if let Some(reply) = reply {
+                            reply.send(None).unwrap();
+                        }
+                    }
                     RenderInfoRequest {
                         culling_range,
                         reply,
@@ -273,19 +780,7 @@ impl Actor for Game {
                             })
                             .collect();
 
-                        let all_data = multi_call_iter(
-                            state.tile_entities.values(),
-                            state.tile_entities.len(),
-                            TileEntityMsg::GetDataWithCoord,
-                            None,
-                        )
-                        .await
-                        .unwrap()
-                        .into_iter()
-                        .map(CallResult::unwrap)
-                        .collect();
-
-                        reply.send((instances, all_data)).unwrap();
+                        reply.send((instances, all_data(state).await)).unwrap();
                     }
                     PlaceTile {
                         coord,
@@ -295,6 +790,36 @@ impl Actor for Game {
                         record,
                         reply,
                     } => {
+                        // the player can only place the tiles the scenario being played allows
+                        if record
+                            && !map_data(state)
+                                .get(&self.resource_man.registry.data_ids.scenario)
+                                .and_then(Data::as_id)
+                                .and_then(|scenario| self.resource_man.registry.scenario(*scenario))
+                                .map_or(true, |scenario| scenario.allows(&self.resource_man, id))
+                        {
+                            if let Some(reply) = reply {
+                                reply.send(PlaceTileResponse::Ignored).unwrap();
+                            }
+
+                            return Ok(());
+                        }
+
+                        if record {
+                            if let Some(recorder) = &mut state.recorder {
+                                let interner = &self.resource_man.interner;
+
+                                if let Some(id) = interner.resolve(id).map(IdRaw::parse) {
+                                    recorder.record(ReplayInput::PlaceTile {
+                                        coord,
+                                        id,
+                                        tile_modifier,
+                                        data: data.as_ref().map(|data| data.to_raw(interner)),
+                                    });
+                                }
+                            }
+                        }
+
                         if let Some((old_id, old_tile_modifier)) = state.map.tiles.get(&coord) {
                             if *old_tile_modifier == tile_modifier && *old_id == id {
                                 if let Some(reply) = reply {
@@ -358,9 +883,97 @@ impl Actor for Game {
                             .send(state.tile_entities.get(&coord).cloned())
                             .unwrap();
                     }
+                    GetTiles(reply) => {
+                        reply.send(state.map.tiles.clone()).unwrap();
+                    }
+                    GetAllData(reply) => {
+                        reply.send(all_data(state).await).unwrap();
+                    }
                     ForwardMsgToTile(coord, msg) => {
+                        if let TileEntityMsg::Transaction {
+                            stack, root_coord, ..
+                        } = &msg
+                        {
+                            state.logistics.arrived(coord, *root_coord, stack.item.id);
+                        }
+
+                        // items carried to a tile that is gone are offered to nothing there, so that the storage takes them back
+                        let msg = match msg {
+                            TileEntityMsg::Carry {
+                                stack,
+                                storage,
+                                storage_id,
+                                ..
+                            } if !state.tile_entities.contains_key(&coord) => {
+                                TileEntityMsg::Transaction {
+                                    stack,
+                                    source_coord: storage,
+                                    source_id: storage_id,
+                                    root_coord: storage,
+                                    root_id: storage_id,
+                                    fallback: None,
+                                }
+                            }
+                            msg => msg,
+                        };
+
                         if let Some(tile_entity) = state.tile_entities.get(&coord) {
                             tile_entity.send_message(msg).unwrap();
+                        } else if let TileEntityMsg::Transaction {
+                            stack,
+                            source_coord,
+                            source_id,
+                            root_coord,
+                            root_id,
+                            fallback: Some(fallback),
+                        } = msg
+                        {
+                            // the items go to the fallback in place of the missing tile, and stay counted
+                            myself
+                                .send_message(ForwardMsgToTile(
+                                    fallback,
+                                    TileEntityMsg::Transaction {
+                                        stack,
+                                        source_coord,
+                                        source_id,
+                                        root_coord,
+                                        root_id,
+                                        fallback: None,
+                                    },
+                                ))
+                                .unwrap();
+                        } else if let TileEntityMsg::Transaction {
+                            stack,
+                            source_coord,
+                            root_coord,
+                            ..
+                        } = msg
+                        {
+                            let refusal = Refusal {
+                                stack,
+                                coord,
+                                reason: RefusalReason::Missing,
+                            };
+
+                            // the tiles the items came from hear that there is nothing there, in place of the transaction
+                            for sender in [source_coord, root_coord]
+                                .into_iter()
+                                .collect::<HashSet<_>>()
+                            {
+                                if let Some(tile_entity) = state.tile_entities.get(&sender) {
+                                    state.pending.fetch_add(1, Ordering::SeqCst);
+
+                                    tile_entity
+                                        .send_message(TileEntityMsg::TransactionResult {
+                                            result: Err(refusal),
+                                        })
+                                        .unwrap();
+                                }
+                            }
+
+                            mark_handled(&state.pending);
+                        } else if msg.is_counted() {
+                            mark_handled(&state.pending);
                         }
                     }
                     CheckAdjacent {
@@ -369,32 +982,47 @@ impl Actor for Game {
                         self_coord,
                     } => {
                         if let Some(adjacent) = script.adjacent {
-                            let mut fulfilled = false;
-
-                            for neighbor in TileHex::NEIGHBORS.iter().map(|v| coord + (*v).into()) {
-                                if let Some((id, _)) = state.map.tiles.get(&neighbor) {
-                                    if item_match(&self.resource_man, *id, adjacent) {
-                                        fulfilled = true;
-                                        break;
-                                    }
-                                }
-                            }
+                            let found = TileHex::NEIGHBORS
+                                .iter()
+                                .map(|v| coord + (*v).into())
+                                .find(|neighbor| {
+                                    state.map.tiles.get(neighbor).map_or(false, |(id, _)| {
+                                        item_match(&self.resource_man, *id, adjacent)
+                                    })
+                                });
 
-                            if let Some(entity) = state.tile_entities.get(&self_coord) {
+                            // the adjacent tile answers with what it has left, so the game never waits on it
+                            if let Some(entity) =
+                                found.and_then(|found| state.tile_entities.get(&found))
+                            {
                                 entity
-                                    .send_message(TileEntityMsg::AdjacentState { fulfilled })
+                                    .send_message(TileEntityMsg::ReportRemaining { to: self_coord })
                                     .unwrap();
+
+                                return Ok(());
                             }
-                        } else if let Some(entity) = state.tile_entities.get(&self_coord) {
-                            entity
-                                .send_message(TileEntityMsg::AdjacentState { fulfilled: true })
-                                .unwrap();
+                        }
+
+                        let message = TileEntityMsg::AdjacentState {
+                            fulfilled: script.adjacent.is_none(),
+                            adjacent: None,
+                            remaining: None,
+                        };
+
+                        if let Some(entity) = state.tile_entities.get(&self_coord) {
+                            entity.send_message(message).unwrap();
+                        } else {
+                            mark_handled(&state.pending);
                         }
                     }
                     StopTicking => {
                         state.stopped = true;
                     }
                     Undo => {
+                        if let Some(recorder) = &mut state.recorder {
+                            recorder.record(ReplayInput::Undo);
+                        }
+
                         if let Some(step) = state.undo_steps.pop_back() {
                             for msg in step {
                                 myself.send_message(msg).unwrap();
@@ -426,41 +1054,49 @@ impl Actor for Game {
 
                         reply.send(state.transaction_records.clone()).unwrap();
                     }
+                    RecordItemStat(stat, stack) => {
+                        state.statistics.record(stat, stack);
+                    }
+                    PublishSignals(network, publisher, signals, tick_count) => {
+                        // signals that arrive late still replace the tile's older ones, which the network keeps reading until then
+                        if !signal::record(
+                            &mut state.publications,
+                            network,
+                            publisher,
+                            &signals,
+                            tick_count,
+                        ) {
+                            log::debug!(
+                                "Dropped signals published on {network} by {publisher} during tick {tick_count}, which were already replaced"
+                            );
+                        }
+                    }
+                    ReportLink(coord, link) => {
+                        state.logistics.report_link(coord, link);
+                    }
+                    Advertise {
+                        provider,
+                        storage,
+                        contents,
+                    } => {
+                        state.logistics.advertise(provider, storage, contents);
+                    }
+                    PostRequest(request) => {
+                        state.logistics.post(request);
+                    }
                     RecordTransaction(stack, source_coord, coord) => {
-                        let mut transaction_records = state.transaction_records.lock().unwrap();
-
-                        if let Some((instant, _)) = transaction_records
-                            .get(&(source_coord, coord))
-                            .and_then(|v| v.back())
-                        {
-                            if Instant::now().duration_since(*instant)
-                                < TRANSACTION_ANIMATION_SPEED.div(4)
-                            {
-                                return Ok(());
+                        record_transaction(state, stack, source_coord, coord);
+                    }
+                    MoveTiles(tiles, direction, record) => {
+                        if record {
+                            if let Some(recorder) = &mut state.recorder {
+                                recorder.record(ReplayInput::MoveTiles {
+                                    coords: tiles.clone(),
+                                    direction,
+                                });
                             }
                         }
 
-                        if let Some(((source_id, _), (id, _))) = state
-                            .map
-                            .tiles
-                            .get(&source_coord)
-                            .cloned()
-                            .zip(state.map.tiles.get(&coord).cloned())
-                        {
-                            transaction_records
-                                .entry((source_coord, coord))
-                                .or_insert_with(Default::default)
-                                .push_back((
-                                    Instant::now(),
-                                    TransactionRecord {
-                                        stack,
-                                        source_id,
-                                        id,
-                                    },
-                                ));
-                        }
-                    }
-                    MoveTiles(tiles, direction, record) => {
                         let mut undo = vec![];
 
                         let mut removed = Vec::new();
@@ -530,6 +1166,9 @@ impl Actor for Game {
 pub async fn new_tile(
     resource_man: Arc<ResourceManager>,
     game: ActorRef<GameMsg>,
+    pending: PendingMessages,
+    profiler: Arc<Profiler>,
+    changes: Changes,
     coord: TileCoord,
     id: Id,
     tile_modifier: TileModifier,
@@ -542,7 +1181,7 @@ pub async fn new_tile(
             tile_modifier,
             resource_man,
         },
-        (game.clone(),),
+        (game.clone(), pending, profiler, changes),
         game.get_cell(),
     )
     .await
@@ -551,7 +1190,7 @@ pub async fn new_tile(
     actor
 }
 
-/// Stops a tile and removes it from the game
+/// Stops a tile and removes it from the game. Its data is taken before it stops, after it has handled what was already sent to it, so that nothing counted is left unhandled.
 async fn remove_tile(
     state: &mut GameState,
     coord: TileCoord,
@@ -570,11 +1209,106 @@ async fn remove_tile(
         None
     };
 
-    state
+    state.logistics.remove(coord);
+
+    let old = state.map.tiles.remove(&coord);
+
+    if old.is_some() {
+        _ = state.changes.send(TileChange::Removed(coord));
+    }
+
+    old.map(|(id, modifier)| (id, modifier, data))
+}
+
+/// Records a transaction between two tiles to be drawn, unless one between them was just drawn.
+fn record_transaction(
+    state: &GameState,
+    stack: ItemStack,
+    source_coord: TileCoord,
+    coord: TileCoord,
+) {
+    let mut transaction_records = state.transaction_records.lock().unwrap();
+
+    if let Some((instant, _)) = transaction_records
+        .get(&(source_coord, coord))
+        .and_then(|v| v.back())
+    {
+        if Instant::now().duration_since(*instant) < TRANSACTION_ANIMATION_SPEED.div(4) {
+            return;
+        }
+    }
+
+    if let Some(((source_id, _), (id, _))) = state
         .map
         .tiles
-        .remove(&coord)
-        .map(|(id, modifier)| (id, modifier, data))
+        .get(&source_coord)
+        .cloned()
+        .zip(state.map.tiles.get(&coord).cloned())
+    {
+        transaction_records
+            .entry((source_coord, coord))
+            .or_insert_with(Default::default)
+            .push_back((
+                Instant::now(),
+                TransactionRecord {
+                    stack,
+                    source_id,
+                    id,
+                },
+            ));
+    }
+}
+
+/// Has the storages the logistics networks matched with requests send the items to their requesters.
+/// The storages take the items out first, so only what they still have is sent, and they take back what the requesters refuse.
+fn deliver(state: &mut GameState) {
+    for delivery in state.logistics.tick() {
+        let Some(storage) = state.tile_entities.get(&delivery.storage) else {
+            continue;
+        };
+
+        state.pending.fetch_add(1, Ordering::SeqCst);
+
+        if let Err(e) = storage.send_message(TileEntityMsg::Deliver {
+            item: delivery.item,
+            amount: delivery.amount,
+            requester: delivery.requester,
+            path: delivery.path,
+        }) {
+            mark_handled(&state.pending);
+            log::error!("{e:?}");
+        }
+    }
+}
+
+/// Gets the checksum of the map, as recorded in replays.
+async fn checksum(resource_man: &ResourceManager, state: &GameState) -> u64 {
+    replay::checksum(
+        &resource_man.interner,
+        &state.map.tiles,
+        &all_data(state).await,
+        map_data(state),
+    )
+}
+
+/// Gets the map's data map, or the copy of it while the GUI has taken it.
+fn map_data(state: &GameState) -> &DataMap {
+    state.taken_data.as_ref().unwrap_or(&state.map.data)
+}
+
+/// Gets the data map of every tile entity, by their position.
+async fn all_data(state: &GameState) -> HashMap<TileCoord, DataMap> {
+    multi_call_iter(
+        state.tile_entities.values(),
+        state.tile_entities.len(),
+        TileEntityMsg::GetDataWithCoord,
+        None,
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(CallResult::unwrap)
+    .collect()
 }
 
 /// Makes a new tile and add it into both the map and the game
@@ -589,7 +1323,22 @@ async fn insert_new_tile(
 ) -> Option<(Id, TileModifier, Option<DataMap>)> {
     let old = remove_tile(state, coord).await;
 
-    let tile_entity = new_tile(resource_man, game, coord, id, tile_modifier).await;
+    // told before the tile entity exists, so that its data is never heard of before the tile
+    _ = state
+        .changes
+        .send(TileChange::Placed(coord, id, tile_modifier));
+
+    let tile_entity = new_tile(
+        resource_man,
+        game,
+        state.pending.clone(),
+        state.profiler.clone(),
+        state.changes.clone(),
+        coord,
+        id,
+        tile_modifier,
+    )
+    .await;
 
     if let Some(data) = data {
         tile_entity
@@ -603,11 +1352,252 @@ async fn insert_new_tile(
     old
 }
 
-fn inner_tick(state: &mut GameState) {
+/// Places a scenario's starting layout, and sets up the player inventory and the objectives. Maps that already have tiles are left alone.
+async fn start_scenario(
+    resource_man: Arc<ResourceManager>,
+    game: ActorRef<GameMsg>,
+    state: &mut GameState,
+    id: Id,
+) {
+    let Some(scenario) = resource_man.registry.scenario(id).cloned() else {
+        log::warn!("Tried to start a scenario that doesn't exist: {id:?}");
+        return;
+    };
+
+    if !state.map.tiles.is_empty() {
+        log::warn!(
+            "Not starting a scenario on map {}, as it already has tiles",
+            state.map.map_name
+        );
+        return;
+    }
+
+    for tile in scenario.tiles {
+        insert_new_tile(
+            resource_man.clone(),
+            game.clone(),
+            state,
+            tile.coord,
+            tile.id,
+            tile.tile_modifier,
+            Some(tile.data),
+        )
+        .await;
+    }
+
+    let data_ids = resource_man.registry.data_ids;
+
+    state.map.data.insert(
+        data_ids.player_inventory,
+        Data::Inventory(scenario.inventory),
+    );
+    state.map.data.insert(data_ids.scenario, Data::Id(id));
+    state.map.data.insert(
+        data_ids.objective_progress,
+        Data::VecAmount(vec![0; scenario.objectives.len()]),
+    );
+    state
+        .map
+        .data
+        .insert(data_ids.scenario_completed, Data::Bool(false));
+}
+
+/// Places the deposits of a new map around the tiles already on it, and records the seed in the map's data. Maps that were already generated are left alone.
+async fn generate_world(
+    resource_man: Arc<ResourceManager>,
+    game: ActorRef<GameMsg>,
+    state: &mut GameState,
+    settings: WorldGenSettings,
+) {
+    let data_ids = resource_man.registry.data_ids;
+
+    if state.map.data.contains_key(&data_ids.world_seed) {
+        log::warn!(
+            "Not generating map {}, as it was already generated",
+            state.map.map_name
+        );
+        return;
+    }
+
+    let occupied = state.map.tiles.keys().cloned().collect::<HashSet<_>>();
+
+    for (coord, id) in worldgen::generate(&resource_man, settings, &occupied) {
+        insert_new_tile(
+            resource_man.clone(),
+            game.clone(),
+            state,
+            coord,
+            id,
+            0,
+            None,
+        )
+        .await;
+    }
+
+    state
+        .map
+        .data
+        .insert(data_ids.world_seed, Data::Amount(settings.seed));
+
+    log::info!(
+        "Generated map {} with seed {}",
+        state.map.map_name,
+        settings.seed
+    );
+}
+
+/// Was the map saved before deposits existed? Maps that were generated, or that have no tiles yet, don't need them.
+fn needs_deposits(resource_man: &ResourceManager, state: &GameState) -> bool {
+    !state.map.tiles.is_empty()
+        && !state
+            .map
+            .data
+            .contains_key(&resource_man.registry.data_ids.world_seed)
+}
+
+/// Brings a map saved before deposits existed up to date. Each extractor gets the deposit its script needs on a free neighbor, if it has none next to it, and the rest of the world is generated around the tiles with the default settings.
+///
+/// Maps that don't [need deposits](needs_deposits) are left alone.
+async fn migrate_deposits(
+    resource_man: Arc<ResourceManager>,
+    game: ActorRef<GameMsg>,
+    state: &mut GameState,
+) {
+    let data_ids = resource_man.registry.data_ids;
+
+    if !needs_deposits(&resource_man, state) {
+        return;
+    }
+
+    let all_data = all_data(state).await;
+
+    let mut coords = all_data.keys().cloned().collect::<Vec<_>>();
+    coords.sort_by_key(|coord| (coord.q(), coord.r()));
+
+    for coord in coords {
+        let Some(deposit) = all_data[&coord]
+            .get(&data_ids.script)
+            .and_then(Data::as_id)
+            .and_then(|script| resource_man.registry.script(*script))
+            .and_then(|script| script.adjacent)
+            .filter(|adjacent| resource_man.registry.tile(*adjacent).is_some())
+        else {
+            continue;
+        };
+
+        let neighbors = coord.neighbors();
+
+        if neighbors.iter().any(|neighbor| {
+            state
+                .map
+                .tiles
+                .get(neighbor)
+                .map_or(false, |(id, _)| item_match(&resource_man, *id, deposit))
+        }) {
+            continue;
+        }
+
+        let Some(free) = neighbors
+            .into_iter()
+            .find(|neighbor| !state.map.tiles.contains_key(neighbor))
+        else {
+            log::warn!("No room for a deposit next to the extractor at {coord}");
+            continue;
+        };
+
+        insert_new_tile(
+            resource_man.clone(),
+            game.clone(),
+            state,
+            free,
+            deposit,
+            0,
+            None,
+        )
+        .await;
+    }
+
+    generate_world(resource_man, game, state, WorldGenSettings::default()).await;
+
+    log::info!("Migrated map {} to have deposits", state.map.map_name);
+}
+
+/// Counts the progress of the map's scenario objectives, and marks the scenario as completed once all of them are done.
+///
+/// The GUI takes the map's data map while drawing, so if it's not there, the update waits until it is put back.
+async fn update_objectives(resource_man: &ResourceManager, state: &mut GameState) {
+    let data_ids = resource_man.registry.data_ids;
+
+    let Some(scenario) = state
+        .map
+        .data
+        .get(&data_ids.scenario)
+        .and_then(Data::as_id)
+        .and_then(|id| resource_man.registry.scenario(*id))
+    else {
+        return;
+    };
+
+    state.objectives_due = false;
+
+    if state
+        .map
+        .data
+        .get(&data_ids.scenario_completed)
+        .and_then(Data::as_bool)
+        == Some(&true)
+    {
+        return;
+    }
+
+    let all_data = all_data(state).await;
+
+    let tiles = state
+        .map
+        .tiles
+        .iter()
+        .flat_map(|(coord, (id, _))| all_data.get(coord).map(|data| (*id, data)))
+        .collect::<Vec<_>>();
+
+    let progress = scenario
+        .objectives
+        .iter()
+        .map(|objective| objective.progress(resource_man, tiles.iter().cloned()))
+        .collect::<Vec<_>>();
+
+    let completed = scenario
+        .objectives
+        .iter()
+        .zip(&progress)
+        .all(|(objective, progress)| *progress >= objective.required());
+
+    state
+        .map
+        .data
+        .insert(data_ids.objective_progress, Data::VecAmount(progress));
+
+    if completed {
+        state
+            .map
+            .data
+            .insert(data_ids.scenario_completed, Data::Bool(true));
+
+        log::info!("Completed the scenario on map {}!", state.map.map_name);
+    }
+}
+
+fn inner_tick(state: &mut GameState, seed: ItemAmount) {
+    state.signals = Arc::new(signal::gather(&mut state.publications, state.tick_count));
+
     state.tile_entities.par_iter().for_each(|(_, tile_entity)| {
+        state.pending.fetch_add(1, Ordering::SeqCst);
+
         if let Err(e) = tile_entity.send_message(TileEntityMsg::Tick {
             tick_count: state.tick_count,
+            signals: state.signals.clone(),
+            seed,
         }) {
+            mark_handled(&state.pending);
             log::error!("{e:?}");
         }
     });
@@ -615,10 +1605,14 @@ fn inner_tick(state: &mut GameState) {
     state.tick_count = state.tick_count.wrapping_add(1);
 }
 
-/// Runs the game for one tick, logging if the tick is too long.
-pub fn tick(state: &mut GameState) {
+/// Runs the game for one tick, with the seed of the map, logging if the tick is too long.
+pub fn tick(state: &mut GameState, seed: ItemAmount) {
+    state
+        .profiler
+        .begin_tick(state.tick_count, state.pending.load(Ordering::SeqCst));
+
     let start = Instant::now();
-    inner_tick(state);
+    inner_tick(state, seed);
     let finish = Instant::now();
 
     let tick_time = finish - start;
@@ -637,12 +1631,24 @@ impl Default for GameState {
         Self {
             tick_count: 0,
             stopped: false,
+            paused: false,
 
             map: Map::new_empty("".to_string()),
+            taken_data: None,
             tile_entities: Default::default(),
 
             undo_steps: Default::default(),
             transaction_records: Arc::new(Default::default()),
+            pending: Arc::new(Default::default()),
+            profiler: Arc::new(Default::default()),
+            objectives_due: false,
+            recorder: None,
+            statistics: Default::default(),
+            signals: Default::default(),
+            publications: Default::default(),
+            logistics: Default::default(),
+            changes: broadcast::channel(CHANGES_BUFFER_SIZE).0,
+            remote: None,
         }
     }
 }
@@ -21,6 +21,8 @@ pub static DEFAULT_KEYMAP: &[(VirtualKeyCode, KeyAction)] = &[
     (VirtualKeyCode::F1, actions::HIDE_GUI),
     (VirtualKeyCode::F2, actions::SCREENSHOT),
     (VirtualKeyCode::E, actions::PLAYER),
+    (VirtualKeyCode::P, actions::STATISTICS),
+    (VirtualKeyCode::L, actions::LOGISTICS),
 ];
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -32,6 +34,8 @@ pub enum KeyActions {
     Screenshot,
     HideGui,
     Player,
+    Statistics,
+    Logistics,
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -78,6 +82,14 @@ pub mod actions {
         action: KeyActions::Player,
         press_type: PressTypes::Toggle,
     };
+    pub static STATISTICS: KeyAction = KeyAction {
+        action: KeyActions::Statistics,
+        press_type: PressTypes::Toggle,
+    };
+    pub static LOGISTICS: KeyAction = KeyAction {
+        action: KeyActions::Logistics,
+        press_type: PressTypes::Toggle,
+    };
 }
 
 /// The various controls of the game.
@@ -3,7 +3,14 @@ pub mod camera;
 pub mod game;
 pub mod gpu;
 pub mod input;
+pub mod logistics;
 pub mod map;
+pub mod net;
 pub mod options;
+pub mod profiler;
+pub mod replay;
+pub mod signal;
+pub mod statistics;
 pub mod tile_entity;
 pub mod util;
+pub mod worldgen;
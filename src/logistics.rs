@@ -0,0 +1,357 @@
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::{HashMap, HashSet};
+use automancy_defs::id::Id;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::stack::ItemAmount;
+
+/// How many ticks a requester waits after it was served, before its requests are taken again.
+pub const REQUEST_COOLDOWN: u64 = 10;
+/// How many ticks links, advertisements and requests are kept for without being reported again.
+pub const REPORT_TIMEOUT: u64 = 30;
+
+/// Items wanted by a tile on a logistics network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Request {
+    pub requester: TileCoord,
+    pub item: Id,
+    pub amount: ItemAmount,
+}
+
+/// Items sent from a storage to a requester.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub storage: TileCoord,
+    pub requester: TileCoord,
+    pub item: Id,
+    pub amount: ItemAmount,
+    /// the tiles the items go through, from the tile providing the storage to the requester
+    pub path: Vec<TileCoord>,
+}
+
+/// A logistics network, as shown to the player.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetworkInfo {
+    /// the tiles in the network, ordered by position
+    pub tiles: Vec<TileCoord>,
+    /// what the storages on the network have
+    pub contents: Inventory,
+    /// the requests that are waiting to be served
+    pub requests: Vec<Request>,
+}
+
+#[derive(Debug, Clone)]
+struct Advertisement {
+    storage: TileCoord,
+    contents: Inventory,
+    reported: u64,
+}
+
+/// Routes items from the storages on logistics networks to the tiles requesting them.
+///
+/// A network is made of the tiles linked to each other. Tiles providing a storage advertise its contents, and requesters post what they want, both every tick.
+/// Each tick, the oldest requests are served first by the nearest provider over the links that has the item, every provider serves at most one request, and requesters that were served wait [`REQUEST_COOLDOWN`] ticks.
+#[derive(Debug, Clone, Default)]
+pub struct Logistics {
+    ticks: u64,
+    /// the tile each networked tile is linked to, and when it was reported
+    links: HashMap<TileCoord, (TileCoord, u64)>,
+    /// the storage of each provider
+    providers: HashMap<TileCoord, Advertisement>,
+    /// the open requests, and when they were posted
+    requests: HashMap<(TileCoord, Id), (ItemAmount, u64)>,
+    /// when each requester was last served
+    served: HashMap<TileCoord, u64>,
+    /// the deliveries that are yet to reach their requesters, with the storage they come from and when they were made
+    in_flight: HashMap<(TileCoord, Id), (TileCoord, ItemAmount, u64)>,
+}
+
+fn coord_order(coord: &TileCoord) -> (i32, i32) {
+    (coord.q() as i32, coord.r() as i32)
+}
+
+impl Logistics {
+    pub fn report_link(&mut self, coord: TileCoord, link: TileCoord) {
+        self.links.insert(coord, (link, self.ticks));
+    }
+
+    pub fn advertise(&mut self, provider: TileCoord, storage: TileCoord, contents: Inventory) {
+        self.providers.insert(
+            provider,
+            Advertisement {
+                storage,
+                contents,
+                reported: self.ticks,
+            },
+        );
+    }
+
+    /// Opens a request, or updates the amount of an open one. Requests from a requester that was just served are dropped, and what is still being delivered to the requester is taken off the amount.
+    pub fn post(&mut self, mut request: Request) {
+        if self
+            .served
+            .get(&request.requester)
+            .map_or(false, |served| self.ticks < served + REQUEST_COOLDOWN)
+        {
+            return;
+        }
+
+        if let Some((_, amount, _)) = self.in_flight.get(&(request.requester, request.item)) {
+            request.amount -= amount;
+        }
+
+        if request.amount <= 0 {
+            self.requests.remove(&(request.requester, request.item));
+            return;
+        }
+
+        let posted = self
+            .requests
+            .get(&(request.requester, request.item))
+            .map_or(self.ticks, |(_, posted)| *posted);
+
+        self.requests
+            .insert((request.requester, request.item), (request.amount, posted));
+    }
+
+    /// Marks the delivery of the item to the requester as arrived, once the storage it comes from sends it on.
+    pub fn arrived(&mut self, requester: TileCoord, storage: TileCoord, item: Id) {
+        if self
+            .in_flight
+            .get(&(requester, item))
+            .map_or(false, |(from, _, _)| *from == storage)
+        {
+            self.in_flight.remove(&(requester, item));
+        }
+    }
+
+    /// Forgets a tile that was removed from the map, along with the links to it.
+    pub fn remove(&mut self, coord: TileCoord) {
+        self.links
+            .retain(|linked, (link, _)| *linked != coord && *link != coord);
+        self.providers.remove(&coord);
+        self.providers.retain(|_, ad| ad.storage != coord);
+        self.requests
+            .retain(|(requester, _), _| *requester != coord);
+        self.served.remove(&coord);
+        self.in_flight
+            .retain(|(requester, _), (storage, _, _)| *requester != coord && *storage != coord);
+    }
+
+    fn neighbors(&self) -> HashMap<TileCoord, Vec<TileCoord>> {
+        let mut neighbors = HashMap::<TileCoord, Vec<TileCoord>>::new();
+
+        for (coord, (link, _)) in &self.links {
+            neighbors.entry(*coord).or_default().push(*link);
+            neighbors.entry(*link).or_default().push(*coord);
+        }
+
+        for list in neighbors.values_mut() {
+            list.sort_by_key(coord_order);
+            list.dedup();
+        }
+
+        neighbors
+    }
+
+    /// Finds the shortest path over the links between two tiles, including both.
+    pub fn path(&self, from: TileCoord, to: TileCoord) -> Option<Vec<TileCoord>> {
+        shortest_path(&self.neighbors(), from, to)
+    }
+
+    /// Drops what wasn't reported again in time, and matches the open requests with the providers.
+    pub fn tick(&mut self) -> Vec<Delivery> {
+        self.ticks += 1;
+
+        let ticks = self.ticks;
+        self.links
+            .retain(|_, (_, reported)| ticks <= *reported + REPORT_TIMEOUT);
+        self.providers
+            .retain(|_, ad| ticks <= ad.reported + REPORT_TIMEOUT);
+        self.requests
+            .retain(|_, (_, posted)| ticks <= *posted + REPORT_TIMEOUT);
+        // a delivery the storage no longer had the items for never arrives
+        self.in_flight
+            .retain(|_, (_, _, made)| ticks <= *made + REPORT_TIMEOUT);
+
+        let neighbors = self.neighbors();
+
+        // the oldest requests are served first
+        let mut requests = self
+            .requests
+            .iter()
+            .map(|((requester, item), (amount, posted))| {
+                (
+                    *posted,
+                    Request {
+                        requester: *requester,
+                        item: *item,
+                        amount: *amount,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        requests.sort_by_key(|(posted, request)| {
+            (*posted, coord_order(&request.requester), request.item)
+        });
+
+        let mut providers = self.providers.keys().cloned().collect::<Vec<_>>();
+        providers.sort_by_key(coord_order);
+
+        let mut busy = HashSet::new();
+        let mut deliveries = vec![];
+
+        for (_, request) in requests {
+            if self
+                .served
+                .get(&request.requester)
+                .map_or(false, |served| self.ticks < served + REQUEST_COOLDOWN)
+            {
+                continue;
+            }
+
+            let nearest = providers
+                .iter()
+                .filter(|provider| !busy.contains(*provider))
+                .filter(|provider| {
+                    self.providers[*provider]
+                        .contents
+                        .deref()
+                        .get(&request.item)
+                        .map_or(false, |amount| *amount > 0)
+                })
+                .flat_map(|provider| {
+                    shortest_path(&neighbors, *provider, request.requester)
+                        .map(|path| (*provider, path))
+                })
+                .min_by_key(|(_, path)| path.len());
+
+            let Some((provider, path)) = nearest else {
+                continue;
+            };
+
+            let ad = self.providers.get_mut(&provider).unwrap();
+            let available = ad.contents.get_mut(request.item);
+            let amount = request.amount.min(*available);
+            *available -= amount;
+
+            busy.insert(provider);
+            self.served.insert(request.requester, self.ticks);
+            self.requests.remove(&(request.requester, request.item));
+            self.in_flight.insert(
+                (request.requester, request.item),
+                (ad.storage, amount, self.ticks),
+            );
+
+            deliveries.push(Delivery {
+                storage: ad.storage,
+                requester: request.requester,
+                item: request.item,
+                amount,
+                path,
+            });
+        }
+
+        deliveries
+    }
+
+    /// Gets every network, with what its storages have and the requests waiting on it.
+    pub fn networks(&self) -> Vec<NetworkInfo> {
+        let neighbors = self.neighbors();
+
+        let mut coords = neighbors
+            .keys()
+            .chain(self.providers.keys())
+            .chain(self.requests.keys().map(|(requester, _)| requester))
+            .cloned()
+            .collect::<Vec<_>>();
+        coords.sort_by_key(coord_order);
+        coords.dedup();
+
+        let mut seen = HashSet::new();
+        let mut networks = vec![];
+
+        for start in coords {
+            if !seen.insert(start) {
+                continue;
+            }
+
+            let mut tiles = vec![start];
+            let mut queue = VecDeque::from([start]);
+
+            while let Some(coord) = queue.pop_front() {
+                for neighbor in neighbors.get(&coord).into_iter().flatten() {
+                    if seen.insert(*neighbor) {
+                        tiles.push(*neighbor);
+                        queue.push_back(*neighbor);
+                    }
+                }
+            }
+
+            tiles.sort_by_key(coord_order);
+
+            let mut contents = Inventory::default();
+            for ad in tiles.iter().flat_map(|coord| self.providers.get(coord)) {
+                for (id, amount) in ad.contents.iter() {
+                    contents.add(*id, *amount);
+                }
+            }
+
+            let mut requests = self
+                .requests
+                .iter()
+                .filter(|((requester, _), _)| tiles.contains(requester))
+                .map(|((requester, item), (amount, _))| Request {
+                    requester: *requester,
+                    item: *item,
+                    amount: *amount,
+                })
+                .collect::<Vec<_>>();
+            requests.sort_by_key(|request| (coord_order(&request.requester), request.item));
+
+            networks.push(NetworkInfo {
+                tiles,
+                contents,
+                requests,
+            });
+        }
+
+        networks
+    }
+}
+
+fn shortest_path(
+    neighbors: &HashMap<TileCoord, Vec<TileCoord>>,
+    from: TileCoord,
+    to: TileCoord,
+) -> Option<Vec<TileCoord>> {
+    let mut previous = HashMap::from([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(coord) = queue.pop_front() {
+        if coord == to {
+            let mut path = vec![to];
+            let mut current = to;
+
+            while current != from {
+                current = previous[&current];
+                path.push(current);
+            }
+
+            path.reverse();
+
+            return Some(path);
+        }
+
+        for neighbor in neighbors.get(&coord).into_iter().flatten() {
+            if !previous.contains_key(neighbor) {
+                previous.insert(*neighbor, coord);
+                queue.push_back(*neighbor);
+            }
+        }
+    }
+
+    None
+}
@@ -21,12 +21,16 @@ use automancy_defs::id::{Id, Interner};
 use automancy_defs::log;
 use automancy_resources::chrono::Local;
 use automancy_resources::data::{DataMap, DataMapRaw};
+use automancy_resources::format::FormatArgs;
 use automancy_resources::ResourceManager;
 
 use crate::game;
-use crate::game::GameMsg;
+use crate::game::{Changes, GameMsg, PendingMessages};
+use crate::profiler::Profiler;
 use crate::tile_entity::{TileEntityMsg, TileModifier};
 
+pub mod diff;
+
 pub const MAP_PATH: &str = "map";
 pub const MAP_EXT: &str = ".zst";
 pub const HEADER_EXT: &str = ".ron";
@@ -130,7 +134,9 @@ impl Map {
                 resource_man.error_man.push(
                     (
                         resource_man.registry.err_ids.invalid_map_data,
-                        vec![map_name.to_string(), err_map_name],
+                        FormatArgs::new()
+                            .with("map_name", map_name)
+                            .with("backup_name", err_map_name),
                     ),
                     resource_man,
                 );
@@ -162,7 +168,9 @@ impl Map {
                 resource_man.error_man.push(
                     (
                         resource_man.registry.err_ids.invalid_map_data,
-                        vec![map_name.to_string(), err_map_name],
+                        FormatArgs::new()
+                            .with("map_name", map_name)
+                            .with("backup_name", err_map_name),
                     ),
                     resource_man,
                 );
@@ -175,6 +183,9 @@ impl Map {
     /// Loads a map from disk.
     pub async fn load(
         game: ActorRef<GameMsg>,
+        pending: PendingMessages,
+        profiler: Arc<Profiler>,
+        changes: Changes,
         resource_man: Arc<ResourceManager>,
         map_name: &str,
     ) -> (Self, TileEntities) {
@@ -196,9 +207,17 @@ impl Map {
                 .get(&id)
                 .and_then(|id| resource_man.interner.get(id.as_str()))
             {
-                let tile_entity =
-                    game::new_tile(resource_man.clone(), game.clone(), coord, id, tile_modifier)
-                        .await;
+                let tile_entity = game::new_tile(
+                    resource_man.clone(),
+                    game.clone(),
+                    pending.clone(),
+                    profiler.clone(),
+                    changes.clone(),
+                    coord,
+                    id,
+                    tile_modifier,
+                )
+                .await;
                 let data = data.to_data(&resource_man.interner).into_inner();
 
                 for (key, value) in data {
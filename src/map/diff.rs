@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+use automancy_defs::coord::{TileCoord, TileUnit};
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::IdRaw;
+use automancy_defs::log;
+use automancy_resources::data::stack::ItemAmount;
+use automancy_resources::data::{DataMapRaw, DataRaw};
+use automancy_resources::ResourceManager;
+
+use crate::map::{Map, SerdeTile};
+use crate::tile_entity::TileModifier;
+
+/// A map as it is saved on disk, with its ids as `namespace:name`, so that maps saved by different runs can be compared.
+#[derive(Debug, Clone, Default)]
+pub struct SavedMap {
+    pub tiles: HashMap<TileCoord, (IdRaw, TileModifier, DataMapRaw)>,
+    pub data: DataMapRaw,
+    /// The tiles whose ids are missing from the map's header, which are left out of `tiles`.
+    pub unknown: Vec<TileCoord>,
+}
+
+impl SavedMap {
+    /// Reads a map's header and tiles, without starting any tile entities.
+    pub fn read(resource_man: &ResourceManager, map_name: &str) -> Option<Self> {
+        let (header, _) = Map::read_header(resource_man, map_name)?;
+        let serde_tiles = Map::read_tiles(resource_man, map_name)?;
+
+        let id_reverse = header.tile_map.into_iter().collect::<HashMap<_, _>>();
+
+        let mut tiles = HashMap::new();
+        let mut unknown = Vec::new();
+
+        for (coord, SerdeTile(id, tile_modifier, data)) in serde_tiles {
+            match id_reverse.get(&id) {
+                Some(id) => {
+                    tiles.insert(coord, (IdRaw::parse(id), tile_modifier, data));
+                }
+                None => {
+                    log::warn!(
+                        "The tile at {coord} in map {map_name} has an id missing from its header"
+                    );
+                    unknown.push(coord);
+                }
+            }
+        }
+
+        unknown.sort_by_key(sort_key);
+
+        Some(Self {
+            tiles,
+            data: header.data,
+            unknown,
+        })
+    }
+}
+
+/// How a value in a data map changed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum DataChange {
+    Added {
+        value: DataRaw,
+    },
+    Removed {
+        value: DataRaw,
+    },
+    Changed {
+        old: DataRaw,
+        new: DataRaw,
+    },
+    /// An inventory changed, by how much of each item it gained or lost.
+    Inventory {
+        deltas: Vec<(IdRaw, ItemAmount)>,
+    },
+}
+
+/// A change to one key of a data map.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyChange {
+    pub key: String,
+    #[serde(flatten)]
+    pub change: DataChange,
+}
+
+/// A tile that is on only one of the maps.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffTile {
+    pub coord: TileCoord,
+    pub id: IdRaw,
+    pub tile_modifier: TileModifier,
+}
+
+/// A tile that was replaced by another tile, or turned to a different modifier.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangedTile {
+    pub coord: TileCoord,
+    pub old_id: IdRaw,
+    pub old_tile_modifier: TileModifier,
+    pub new_id: IdRaw,
+    pub new_tile_modifier: TileModifier,
+}
+
+/// The changes to the data map of a tile that is on both maps.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TileDataChanges {
+    pub coord: TileCoord,
+    pub changes: Vec<KeyChange>,
+}
+
+/// Everything that is different between two maps. Everything is sorted by position, then by key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MapDiff {
+    pub added: Vec<DiffTile>,
+    pub removed: Vec<DiffTile>,
+    pub changed: Vec<ChangedTile>,
+    pub tile_data: Vec<TileDataChanges>,
+    /// The changes to the data map of the map itself.
+    pub map_data: Vec<KeyChange>,
+}
+
+impl MapDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.tile_data.is_empty()
+            && self.map_data.is_empty()
+    }
+}
+
+fn sort_key(coord: &TileCoord) -> (TileUnit, TileUnit) {
+    (coord.q(), coord.r())
+}
+
+fn inventory_deltas(
+    old: Vec<(IdRaw, ItemAmount)>,
+    new: Vec<(IdRaw, ItemAmount)>,
+) -> Vec<(IdRaw, ItemAmount)> {
+    let mut deltas = BTreeMap::<String, (IdRaw, ItemAmount)>::new();
+
+    for (id, amount) in old {
+        deltas.entry(id.to_string()).or_insert((id, 0)).1 -= amount;
+    }
+    for (id, amount) in new {
+        deltas.entry(id.to_string()).or_insert((id, 0)).1 += amount;
+    }
+
+    deltas
+        .into_values()
+        .filter(|(_, delta)| *delta != 0)
+        .collect()
+}
+
+/// Puts a value in the order it is compared in. Sets are saved in no particular order, so they are sorted.
+fn normalize(value: DataRaw) -> DataRaw {
+    match value {
+        DataRaw::SetId(mut ids) => {
+            ids.sort_by_key(|id| id.to_string());
+
+            DataRaw::SetId(ids)
+        }
+        value => value,
+    }
+}
+
+fn normalized(data: &DataMapRaw) -> BTreeMap<String, DataRaw> {
+    HashMap::<String, DataRaw>::from(data.clone())
+        .into_iter()
+        .map(|(key, value)| (key, normalize(value)))
+        .collect()
+}
+
+/// Compares two data maps, key by key.
+pub fn diff_data(old: &DataMapRaw, new: &DataMapRaw) -> Vec<KeyChange> {
+    let old = normalized(old);
+    let mut new = normalized(new);
+
+    let mut changes = Vec::new();
+
+    for (key, old) in old {
+        let change = match new.remove(&key) {
+            None => DataChange::Removed { value: old },
+            Some(DataRaw::Inventory(new)) => match old {
+                DataRaw::Inventory(old) => {
+                    let deltas = inventory_deltas(old.into_inner(), new.into_inner());
+                    if deltas.is_empty() {
+                        continue;
+                    }
+
+                    DataChange::Inventory { deltas }
+                }
+                old => DataChange::Changed {
+                    old,
+                    new: DataRaw::Inventory(new),
+                },
+            },
+            Some(new) => {
+                if old == new {
+                    continue;
+                }
+
+                DataChange::Changed { old, new }
+            }
+        };
+
+        changes.push(KeyChange { key, change });
+    }
+
+    for (key, value) in new {
+        changes.push(KeyChange {
+            key,
+            change: DataChange::Added { value },
+        });
+    }
+
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+
+    changes
+}
+
+/// Compares two maps, tile by tile.
+pub fn diff(old: &SavedMap, new: &SavedMap) -> MapDiff {
+    let mut diff = MapDiff::default();
+
+    for (coord, (old_id, old_tile_modifier, old_data)) in &old.tiles {
+        match new.tiles.get(coord) {
+            None => diff.removed.push(DiffTile {
+                coord: *coord,
+                id: old_id.clone(),
+                tile_modifier: *old_tile_modifier,
+            }),
+            Some((new_id, new_tile_modifier, new_data)) => {
+                if old_id != new_id || old_tile_modifier != new_tile_modifier {
+                    diff.changed.push(ChangedTile {
+                        coord: *coord,
+                        old_id: old_id.clone(),
+                        old_tile_modifier: *old_tile_modifier,
+                        new_id: new_id.clone(),
+                        new_tile_modifier: *new_tile_modifier,
+                    });
+                }
+
+                let changes = diff_data(old_data, new_data);
+                if !changes.is_empty() {
+                    diff.tile_data.push(TileDataChanges {
+                        coord: *coord,
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+
+    for (coord, (id, tile_modifier, _)) in &new.tiles {
+        if !old.tiles.contains_key(coord) {
+            diff.added.push(DiffTile {
+                coord: *coord,
+                id: id.clone(),
+                tile_modifier: *tile_modifier,
+            });
+        }
+    }
+
+    diff.added.sort_by_key(|tile| sort_key(&tile.coord));
+    diff.removed.sort_by_key(|tile| sort_key(&tile.coord));
+    diff.changed.sort_by_key(|tile| sort_key(&tile.coord));
+    diff.tile_data.sort_by_key(|tile| sort_key(&tile.coord));
+
+    diff.map_data = diff_data(&old.data, &new.data);
+
+    diff
+}
+
+/// Writes a value the way a player would read it.
+fn fmt_value(value: &DataRaw) -> String {
+    match value {
+        DataRaw::Amount(v) => v.to_string(),
+        DataRaw::Bool(v) => v.to_string(),
+        DataRaw::Id(v) => v.to_string(),
+        DataRaw::Coord(v) => v.to_string(),
+        DataRaw::Inventory(v) => {
+            let items = v
+                .clone()
+                .into_inner()
+                .into_iter()
+                .map(|(id, amount)| format!("{id} x{amount}"))
+                .collect::<Vec<_>>();
+
+            format!("[{}]", items.join(", "))
+        }
+        DataRaw::VecId(v) | DataRaw::SetId(v) => {
+            let ids = v.iter().map(IdRaw::to_string).collect::<Vec<_>>();
+
+            format!("[{}]", ids.join(", "))
+        }
+        DataRaw::VecCoord(v) => {
+            let coords = v.iter().map(TileCoord::to_string).collect::<Vec<_>>();
+
+            format!("[{}]", coords.join(", "))
+        }
+        DataRaw::VecAmount(v) => format!("{v:?}"),
+    }
+}
+
+impl Display for KeyChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let key = &self.key;
+
+        match &self.change {
+            DataChange::Added { value } => write!(f, "+ {key}: {}", fmt_value(value)),
+            DataChange::Removed { value } => write!(f, "- {key}: {}", fmt_value(value)),
+            DataChange::Changed { old, new } => {
+                write!(f, "~ {key}: {} -> {}", fmt_value(old), fmt_value(new))
+            }
+            DataChange::Inventory { deltas } => {
+                let deltas = deltas
+                    .iter()
+                    .map(|(id, delta)| format!("{id} {delta:+}"))
+                    .collect::<Vec<_>>();
+
+                write!(f, "~ {key}: {}", deltas.join(", "))
+            }
+        }
+    }
+}
+
+impl Display for MapDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "The maps are the same.");
+        }
+
+        for tile in &self.added {
+            writeln!(f, "+ {} {} ({})", tile.coord, tile.id, tile.tile_modifier)?;
+        }
+        for tile in &self.removed {
+            writeln!(f, "- {} {} ({})", tile.coord, tile.id, tile.tile_modifier)?;
+        }
+        for tile in &self.changed {
+            writeln!(
+                f,
+                "~ {} {} ({}) -> {} ({})",
+                tile.coord,
+                tile.old_id,
+                tile.old_tile_modifier,
+                tile.new_id,
+                tile.new_tile_modifier
+            )?;
+        }
+
+        for tile in &self.tile_data {
+            writeln!(f, "{}:", tile.coord)?;
+
+            for change in &tile.changes {
+                writeln!(f, "    {change}")?;
+            }
+        }
+
+        if !self.map_data.is_empty() {
+            writeln!(f, "map:")?;
+
+            for change in &self.map_data {
+                writeln!(f, "    {change}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+use ractor::ActorRef;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::{HashMap, HashSet};
+use automancy_defs::id::IdRaw;
+use automancy_defs::log;
+use automancy_resources::data::inventory::InventoryRaw;
+use automancy_resources::data::{Data, DataMapRaw};
+use automancy_resources::ResourceManager;
+
+use crate::game::GameMsg;
+use crate::net::{id_from_raw, read_msg, write_msg, ClientMsg, PlayerId, PlayerToken, ServerMsg};
+use crate::tile_entity::{TileEntityMsg, TileModifier};
+
+/// The scratch map a server's map is mirrored on in the game.
+pub const MIRROR_MAP: &str = ".multiplayer";
+
+/// How many times a client tries to connect again after losing the connection, before giving up.
+const RECONNECT_ATTEMPTS: usize = 5;
+/// How long a client waits before each try.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// What a client knows about the map, from what the server has sent it.
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    pub tiles: HashMap<TileCoord, (IdRaw, TileModifier)>,
+    pub data: HashMap<TileCoord, DataMapRaw>,
+    /// The player's own inventory.
+    pub inventory: InventoryRaw,
+}
+
+impl World {
+    /// Updates the world with a message from the server.
+    pub fn apply(&mut self, msg: &ServerMsg) {
+        match msg {
+            ServerMsg::Snapshot { tiles, data } => {
+                self.tiles = tiles
+                    .iter()
+                    .map(|(coord, id, tile_modifier)| (*coord, (id.clone(), *tile_modifier)))
+                    .collect();
+                self.data = data.iter().cloned().collect();
+            }
+            ServerMsg::Update { tiles, data } => {
+                for (coord, tile) in tiles {
+                    match tile {
+                        Some(tile) => {
+                            self.tiles.insert(*coord, tile.clone());
+                        }
+                        None => {
+                            self.tiles.remove(coord);
+                            self.data.remove(coord);
+                        }
+                    }
+                }
+
+                self.data.extend(data.iter().cloned());
+            }
+            ServerMsg::Inventory(inventory) => {
+                self.inventory = inventory.clone();
+            }
+            ServerMsg::Welcome { .. } | ServerMsg::Rejected(_) => {}
+        }
+    }
+}
+
+/// A connection to a server, which keeps a copy of the map up to date without running the game itself.
+pub struct Client {
+    addr: SocketAddr,
+    name: String,
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+
+    pub player: PlayerId,
+    /// Sent back when reconnecting, to rejoin as the same player.
+    pub token: PlayerToken,
+    pub world: World,
+}
+
+impl Client {
+    /// Joins the server as a new player.
+    pub async fn connect(addr: SocketAddr, name: &str) -> anyhow::Result<Self> {
+        Self::join(addr, name, None).await
+    }
+
+    /// Joins the server, as the player the token was given to if there is one.
+    pub async fn join(
+        addr: SocketAddr,
+        name: &str,
+        token: Option<PlayerToken>,
+    ) -> anyhow::Result<Self> {
+        let (reader, mut writer) = TcpStream::connect(addr).await?.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        write_msg(
+            &mut writer,
+            &ClientMsg::Hello {
+                name: name.to_string(),
+                token,
+            },
+        )
+        .await?;
+
+        let (player, token) = match read_msg(&mut lines).await? {
+            Some(ServerMsg::Welcome { player, token }) => (player, token),
+            Some(ServerMsg::Rejected(reason)) => {
+                bail!("The server rejected the connection: {reason}")
+            }
+            Some(msg) => bail!("Expected a welcome from the server, got {msg:?}"),
+            None => bail!("The server closed the connection"),
+        };
+
+        Ok(Self {
+            addr,
+            name: name.to_string(),
+            lines,
+            writer,
+            player,
+            token,
+            world: World::default(),
+        })
+    }
+
+    /// Connects to the server again after losing the connection, as the same player. The world is sent again from scratch.
+    pub async fn reconnect(&mut self) -> anyhow::Result<()> {
+        *self = Self::join(self.addr, &self.name, Some(self.token)).await?;
+
+        Ok(())
+    }
+
+    pub async fn send(&mut self, msg: &ClientMsg) -> anyhow::Result<()> {
+        write_msg(&mut self.writer, msg).await
+    }
+
+    /// Waits for the next message from the server, and updates the world with it.
+    pub async fn recv(&mut self) -> anyhow::Result<ServerMsg> {
+        let msg = read_msg(&mut self.lines)
+            .await?
+            .ok_or_else(|| anyhow!("The server closed the connection"))?;
+
+        self.world.apply(&msg);
+
+        Ok(msg)
+    }
+
+    /// Handles messages from the server until the world matches the condition, failing if that takes longer than the timeout.
+    pub async fn wait_for(
+        &mut self,
+        timeout: Duration,
+        condition: impl Fn(&World) -> bool,
+    ) -> anyhow::Result<()> {
+        tokio::time::timeout(timeout, async {
+            while !condition(&self.world) {
+                self.recv().await?;
+            }
+
+            Ok::<_, anyhow::Error>(())
+        })
+        .await
+        .map_err(|_| anyhow!("The world did not change as expected in time"))?
+    }
+}
+
+/// Mirrors the server's map on a game, which stops ticking and sends what the player does to the server in its place.
+///
+/// Connects again if the connection is lost, and returns once the game stops, or the server can't be reached any more.
+pub async fn mirror(
+    resource_man: Arc<ResourceManager>,
+    game: ActorRef<GameMsg>,
+    mut client: Client,
+) -> anyhow::Result<()> {
+    let (commands, mut player) = mpsc::unbounded_channel();
+    game.send_message(GameMsg::SetRemote(Some(commands)))?;
+
+    let result = async {
+        loop {
+            tokio::select! {
+                command = player.recv() => {
+                    // the game lets go of it when it stops, or loads another map
+                    let Some(command) = command else {
+                        return Ok::<_, anyhow::Error>(());
+                    };
+
+                    if let Err(err) = client.send(&command).await {
+                        reconnect(&mut client, err).await?;
+                    }
+                }
+                msg = client.recv() => match msg {
+                    Ok(msg) => apply(&resource_man, &game, msg).await?,
+                    Err(err) => reconnect(&mut client, err).await?,
+                }
+            }
+        }
+    }
+    .await;
+
+    _ = game.send_message(GameMsg::SetRemote(None));
+
+    result
+}
+
+/// Connects to the server again after losing the connection, which sends the whole map again.
+async fn reconnect(client: &mut Client, err: anyhow::Error) -> anyhow::Result<()> {
+    log::warn!("Lost the connection to the server: {err:#}");
+
+    for attempt in 1..=RECONNECT_ATTEMPTS {
+        tokio::time::sleep(RECONNECT_DELAY).await;
+
+        match client.reconnect().await {
+            Ok(()) => {
+                log::info!("Connected to the server again");
+                return Ok(());
+            }
+            Err(err) => log::warn!("Could not connect to the server again, try {attempt}: {err:#}"),
+        }
+    }
+
+    bail!("Gave up connecting to the server again")
+}
+
+/// Places a tile the server has on the game, or removes it if there is none. Tiles the resources don't have are left out.
+fn place(
+    resource_man: &ResourceManager,
+    coord: TileCoord,
+    tile: Option<(IdRaw, TileModifier)>,
+) -> Option<GameMsg> {
+    let (id, tile_modifier) = match tile {
+        Some((id, tile_modifier)) => (id_from_raw(resource_man, &id)?, tile_modifier),
+        None => (resource_man.registry.none, 0),
+    };
+
+    Some(GameMsg::PlaceTile {
+        coord,
+        id,
+        tile_modifier,
+        data: None,
+        record: false,
+        reply: None,
+    })
+}
+
+/// Makes the change the server told of on the game.
+async fn apply(
+    resource_man: &ResourceManager,
+    game: &ActorRef<GameMsg>,
+    msg: ServerMsg,
+) -> anyhow::Result<()> {
+    let interner = &resource_man.interner;
+
+    let (tiles, data) = match msg {
+        ServerMsg::Snapshot { tiles, data } => {
+            let tiles = tiles
+                .into_iter()
+                .map(|(coord, id, tile_modifier)| (coord, Some((id, tile_modifier))))
+                .collect::<Vec<_>>();
+
+            // what the server doesn't have any more goes
+            let kept = tiles
+                .iter()
+                .map(|(coord, _)| *coord)
+                .collect::<HashSet<_>>();
+            let removed = game
+                .call(GameMsg::GetTiles, None)
+                .await?
+                .unwrap()
+                .into_keys()
+                .filter(|coord| !kept.contains(coord))
+                .map(|coord| (coord, None))
+                .collect::<Vec<_>>();
+
+            (removed.into_iter().chain(tiles).collect(), data)
+        }
+        ServerMsg::Update { tiles, data } => (tiles, data),
+        ServerMsg::Inventory(inventory) => {
+            game.send_message(GameMsg::ApplyRemote(vec![GameMsg::SetData(
+                resource_man.registry.data_ids.player_inventory,
+                Data::Inventory(inventory.to_inventory(interner)),
+            )]))?;
+
+            return Ok(());
+        }
+        ServerMsg::Rejected(reason) => {
+            log::warn!("The server rejected what the player did: {reason}");
+
+            return Ok(());
+        }
+        ServerMsg::Welcome { .. } => return Ok(()),
+    };
+
+    let mut msgs = tiles
+        .into_iter()
+        .flat_map(|(coord, tile)| place(resource_man, coord, tile))
+        .collect::<Vec<_>>();
+
+    // after the tiles, so that they are there to take it
+    msgs.extend(data.into_iter().map(|(coord, data)| {
+        GameMsg::ForwardMsgToTile(coord, TileEntityMsg::SetData(data.to_data(interner)))
+    }));
+
+    game.send_message(GameMsg::ApplyRemote(msgs))?;
+
+    Ok(())
+}
@@ -0,0 +1,346 @@
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use ractor::ActorRef;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::{JoinHandle, JoinSet};
+
+use automancy_defs::coord::{TileCoord, TileRange};
+use automancy_defs::id::IdRaw;
+use automancy_defs::log;
+use automancy_resources::data::DataRaw;
+use automancy_resources::ResourceManager;
+
+use crate::game::{GameMsg, PlaceTileResponse, TickUnit};
+use crate::net::{id_from_raw, id_raw};
+use crate::tile_entity::{TileEntityMsg, TileModifier};
+
+/// The environment variable the game reads the path of the control socket from. The control server only runs if it is set.
+pub const CONTROL_SOCKET_ENV: &str = "AUTOMANCY_CONTROL_SOCKET";
+/// The most ticks one `step` request can run.
+pub const MAX_STEP_TICKS: TickUnit = 1000;
+
+/// A request to the control server, sent as one line of JSON, such as `{"request": "get_tile", "coord": [0, 0]}`.
+/// Ids are written as `namespace:name`, and coordinates as `[q, r]`. See `CONTROL_API.md` for the full schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Places a tile, replacing whatever was there.
+    PlaceTile {
+        coord: TileCoord,
+        id: IdRaw,
+        #[serde(default)]
+        tile_modifier: TileModifier,
+    },
+    RemoveTile {
+        coord: TileCoord,
+    },
+    GetTile {
+        coord: TileCoord,
+    },
+    /// Gets the data map of a tile.
+    GetTileData {
+        coord: TileCoord,
+    },
+    /// Sets a value in the data map of a tile, the way the tile config does.
+    SetTileData {
+        coord: TileCoord,
+        key: IdRaw,
+        value: DataRaw,
+    },
+    /// Gets the data map of the map itself, such as the player's inventory.
+    GetMapData,
+    /// Lists the tiles between the two corners, inclusive.
+    ListTiles {
+        start: TileCoord,
+        end: TileCoord,
+    },
+    Save,
+    /// Stops the game from ticking on its own.
+    Pause,
+    Resume,
+    /// Ticks the game, even while paused.
+    Step {
+        #[serde(default = "one")]
+        ticks: TickUnit,
+    },
+}
+
+fn one() -> TickUnit {
+    1
+}
+
+/// The reply to a request, sent as one line of JSON: either `{"ok": ...}` with the result, or `{"error": "..."}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlResponse {
+    Ok(Value),
+    Error(String),
+}
+
+/// A tile, as the control server describes it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlTile {
+    pub coord: TileCoord,
+    pub id: IdRaw,
+    pub tile_modifier: TileModifier,
+}
+
+struct Shared {
+    resource_man: Arc<ResourceManager>,
+    game: ActorRef<GameMsg>,
+}
+
+/// Lets local programs control a running game, with JSON requests over a Unix socket.
+pub struct ControlServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// Starts listening on a socket at the path. A socket left behind at the path is replaced, but any other file is not.
+    pub fn start(
+        path: impl Into<PathBuf>,
+        resource_man: Arc<ResourceManager>,
+        game: ActorRef<GameMsg>,
+    ) -> anyhow::Result<Self> {
+        let path = path.into();
+
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            if metadata.file_type().is_socket() {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        let shared = Arc::new(Shared { resource_man, game });
+
+        log::info!("Listening for control requests on {}", path.display());
+
+        Ok(Self {
+            path,
+            task: tokio::spawn(accept(shared, listener)),
+        })
+    }
+
+    /// The path of the socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stops listening, disconnects everyone, and removes the socket.
+    pub async fn stop(self) {
+        self.task.abort();
+        _ = self.task.await;
+
+        _ = fs::remove_file(&self.path);
+    }
+}
+
+async fn accept(shared: Arc<Shared>, listener: UnixListener) {
+    // dropped along with the task, which disconnects everyone
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    connections.spawn(connection(shared.clone(), stream));
+                }
+                Err(err) => log::warn!("Could not accept a control connection: {err}"),
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+async fn connection(shared: Arc<Shared>, stream: UnixStream) {
+    if let Err(err) = serve(&shared, stream).await {
+        log::warn!("A control connection ended: {err:#}");
+    }
+}
+
+/// Answers the requests on one connection, in order, until it is closed.
+async fn serve(shared: &Shared, stream: UnixStream) -> anyhow::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => match handle(shared, request).await {
+                Ok(value) => ControlResponse::Ok(value),
+                Err(err) => ControlResponse::Error(format!("{err:#}")),
+            },
+            Err(err) => ControlResponse::Error(format!("Invalid request: {err}")),
+        };
+
+        let mut line = serde_json::to_string(&response)?;
+        line.push('\n');
+
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+    }
+
+    Ok(())
+}
+
+/// Acts on a request, and returns its result.
+async fn handle(shared: &Shared, request: ControlRequest) -> anyhow::Result<Value> {
+    let resource_man = &shared.resource_man;
+    let game = &shared.game;
+
+    let place = |coord, id, tile_modifier| async move {
+        let response = game
+            .call(
+                |reply| GameMsg::PlaceTile {
+                    coord,
+                    id,
+                    tile_modifier,
+                    data: None,
+                    record: true,
+                    reply: Some(reply),
+                },
+                None,
+            )
+            .await?
+            .unwrap();
+
+        Ok::<_, anyhow::Error>(json!(match response {
+            PlaceTileResponse::Placed => "placed",
+            PlaceTileResponse::Removed => "removed",
+            PlaceTileResponse::Ignored => "ignored",
+        }))
+    };
+
+    let tile_entity = |coord: TileCoord| async move {
+        game.call(|reply| GameMsg::GetTileEntity(coord, reply), None)
+            .await?
+            .unwrap()
+            .ok_or_else(|| anyhow!("There is no tile at {coord}"))
+    };
+
+    match request {
+        ControlRequest::PlaceTile {
+            coord,
+            id,
+            tile_modifier,
+        } => {
+            let Some(tile) = id_from_raw(resource_man, &id)
+                .filter(|tile| resource_man.registry.tile(*tile).is_some())
+            else {
+                bail!("{id} is not a tile");
+            };
+
+            place(coord, tile, tile_modifier).await
+        }
+        ControlRequest::RemoveTile { coord } => place(coord, resource_man.registry.none, 0).await,
+        ControlRequest::GetTile { coord } => {
+            let tile = game
+                .call(|reply| GameMsg::GetTile(coord, reply), None)
+                .await?
+                .unwrap()
+                .and_then(|(id, tile_modifier)| {
+                    id_raw(resource_man, id).map(|id| ControlTile {
+                        coord,
+                        id,
+                        tile_modifier,
+                    })
+                });
+
+            Ok(serde_json::to_value(tile)?)
+        }
+        ControlRequest::GetTileData { coord } => {
+            let data = tile_entity(coord)
+                .await?
+                .call(TileEntityMsg::GetData, None)
+                .await?
+                .unwrap();
+
+            Ok(serde_json::to_value(data.to_raw(&resource_man.interner))?)
+        }
+        ControlRequest::SetTileData { coord, key, value } => {
+            let Some(key) = id_from_raw(resource_man, &key) else {
+                bail!("{key} is not a data key");
+            };
+            let Some(value) = value.try_to_data(&resource_man.interner) else {
+                bail!("The value refers to something that doesn't exist");
+            };
+
+            tile_entity(coord).await?;
+            game.send_message(GameMsg::SetTileData {
+                coord,
+                key,
+                value: Some(value),
+            })?;
+
+            Ok(Value::Null)
+        }
+        ControlRequest::GetMapData => {
+            let data = game.call(GameMsg::GetDataMap, None).await?.unwrap();
+
+            Ok(serde_json::to_value(data.to_raw(&resource_man.interner))?)
+        }
+        ControlRequest::ListTiles { start, end } => {
+            let range = TileRange::new(start, end);
+
+            let mut tiles = game
+                .call(GameMsg::GetTiles, None)
+                .await?
+                .unwrap()
+                .into_iter()
+                .filter(|(coord, _)| range.contains(*coord))
+                .flat_map(|(coord, (id, tile_modifier))| {
+                    id_raw(resource_man, id).map(|id| ControlTile {
+                        coord,
+                        id,
+                        tile_modifier,
+                    })
+                })
+                .collect::<Vec<_>>();
+            tiles.sort_by_key(|tile| (tile.coord.q(), tile.coord.r()));
+
+            Ok(serde_json::to_value(tiles)?)
+        }
+        ControlRequest::Save => {
+            game.call(|reply| GameMsg::SaveMap(resource_man.clone(), reply), None)
+                .await?
+                .unwrap();
+
+            Ok(Value::Null)
+        }
+        ControlRequest::Pause => {
+            game.send_message(GameMsg::SetPaused(true))?;
+
+            Ok(Value::Null)
+        }
+        ControlRequest::Resume => {
+            game.send_message(GameMsg::SetPaused(false))?;
+
+            Ok(Value::Null)
+        }
+        ControlRequest::Step { ticks } => {
+            if ticks > MAX_STEP_TICKS {
+                bail!("Can't step more than {MAX_STEP_TICKS} ticks at once");
+            }
+
+            for _ in 0..ticks {
+                game.send_message(GameMsg::Step)?;
+            }
+
+            // handled after the steps, so the count includes them
+            let tick_count = game.call(GameMsg::GetTickCount, None).await?.unwrap();
+
+            Ok(json!({ "tick_count": tick_count }))
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncWrite, AsyncWriteExt, Lines};
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::{Id, IdRaw};
+use automancy_resources::data::inventory::InventoryRaw;
+use automancy_resources::data::stack::ItemAmount;
+use automancy_resources::data::{DataMapRaw, DataRaw};
+use automancy_resources::ResourceManager;
+
+use crate::tile_entity::TileModifier;
+
+pub mod client;
+#[cfg(unix)]
+pub mod control;
+pub mod server;
+
+/// The port servers listen on, unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Identifies a player on the map they joined.
+pub type PlayerId = u64;
+/// The secret a client is given when it joins, which it sends back to rejoin as the same player.
+pub type PlayerToken = u64;
+
+/// What a client can ask the server to do. Ids are sent as `namespace:name`, since each process interns them on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMsg {
+    /// Joins the game as the named player, or rejoins as the player the token was given to.
+    Hello {
+        name: String,
+        token: Option<PlayerToken>,
+    },
+    /// Places a tile, or removes it if the id is `automancy:none`.
+    PlaceTile {
+        coord: TileCoord,
+        id: IdRaw,
+        tile_modifier: TileModifier,
+    },
+    MoveTiles {
+        coords: Vec<TileCoord>,
+        direction: TileCoord,
+    },
+    /// Always rejected, as the game has one undo history shared by every player.
+    Undo,
+    /// Sets a value in the data map of a tile, the way the tile config does, or removes it if there is none.
+    SetTileData {
+        coord: TileCoord,
+        key: IdRaw,
+        value: Option<DataRaw>,
+    },
+    /// Takes items out of a tile's buffer, into the player's own inventory.
+    TakeItems {
+        coord: TileCoord,
+        item: IdRaw,
+        amount: ItemAmount,
+    },
+}
+
+/// What the server tells its clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMsg {
+    /// Sent once the player has joined.
+    Welcome {
+        player: PlayerId,
+        token: PlayerToken,
+    },
+    /// Everything on the map. Sent on joining, and replaces what the client knew.
+    Snapshot {
+        tiles: Vec<(TileCoord, IdRaw, TileModifier)>,
+        data: Vec<(TileCoord, DataMapRaw)>,
+    },
+    /// What changed on the map since the last update. Tiles that are `None` were removed.
+    Update {
+        tiles: Vec<(TileCoord, Option<(IdRaw, TileModifier)>)>,
+        data: Vec<(TileCoord, DataMapRaw)>,
+    },
+    /// The player's own inventory.
+    Inventory(InventoryRaw),
+    /// A message the server could not act on.
+    Rejected(String),
+}
+
+/// Writes a message as a single line of RON.
+pub async fn write_msg<T: Serialize>(
+    writer: &mut (impl AsyncWrite + Unpin),
+    msg: &T,
+) -> anyhow::Result<()> {
+    let mut line = ron::to_string(msg)?;
+    line.push('\n');
+
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// Reads the next message, or `None` if the other side closed the connection. Safe to cancel, so it can be raced against other events.
+pub async fn read_msg<T: DeserializeOwned>(
+    lines: &mut Lines<impl AsyncBufRead + Unpin>,
+) -> anyhow::Result<Option<T>> {
+    match lines.next_line().await? {
+        Some(line) => Ok(Some(ron::from_str(&line)?)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn id_raw(resource_man: &ResourceManager, id: Id) -> Option<IdRaw> {
+    resource_man.interner.resolve(id).map(IdRaw::parse)
+}
+
+pub(crate) fn id_from_raw(resource_man: &ResourceManager, id: &IdRaw) -> Option<Id> {
+    resource_man.interner.get(id.to_string())
+}
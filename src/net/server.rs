@@ -0,0 +1,610 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::mem;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ractor::ActorRef;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::{JoinHandle, JoinSet};
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::Id;
+use automancy_defs::log;
+use automancy_resources::data::inventory::{Inventory, InventoryRaw};
+use automancy_resources::data::DataMap;
+use automancy_resources::ResourceManager;
+
+use crate::game::{GameMsg, TileChange};
+use crate::map::{Map, Tiles};
+use crate::net::{
+    id_from_raw, id_raw, read_msg, write_msg, ClientMsg, PlayerId, PlayerToken, ServerMsg,
+};
+use crate::tile_entity::TileModifier;
+
+/// How often the clients are sent what changed on the map since the last time.
+pub const SYNC_INTERVAL: Duration = Duration::from_millis(100);
+/// The file in a map's folder the players that joined it are saved to.
+pub const PLAYERS_FILE: &str = "players.ron";
+
+/// How many updates a client can fall behind on before it is sent a new snapshot instead.
+const UPDATE_BUFFER_SIZE: usize = 64;
+
+/// Someone who has joined the server.
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub id: PlayerId,
+    pub name: String,
+    /// The items the player has taken, which replaces the map's `player_inventory` in multiplayer.
+    pub inventory: Inventory,
+    /// How many clients are connected as the player. Can be more than one while a client that reconnected still has its old connection open.
+    pub connections: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayerRaw {
+    id: PlayerId,
+    name: String,
+    token: PlayerToken,
+    inventory: InventoryRaw,
+}
+
+/// Every player that has joined the map, by their token.
+#[derive(Debug, Default)]
+pub struct Players {
+    players: HashMap<PlayerToken, Player>,
+    next_id: PlayerId,
+}
+
+impl Players {
+    /// Loads the players that joined the map before. Maps that were never hosted have none.
+    pub fn load(resource_man: &ResourceManager, map_name: &str) -> Self {
+        let path = Map::path(map_name).join(PLAYERS_FILE);
+
+        let Ok(file) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        let players = match ron::from_str::<Vec<PlayerRaw>>(&file) {
+            Ok(players) => players,
+            Err(err) => {
+                log::error!("Could not read the players at {path:?}, starting without them: {err}");
+                return Self::default();
+            }
+        };
+
+        let next_id = players.iter().map(|v| v.id + 1).max().unwrap_or(0);
+
+        Self {
+            players: players
+                .into_iter()
+                .map(|player| {
+                    (
+                        player.token,
+                        Player {
+                            id: player.id,
+                            name: player.name,
+                            inventory: player.inventory.to_inventory(&resource_man.interner),
+                            connections: 0,
+                        },
+                    )
+                })
+                .collect(),
+            next_id,
+        }
+    }
+
+    /// Saves the players into the map's folder.
+    pub fn save(&self, resource_man: &ResourceManager, map_name: &str) -> anyhow::Result<()> {
+        let path = Map::path(map_name);
+        fs::create_dir_all(&path)?;
+
+        let mut players = self
+            .players
+            .iter()
+            .map(|(token, player)| PlayerRaw {
+                id: player.id,
+                name: player.name.clone(),
+                token: *token,
+                inventory: player.inventory.to_raw(&resource_man.interner),
+            })
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+
+        let writer = BufWriter::new(File::create(path.join(PLAYERS_FILE))?);
+        ron::ser::to_writer(writer, &players)?;
+
+        Ok(())
+    }
+
+    /// Joins as the player the token was given to, or as a new player if there is no token or it is unknown.
+    fn join(&mut self, name: String, token: Option<PlayerToken>) -> (PlayerToken, &Player) {
+        let token = match token.filter(|token| self.players.contains_key(token)) {
+            Some(token) => {
+                self.players.get_mut(&token).unwrap().connections += 1;
+
+                token
+            }
+            None => {
+                let token = loop {
+                    let token = rand::random();
+
+                    if !self.players.contains_key(&token) {
+                        break token;
+                    }
+                };
+
+                self.players.insert(
+                    token,
+                    Player {
+                        id: self.next_id,
+                        name,
+                        inventory: Inventory::default(),
+                        connections: 1,
+                    },
+                );
+                self.next_id += 1;
+
+                token
+            }
+        };
+
+        (token, &self.players[&token])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Player> {
+        self.players.values()
+    }
+}
+
+/// The server's copy of the map, kept up to date with the changes the game tells of, which is sent to the clients that join.
+#[derive(Debug, Default)]
+struct Mirror {
+    tiles: Tiles,
+    data: HashMap<TileCoord, DataMap>,
+}
+
+/// What changed on the map since the clients were last sent an update.
+#[derive(Debug, Default)]
+struct Pending {
+    tiles: HashMap<TileCoord, Option<(Id, TileModifier)>>,
+    data: HashMap<TileCoord, DataMap>,
+}
+
+struct Shared {
+    resource_man: Arc<ResourceManager>,
+    game: ActorRef<GameMsg>,
+    map_name: String,
+    players: Mutex<Players>,
+    /// Locked while the clients are sent an update, so that one that joins in the meantime gets every update after its snapshot, and none before.
+    mirror: Mutex<Mirror>,
+    updates: broadcast::Sender<Arc<ServerMsg>>,
+}
+
+/// Marks the player as disconnected when dropped, however the connection ended.
+struct Connected<'a>(&'a Shared, PlayerToken);
+
+impl Drop for Connected<'_> {
+    fn drop(&mut self) {
+        if let Some(player) = self.0.players.lock().unwrap().players.get_mut(&self.1) {
+            player.connections -= 1;
+
+            log::info!("{} left", player.name);
+        }
+    }
+}
+
+/// Hosts a game for clients to join over TCP. The game keeps running in this process, and the clients only send it commands.
+pub struct Server {
+    shared: Arc<Shared>,
+    addr: SocketAddr,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Server {
+    /// Starts accepting clients on the listener, for the map the game has loaded.
+    pub async fn start(
+        listener: TcpListener,
+        resource_man: Arc<ResourceManager>,
+        game: ActorRef<GameMsg>,
+    ) -> anyhow::Result<Self> {
+        let addr = listener.local_addr()?;
+        let (_, map_name) = game.call(GameMsg::GetMapInfo, None).await?.unwrap();
+
+        let shared = Arc::new(Shared {
+            players: Mutex::new(Players::load(&resource_man, &map_name)),
+            resource_man,
+            game,
+            map_name,
+            mirror: Default::default(),
+            updates: broadcast::channel(UPDATE_BUFFER_SIZE).0,
+        });
+
+        let changes = watch(&shared).await?;
+
+        let tasks = vec![
+            tokio::spawn(accept(shared.clone(), listener)),
+            tokio::spawn(relay(shared.clone(), changes)),
+        ];
+
+        log::info!("Hosting map {} on {addr}", shared.map_name);
+
+        Ok(Self {
+            shared,
+            addr,
+            tasks,
+        })
+    }
+
+    /// The address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Gets every player that has joined.
+    pub fn players(&self) -> Vec<Player> {
+        let mut players = self
+            .shared
+            .players
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+
+        players
+    }
+
+    /// Saves the map along with the players.
+    pub async fn save(&self) -> anyhow::Result<()> {
+        self.shared
+            .game
+            .call(
+                |reply| GameMsg::SaveMap(self.shared.resource_man.clone(), reply),
+                None,
+            )
+            .await?;
+
+        self.shared
+            .players
+            .lock()
+            .unwrap()
+            .save(&self.shared.resource_man, &self.shared.map_name)
+    }
+
+    /// Stops accepting clients, and disconnects the ones that have joined.
+    pub async fn stop(self) {
+        for task in self.tasks {
+            task.abort();
+            _ = task.await;
+        }
+    }
+}
+
+/// Gets everything on the map, as sent to clients that just joined.
+fn snapshot(resource_man: &ResourceManager, mirror: &Mirror) -> ServerMsg {
+    ServerMsg::Snapshot {
+        tiles: mirror
+            .tiles
+            .iter()
+            .flat_map(|(coord, (id, tile_modifier))| {
+                id_raw(resource_man, *id).map(|id| (*coord, id, *tile_modifier))
+            })
+            .collect(),
+        data: mirror
+            .data
+            .iter()
+            .map(|(coord, data)| (*coord, data.to_raw(&resource_man.interner)))
+            .collect(),
+    }
+}
+
+/// Starts watching the changes to the map, and copies the map as it is now. The changes made while it was being copied are still to be heard, and each of them holds the whole of what it changed, so hearing them again after the copy leaves it the same as the map.
+async fn watch(shared: &Shared) -> anyhow::Result<broadcast::Receiver<TileChange>> {
+    let changes = shared
+        .game
+        .call(GameMsg::WatchChanges, None)
+        .await?
+        .unwrap();
+    let tiles = shared.game.call(GameMsg::GetTiles, None).await?.unwrap();
+    let data = shared.game.call(GameMsg::GetAllData, None).await?.unwrap();
+
+    *shared.mirror.lock().unwrap() = Mirror { tiles, data };
+
+    Ok(changes)
+}
+
+async fn accept(shared: Arc<Shared>, listener: TcpListener) {
+    // dropped along with the task, which disconnects everyone
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    connections.spawn(connection(shared.clone(), stream, addr));
+                }
+                Err(err) => log::warn!("Could not accept a client: {err}"),
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+/// Keeps the server's copy of the map up to date with the changes the game tells of, and sends what changed to every client every so often.
+async fn relay(shared: Arc<Shared>, mut changes: broadcast::Receiver<TileChange>) {
+    let mut interval = tokio::time::interval(SYNC_INTERVAL);
+    let mut pending = Pending::default();
+
+    loop {
+        tokio::select! {
+            change = changes.recv() => match change {
+                Ok(TileChange::Reloaded) | Err(RecvError::Lagged(_)) => {
+                    // copied again from scratch, and sent to every client as such
+                    changes = match watch(&shared).await {
+                        Ok(changes) => changes,
+                        Err(err) => {
+                            log::error!("Could not watch the map any more: {err:#}");
+                            return;
+                        }
+                    };
+                    pending = Pending::default();
+
+                    let mirror = shared.mirror.lock().unwrap();
+                    // nobody might be connected, which is fine
+                    _ = shared.updates.send(Arc::new(snapshot(&shared.resource_man, &mirror)));
+                }
+                Ok(change) => apply(&mut shared.mirror.lock().unwrap(), &mut pending, change),
+                Err(RecvError::Closed) => return,
+            },
+            _ = interval.tick() => flush(&shared, mem::take(&mut pending)),
+        }
+    }
+}
+
+/// Sends the clients what changed since the last update, if anything did.
+fn flush(shared: &Shared, pending: Pending) {
+    if pending.tiles.is_empty() && pending.data.is_empty() {
+        return;
+    }
+
+    let resource_man = &shared.resource_man;
+
+    let update = ServerMsg::Update {
+        tiles: pending
+            .tiles
+            .into_iter()
+            .map(|(coord, tile)| {
+                (
+                    coord,
+                    tile.and_then(|(id, tile_modifier)| {
+                        id_raw(resource_man, id).map(|id| (id, tile_modifier))
+                    }),
+                )
+            })
+            .collect(),
+        data: pending
+            .data
+            .into_iter()
+            .map(|(coord, data)| (coord, data.to_raw(&resource_man.interner)))
+            .collect(),
+    };
+
+    let _mirror = shared.mirror.lock().unwrap();
+    // nobody might be connected, which is fine
+    _ = shared.updates.send(Arc::new(update));
+}
+
+/// Applies a change to the server's copy of the map, and keeps it to be sent to the clients.
+fn apply(mirror: &mut Mirror, pending: &mut Pending, change: TileChange) {
+    match change {
+        TileChange::Placed(coord, id, tile_modifier) => {
+            mirror.tiles.insert(coord, (id, tile_modifier));
+            mirror.data.remove(&coord);
+
+            // the clients drop what they had of the data of the tile it replaced
+            pending.tiles.insert(coord, Some((id, tile_modifier)));
+            pending.data.insert(coord, DataMap::default());
+        }
+        TileChange::Removed(coord) => {
+            mirror.tiles.remove(&coord);
+            mirror.data.remove(&coord);
+
+            pending.tiles.insert(coord, None);
+            pending.data.remove(&coord);
+        }
+        TileChange::Data(coord, data) => {
+            // a tile that was just removed can still tell of its data
+            if mirror.tiles.contains_key(&coord) {
+                mirror.data.insert(coord, data.clone());
+                pending.data.insert(coord, data);
+            }
+        }
+        TileChange::Reloaded => {}
+    }
+}
+
+async fn connection(shared: Arc<Shared>, stream: TcpStream, addr: SocketAddr) {
+    if let Err(err) = serve(&shared, stream).await {
+        log::warn!("The connection with {addr} ended: {err:#}");
+    }
+}
+
+/// Talks to one client, until it disconnects.
+async fn serve(shared: &Shared, stream: TcpStream) -> anyhow::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let Some(ClientMsg::Hello { name, token }) = read_msg(&mut lines).await? else {
+        let rejected = ServerMsg::Rejected("Expected a hello".to_string());
+        return write_msg(&mut writer, &rejected).await;
+    };
+
+    let (token, player) = {
+        let mut players = shared.players.lock().unwrap();
+        let (token, player) = players.join(name, token);
+
+        (token, player.clone())
+    };
+    let _connected = Connected(shared, token);
+
+    log::info!("{} joined as player {}", player.name, player.id);
+
+    // subscribed while the copy of the map can't change, so that the client gets every update after the snapshot
+    let (mut updates, current) = {
+        let mirror = shared.mirror.lock().unwrap();
+
+        (
+            shared.updates.subscribe(),
+            snapshot(&shared.resource_man, &mirror),
+        )
+    };
+
+    write_msg(
+        &mut writer,
+        &ServerMsg::Welcome {
+            player: player.id,
+            token,
+        },
+    )
+    .await?;
+    write_msg(&mut writer, &current).await?;
+    write_msg(
+        &mut writer,
+        &ServerMsg::Inventory(player.inventory.to_raw(&shared.resource_man.interner)),
+    )
+    .await?;
+
+    loop {
+        tokio::select! {
+            msg = read_msg(&mut lines) => {
+                let Some(msg) = msg? else {
+                    return Ok(());
+                };
+
+                if let Some(reply) = handle(shared, token, msg).await? {
+                    write_msg(&mut writer, &reply).await?;
+                }
+            }
+            update = updates.recv() => match update {
+                Ok(update) => write_msg(&mut writer, update.as_ref()).await?,
+                Err(RecvError::Lagged(_)) => {
+                    let current = snapshot(&shared.resource_man, &shared.mirror.lock().unwrap());
+
+                    write_msg(&mut writer, &current).await?;
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Acts on a message from the player with the given token, and returns what to reply with, if anything.
+async fn handle(
+    shared: &Shared,
+    token: PlayerToken,
+    msg: ClientMsg,
+) -> anyhow::Result<Option<ServerMsg>> {
+    let resource_man = &shared.resource_man;
+    let rejected = |reason: String| Ok(Some(ServerMsg::Rejected(reason)));
+
+    match msg {
+        ClientMsg::Hello { .. } => {
+            return rejected("Already joined".to_string());
+        }
+        ClientMsg::PlaceTile {
+            coord,
+            id,
+            tile_modifier,
+        } => {
+            let Some(tile) = id_from_raw(resource_man, &id)
+                .filter(|tile| resource_man.registry.tile(*tile).is_some())
+            else {
+                return rejected(format!("{id} is not a tile"));
+            };
+
+            shared.game.send_message(GameMsg::PlaceTile {
+                coord,
+                id: tile,
+                tile_modifier,
+                data: None,
+                record: true,
+                reply: None,
+            })?;
+        }
+        ClientMsg::MoveTiles { coords, direction } => {
+            shared
+                .game
+                .send_message(GameMsg::MoveTiles(coords, direction, true))?;
+        }
+        ClientMsg::Undo => {
+            // the game has one undo history, which would undo what the other players did
+            return rejected("Undoing isn't possible in multiplayer".to_string());
+        }
+        ClientMsg::SetTileData { coord, key, value } => {
+            let Some(key) = id_from_raw(resource_man, &key) else {
+                return rejected(format!("{key} is not a data key"));
+            };
+            let value = match value {
+                Some(value) => match value.try_to_data(&resource_man.interner) {
+                    Some(value) => Some(value),
+                    None => {
+                        return rejected(
+                            "The value refers to something that doesn't exist".to_string(),
+                        )
+                    }
+                },
+                None => None,
+            };
+
+            shared
+                .game
+                .send_message(GameMsg::SetTileData { coord, key, value })?;
+        }
+        ClientMsg::TakeItems {
+            coord,
+            item,
+            amount,
+        } => {
+            let Some(item) = id_from_raw(resource_man, &item) else {
+                return rejected(format!("{item} is not an item"));
+            };
+
+            // taken by the tile itself, so that nothing else can change its buffer in the meantime
+            let Some(taken) = shared
+                .game
+                .call(
+                    |reply| GameMsg::TakeItems {
+                        coord,
+                        item,
+                        amount,
+                        reply: Some(reply),
+                    },
+                    None,
+                )
+                .await?
+                .unwrap()
+            else {
+                return rejected(format!("The tile at {coord} has none to take"));
+            };
+
+            let mut players = shared.players.lock().unwrap();
+            let player = players.players.get_mut(&token).unwrap();
+            player.inventory.add(item, taken);
+
+            return Ok(Some(ServerMsg::Inventory(
+                player.inventory.to_raw(&resource_man.interner),
+            )));
+        }
+    }
+
+    Ok(None)
+}
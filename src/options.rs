@@ -9,6 +9,7 @@ use winit::event::VirtualKeyCode;
 use automancy_defs::hashbrown::HashMap;
 use automancy_defs::log;
 use automancy_defs::math::{Double, Float};
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
 
 use crate::input::{KeyAction, DEFAULT_KEYMAP};
 
@@ -105,6 +106,12 @@ impl Default for GraphicsOptions {
 pub struct GuiOptions {
     pub scale: f32,
     pub font: String,
+    #[serde(default = "default_language")]
+    pub language: String,
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
 }
 
 impl Default for GuiOptions {
@@ -112,6 +119,7 @@ impl Default for GuiOptions {
         Self {
             scale: 1.0,
             font: "iosevka-extended.ttf".to_string(),
+            language: default_language(),
         }
     }
 }
@@ -0,0 +1,327 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::{Id, IdRaw};
+use automancy_resources::ResourceManager;
+
+use crate::game::{TickUnit, TPS};
+
+/// The folder profiles are exported to.
+pub const PROFILE_PATH: &str = "profiles";
+/// How many of the last ticks are kept in the profile.
+pub const TICK_HISTORY: usize = TPS as usize * 10;
+
+/// A handler in a tile's function, which the tile entity calls as it handles a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Handler {
+    #[serde(rename = "handle_tick")]
+    Tick,
+    #[serde(rename = "handle_transaction")]
+    Transaction,
+    #[serde(rename = "handle_transaction_result")]
+    TransactionResult,
+    #[serde(rename = "handle_transaction_failed")]
+    TransactionFailed,
+    #[serde(rename = "handle_extract_request")]
+    ExtractRequest,
+}
+
+impl Handler {
+    /// The name of the handler in the function's script.
+    pub fn function_name(self) -> &'static str {
+        match self {
+            Handler::Tick => "handle_tick",
+            Handler::Transaction => "handle_transaction",
+            Handler::TransactionResult => "handle_transaction_result",
+            Handler::TransactionFailed => "handle_transaction_failed",
+            Handler::ExtractRequest => "handle_extract_request",
+        }
+    }
+}
+
+impl Display for Handler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.function_name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Timing {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TickSample {
+    tick: TickUnit,
+    queue_depth: usize,
+    transactions: u64,
+    handler_calls: u64,
+    handler_time: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Profile {
+    handlers: HashMap<(Id, Handler), Timing>,
+    /// the tick being run, which the handlers are counted towards
+    current: Option<TickSample>,
+    ticks: VecDeque<TickSample>,
+}
+
+/// Measures the time tile entities spend in each handler of their functions, and what each tick costs.
+/// It is shared by the game and every tile entity, and measures nothing until it is enabled.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    enabled: AtomicBool,
+    transactions: AtomicU64,
+    profile: Mutex<Profile>,
+}
+
+impl Profiler {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Forgets everything measured so far.
+    pub fn reset(&self) {
+        *self.profile.lock().unwrap() = Profile::default();
+        self.transactions.store(0, Ordering::Relaxed);
+    }
+
+    /// Starts timing a handler, if the profiler is enabled.
+    pub fn start(&self) -> Option<Instant> {
+        self.is_enabled().then(Instant::now)
+    }
+
+    /// Finishes timing a handler of the given tile, which was started with [`Profiler::start`].
+    pub fn finish(&self, tile: Id, handler: Handler, start: Option<Instant>) {
+        let Some(start) = start else {
+            return;
+        };
+        let elapsed = start.elapsed();
+
+        let mut profile = self.profile.lock().unwrap();
+
+        let timing = profile.handlers.entry((tile, handler)).or_default();
+        timing.calls += 1;
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+
+        if let Some(current) = &mut profile.current {
+            current.handler_calls += 1;
+            current.handler_time += elapsed;
+        }
+    }
+
+    /// Counts a transaction towards the tick being run.
+    pub fn count_transaction(&self) {
+        if self.is_enabled() {
+            self.transactions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Finishes measuring the previous tick, and starts measuring the given one.
+    /// `queue_depth` is the number of messages left from the previous ticks, that are yet to be handled.
+    pub fn begin_tick(&self, tick: TickUnit, queue_depth: usize) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let transactions = self.transactions.swap(0, Ordering::Relaxed);
+
+        let mut profile = self.profile.lock().unwrap();
+
+        if let Some(mut previous) = profile.current.take() {
+            previous.transactions = transactions;
+
+            if profile.ticks.len() == TICK_HISTORY {
+                profile.ticks.pop_front();
+            }
+            profile.ticks.push_back(previous);
+        }
+
+        profile.current = Some(TickSample {
+            tick,
+            queue_depth,
+            ..Default::default()
+        });
+    }
+
+    /// Gets what was measured so far, with the ids resolved.
+    pub fn report(&self, resource_man: &ResourceManager) -> ProfileReport {
+        let profile = self.profile.lock().unwrap();
+
+        let id_raw = |id: Id| {
+            resource_man
+                .interner
+                .resolve(id)
+                .map(IdRaw::parse)
+                .unwrap_or_else(|| IdRaw::parse("automancy:unknown"))
+        };
+
+        let handlers = profile
+            .handlers
+            .iter()
+            .map(|((tile, handler), timing)| HandlerRow {
+                tile: id_raw(*tile),
+                function: resource_man
+                    .registry
+                    .tile(*tile)
+                    .and_then(|tile| tile.function)
+                    .map(id_raw),
+                handler: *handler,
+                calls: timing.calls,
+                total_us: timing.total.as_micros() as u64,
+                mean_us: (timing.total / timing.calls.max(1) as u32).as_micros() as u64,
+                max_us: timing.max.as_micros() as u64,
+            })
+            .collect();
+
+        let ticks = profile
+            .ticks
+            .iter()
+            .map(|sample| TickRow {
+                tick: sample.tick,
+                queue_depth: sample.queue_depth,
+                transactions: sample.transactions,
+                handler_calls: sample.handler_calls,
+                handler_us: sample.handler_time.as_micros() as u64,
+            })
+            .collect();
+
+        let mut report = ProfileReport { handlers, ticks };
+        report.sort_handlers(ProfileColumn::Total, true);
+
+        report
+    }
+
+    /// Writes a report of what was measured so far to a new file in [`PROFILE_PATH`], and returns its path.
+    pub fn export(&self, resource_man: &ResourceManager) -> anyhow::Result<PathBuf> {
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs();
+        let path = Path::new(PROFILE_PATH).join(format!("profile-{time}.json"));
+
+        self.report(resource_man).save(&path)?;
+
+        Ok(path)
+    }
+}
+
+/// The time spent in one handler of one tile type, over every call to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HandlerRow {
+    pub tile: IdRaw,
+    pub function: Option<IdRaw>,
+    pub handler: Handler,
+    pub calls: u64,
+    pub total_us: u64,
+    pub mean_us: u64,
+    pub max_us: u64,
+}
+
+/// What one tick cost, counting everything handled until the next tick began.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TickRow {
+    pub tick: TickUnit,
+    /// the number of messages from the previous ticks that were yet to be handled as the tick began
+    pub queue_depth: usize,
+    pub transactions: u64,
+    pub handler_calls: u64,
+    pub handler_us: u64,
+}
+
+/// A column of the handler table, to sort it by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileColumn {
+    Tile,
+    Function,
+    Handler,
+    Calls,
+    Total,
+    Mean,
+    Max,
+}
+
+impl ProfileColumn {
+    pub const ALL: [ProfileColumn; 7] = [
+        ProfileColumn::Tile,
+        ProfileColumn::Function,
+        ProfileColumn::Handler,
+        ProfileColumn::Calls,
+        ProfileColumn::Total,
+        ProfileColumn::Mean,
+        ProfileColumn::Max,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProfileColumn::Tile => "Tile",
+            ProfileColumn::Function => "Function",
+            ProfileColumn::Handler => "Handler",
+            ProfileColumn::Calls => "Calls",
+            ProfileColumn::Total => "Total (µs)",
+            ProfileColumn::Mean => "Mean (µs)",
+            ProfileColumn::Max => "Max (µs)",
+        }
+    }
+}
+
+/// Everything the [`Profiler`] measured, as it is shown and exported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProfileReport {
+    pub handlers: Vec<HandlerRow>,
+    pub ticks: Vec<TickRow>,
+}
+
+impl ProfileReport {
+    pub fn sort_handlers(&mut self, column: ProfileColumn, descending: bool) {
+        self.handlers.sort_by(|a, b| {
+            let ordering = match column {
+                ProfileColumn::Tile => a.tile.to_string().cmp(&b.tile.to_string()),
+                ProfileColumn::Function => a
+                    .function
+                    .as_ref()
+                    .map(IdRaw::to_string)
+                    .cmp(&b.function.as_ref().map(IdRaw::to_string)),
+                ProfileColumn::Handler => a.handler.function_name().cmp(b.handler.function_name()),
+                ProfileColumn::Calls => a.calls.cmp(&b.calls),
+                ProfileColumn::Total => a.total_us.cmp(&b.total_us),
+                ProfileColumn::Mean => a.mean_us.cmp(&b.mean_us),
+                ProfileColumn::Max => a.max_us.cmp(&b.max_us),
+            };
+
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+}
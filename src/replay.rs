@@ -0,0 +1,455 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ractor::ActorRef;
+use serde::{Deserialize, Serialize};
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::{Id, IdRaw, Interner};
+use automancy_resources::data::stack::ItemAmount;
+use automancy_resources::data::{Data, DataMap, DataMapRaw, DataRaw};
+use automancy_resources::ResourceManager;
+
+use crate::game::{GameMsg, TickUnit, TPS};
+use crate::map::{Map, Tiles};
+use crate::tile_entity::TileModifier;
+
+/// The file in a map's folder the replay of its last session is saved to.
+pub const REPLAY_FILE: &str = "replay.ron";
+/// The scratch map replays are played back on in the game.
+pub const REPLAY_MAP: &str = ".replay";
+/// How many ticks apart the state of the game is checksummed while recording.
+pub const CHECKSUM_INTERVAL: u64 = TPS;
+
+/// How long [`play`] waits for the tile entities to handle a tick before giving up.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Something the player did to the map. Ids are stored as `namespace:name`, since they are interned differently in each run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayInput {
+    PlaceTile {
+        coord: TileCoord,
+        id: IdRaw,
+        tile_modifier: TileModifier,
+        data: Option<DataMapRaw>,
+    },
+    MoveTiles {
+        coords: Vec<TileCoord>,
+        direction: TileCoord,
+    },
+    Undo,
+    /// Sets a value in the data map of a tile, or removes it if there is none.
+    SetTileData {
+        coord: TileCoord,
+        key: IdRaw,
+        value: Option<DataRaw>,
+    },
+    /// Sets a value in the data map of the map, or removes it if there is none.
+    SetMapData {
+        key: IdRaw,
+        value: Option<DataRaw>,
+    },
+    /// Takes items out of a tile's buffer.
+    TakeItems {
+        coord: TileCoord,
+        item: IdRaw,
+        amount: ItemAmount,
+    },
+}
+
+/// The map as it was when recording started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayStart {
+    pub tiles: Vec<(TileCoord, IdRaw, TileModifier, DataMapRaw)>,
+    pub data: DataMapRaw,
+}
+
+/// A recording of what the player did to a map, which can be played back against the map as it was when recording started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub map_name: String,
+    pub start: ReplayStart,
+    /// The tick count of the game when recording started, which tile entities mix into their randomness and timing.
+    #[serde(default)]
+    pub tick_count: TickUnit,
+    /// How many ticks were recorded.
+    pub ticks: u64,
+    /// What the player did, along with how many ticks had happened before they did it.
+    pub inputs: Vec<(u64, ReplayInput)>,
+    /// The checksum of the game's state after some number of ticks, and the inputs made during them.
+    pub checksums: Vec<(u64, u64)>,
+}
+
+impl Replay {
+    /// Gets the path to a map's replay from its name.
+    pub fn path(map_name: &str) -> PathBuf {
+        Map::path(map_name).join(REPLAY_FILE)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+
+        Ok(ron::de::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = File::create(path)?;
+        ron::ser::to_writer(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+}
+
+/// Records what the player does to a map, as the game handles it.
+#[derive(Debug)]
+pub struct Recorder {
+    replay: Replay,
+}
+
+impl Recorder {
+    /// Starts recording, from the map as it is now.
+    pub fn new(
+        interner: &Interner,
+        map_name: String,
+        tick_count: TickUnit,
+        tiles: &Tiles,
+        data: &HashMap<TileCoord, DataMap>,
+        map_data: &DataMap,
+    ) -> Self {
+        let mut start_tiles = tiles
+            .iter()
+            .flat_map(|(coord, (id, tile_modifier))| {
+                let id = IdRaw::parse(interner.resolve(*id)?);
+                let data = data.get(coord).cloned().unwrap_or_default();
+
+                Some((*coord, id, *tile_modifier, data.to_raw(interner)))
+            })
+            .collect::<Vec<_>>();
+        start_tiles.sort_by_key(|(coord, ..)| (coord.q(), coord.r()));
+
+        Self {
+            replay: Replay {
+                map_name,
+                start: ReplayStart {
+                    tiles: start_tiles,
+                    data: map_data.to_raw(interner),
+                },
+                tick_count,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn into_replay(self) -> Replay {
+        self.replay
+    }
+
+    /// Records an input, at the current tick.
+    pub fn record(&mut self, input: ReplayInput) {
+        self.replay.inputs.push((self.replay.ticks, input));
+    }
+
+    /// Records a change to the map's data map.
+    pub fn record_map_data(&mut self, interner: &Interner, key: Id, value: Option<&Data>) {
+        let Some(key) = interner.resolve(key).map(IdRaw::parse) else {
+            return;
+        };
+
+        self.record(ReplayInput::SetMapData {
+            key,
+            value: value.and_then(|value| value.try_to_raw(interner)),
+        });
+    }
+
+    /// Records every key the new data map changed from the old one.
+    pub fn record_data_map_changes(&mut self, interner: &Interner, old: &DataMap, new: &DataMap) {
+        for (key, value) in new.iter() {
+            if old.get(key) != Some(value) {
+                self.record_map_data(interner, *key, Some(value));
+            }
+        }
+
+        for key in old.keys() {
+            if !new.contains_key(key) {
+                self.record_map_data(interner, *key, None);
+            }
+        }
+    }
+
+    /// Is the state of the game due to be checksummed, before the next tick?
+    pub fn checksum_due(&self) -> bool {
+        self.replay.ticks % CHECKSUM_INTERVAL == 0
+    }
+
+    pub fn record_checksum(&mut self, checksum: u64) {
+        self.replay.checksums.push((self.replay.ticks, checksum));
+    }
+
+    /// Counts a tick that has happened.
+    pub fn tick(&mut self) {
+        self.replay.ticks += 1;
+    }
+}
+
+/// A 64-bit FNV-1a hash, which stays the same across platforms and versions, unlike the standard library's.
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, s: &str) {
+        for byte in s.bytes().chain([0]) {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+fn hash_data_map(hasher: &mut Fnv, data: DataMapRaw) {
+    let mut data = HashMap::<String, DataRaw>::from(data)
+        .into_iter()
+        .collect::<Vec<_>>();
+    data.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (key, value) in data {
+        hasher.write(&key);
+
+        // the order of the entries in these isn't part of the state
+        match value {
+            DataRaw::Inventory(inventory) => {
+                let mut entries = inventory
+                    .into_inner()
+                    .into_iter()
+                    .map(|(id, amount)| format!("{id}={amount}"))
+                    .collect::<Vec<_>>();
+                entries.sort();
+
+                hasher.write(&entries.join(","));
+            }
+            DataRaw::SetId(ids) => {
+                let mut ids = ids.iter().map(IdRaw::to_string).collect::<Vec<_>>();
+                ids.sort();
+
+                hasher.write(&ids.join(","));
+            }
+            value => hasher.write(&ron::to_string(&value).unwrap()),
+        }
+    }
+}
+
+/// Sums up the tiles on the map along with their data, and the data of the map itself. The same state always has the same checksum, whatever order ids were interned in.
+pub fn checksum(
+    interner: &Interner,
+    tiles: &Tiles,
+    data: &HashMap<TileCoord, DataMap>,
+    map_data: &DataMap,
+) -> u64 {
+    let mut hasher = Fnv(0xcbf29ce484222325);
+
+    let mut coords = tiles.keys().collect::<Vec<_>>();
+    coords.sort_by_key(|coord| (coord.q(), coord.r()));
+
+    for coord in coords {
+        let (id, tile_modifier) = tiles[coord];
+
+        hasher.write(&format!(
+            "{},{} {} {tile_modifier}",
+            coord.q(),
+            coord.r(),
+            interner.resolve(id).unwrap_or_default()
+        ));
+
+        if let Some(data) = data.get(coord) {
+            hash_data_map(&mut hasher, data.to_raw(interner));
+        }
+    }
+
+    hasher.write("map");
+    hash_data_map(&mut hasher, map_data.to_raw(interner));
+
+    hasher.0
+}
+
+/// A point where the state of the game being played back didn't match what was recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// How playing back a replay went.
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackReport {
+    pub ticks: u64,
+    /// How many of the inputs could be applied.
+    pub applied: usize,
+    /// The inputs that refer to something the resources don't have, with the tick they were made at.
+    pub skipped: Vec<(u64, ReplayInput)>,
+    /// How many checksums were compared.
+    pub checked: usize,
+    /// Every checksum that didn't match, in order. The first is where the replay diverged.
+    pub divergences: Vec<Divergence>,
+}
+
+fn id(resource_man: &ResourceManager, id: &IdRaw) -> Option<Id> {
+    resource_man.interner.get(id.to_string())
+}
+
+fn data_value(resource_man: &ResourceManager, value: &Option<DataRaw>) -> Option<Option<Data>> {
+    match value {
+        Some(value) => value.try_to_data(&resource_man.interner).map(Some),
+        None => Some(None),
+    }
+}
+
+/// Turns an input back into the message the game got for it, if everything it refers to exists.
+fn to_msg(resource_man: &ResourceManager, input: &ReplayInput) -> Option<GameMsg> {
+    Some(match input {
+        ReplayInput::PlaceTile {
+            coord,
+            id: tile,
+            tile_modifier,
+            data,
+        } => GameMsg::PlaceTile {
+            coord: *coord,
+            id: id(resource_man, tile)?,
+            tile_modifier: *tile_modifier,
+            data: data
+                .as_ref()
+                .map(|data| data.to_data(&resource_man.interner)),
+            record: true,
+            reply: None,
+        },
+        ReplayInput::MoveTiles { coords, direction } => {
+            GameMsg::MoveTiles(coords.clone(), *direction, true)
+        }
+        ReplayInput::Undo => GameMsg::Undo,
+        ReplayInput::SetTileData { coord, key, value } => GameMsg::SetTileData {
+            coord: *coord,
+            key: id(resource_man, key)?,
+            value: data_value(resource_man, value)?,
+        },
+        ReplayInput::SetMapData { key, value } => {
+            let key = id(resource_man, key)?;
+
+            match data_value(resource_man, value)? {
+                Some(value) => GameMsg::SetData(key, value),
+                None => GameMsg::RemoveData(key),
+            }
+        }
+        ReplayInput::TakeItems {
+            coord,
+            item,
+            amount,
+        } => GameMsg::TakeItems {
+            coord: *coord,
+            item: id(resource_man, item)?,
+            amount: *amount,
+            reply: None,
+        },
+    })
+}
+
+/// Waits until every message the tile entities sent each other has been handled.
+async fn settle(game: &ActorRef<GameMsg>) -> anyhow::Result<()> {
+    let start = tokio::time::Instant::now();
+
+    while game.call(GameMsg::GetPendingMessages, None).await?.unwrap() != 0 {
+        if start.elapsed() > SETTLE_TIMEOUT {
+            anyhow::bail!("The game did not settle");
+        }
+
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    Ok(())
+}
+
+/// Plays a replay back on a game with an empty map, comparing its state against the recorded checksums as it goes.
+///
+/// The game is paused, set to the tick count recording started at, and stepped one tick at a time, each one settling before the next. With a pace, it waits that long between ticks, so that it can be watched.
+pub async fn play(
+    resource_man: Arc<ResourceManager>,
+    game: &ActorRef<GameMsg>,
+    replay: &Replay,
+    pace: Option<Duration>,
+) -> anyhow::Result<PlaybackReport> {
+    let interner = &resource_man.interner;
+
+    game.send_message(GameMsg::SetPaused(true))?;
+    game.send_message(GameMsg::SetTickCount(replay.tick_count))?;
+
+    for (coord, tile, tile_modifier, data) in &replay.start.tiles {
+        let Some(tile) = id(&resource_man, tile) else {
+            anyhow::bail!("The replay starts with {tile}, which doesn't exist");
+        };
+
+        game.send_message(GameMsg::PlaceTile {
+            coord: *coord,
+            id: tile,
+            tile_modifier: *tile_modifier,
+            data: Some(data.to_data(interner)),
+            record: false,
+            reply: None,
+        })?;
+    }
+    game.send_message(GameMsg::SetDataMap(replay.start.data.to_data(interner)))?;
+    settle(game).await?;
+
+    let checksums = replay.checksums.iter().cloned().collect::<HashMap<_, _>>();
+    let mut inputs = replay.inputs.iter().peekable();
+
+    let mut report = PlaybackReport {
+        ticks: replay.ticks,
+        ..Default::default()
+    };
+
+    for tick in 0..=replay.ticks {
+        while let Some((_, input)) = inputs.next_if(|(at, _)| *at == tick) {
+            match to_msg(&resource_man, input) {
+                Some(msg) => {
+                    game.send_message(msg)?;
+                    report.applied += 1;
+                }
+                None => report.skipped.push((tick, input.clone())),
+            }
+        }
+        settle(game).await?;
+
+        if let Some(expected) = checksums.get(&tick) {
+            let actual = game.call(GameMsg::GetChecksum, None).await?.unwrap();
+            report.checked += 1;
+
+            if actual != *expected {
+                report.divergences.push(Divergence {
+                    tick,
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+
+        if tick < replay.ticks {
+            game.send_message(GameMsg::Step)?;
+            settle(game).await?;
+
+            if let Some(pace) = pace {
+                tokio::time::sleep(pace).await;
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,181 @@
+use std::ops::Deref;
+
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::Id;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::stack::ItemAmount;
+use automancy_resources::data::{Data, DataMap};
+use automancy_resources::registry::DataIds;
+
+use crate::game::TickUnit;
+
+/// The signals on each network, by the position of the tile linking it. Each signal is keyed by an ID, usually of an item.
+pub type Signals = HashMap<TileCoord, Inventory>;
+
+/// Adds signals published onto a network to the ones already on it, so that several tiles can publish the same signal.
+pub fn publish(signals: &mut Signals, network: TileCoord, published: &Inventory) {
+    let on_network = signals.entry(network).or_default();
+
+    for (id, amount) in published.iter() {
+        on_network.add(*id, *amount);
+    }
+}
+
+/// How many ticks the signals a tile published stay on the network, if it publishes none after them. Signals that arrive late leave the last ones in place until then.
+pub const SIGNAL_HOLD_TICKS: TickUnit = 10;
+
+/// The signals a tile published onto a network, and during which tick.
+#[derive(Debug, Clone)]
+pub struct Publication {
+    pub tick_count: TickUnit,
+    pub signals: Inventory,
+}
+
+/// The last signals each tile published onto each network, by the position of the tile linking the network and of the tile publishing.
+pub type Publications = HashMap<(TileCoord, TileCoord), Publication>;
+
+/// Records the signals a tile published during the given tick, adding them to what it already published during it. Signals from before the tile's last ones are dropped, as they were already replaced.
+pub fn record(
+    publications: &mut Publications,
+    network: TileCoord,
+    publisher: TileCoord,
+    published: &Inventory,
+    tick_count: TickUnit,
+) -> bool {
+    let publication = publications
+        .entry((network, publisher))
+        .or_insert_with(|| Publication {
+            tick_count,
+            signals: Inventory::default(),
+        });
+
+    let age = tick_count.wrapping_sub(publication.tick_count);
+
+    if age > TickUnit::MAX / 2 {
+        return false;
+    }
+
+    if age > 0 {
+        *publication = Publication {
+            tick_count,
+            signals: Inventory::default(),
+        };
+    }
+
+    for (id, amount) in published.iter() {
+        publication.signals.add(*id, *amount);
+    }
+
+    true
+}
+
+/// Gathers the signals on each network from the last ones each tile published onto it, forgetting the tiles that stopped publishing.
+pub fn gather(publications: &mut Publications, tick_count: TickUnit) -> Signals {
+    publications.retain(|_, publication| {
+        tick_count.wrapping_sub(publication.tick_count) <= SIGNAL_HOLD_TICKS
+    });
+
+    let mut signals = Signals::default();
+
+    for ((network, _), publication) in publications.iter() {
+        publish(&mut signals, *network, &publication.signals);
+    }
+
+    signals
+}
+
+/// How a condition compares a signal to its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 6] = [
+        Comparison::Less,
+        Comparison::LessOrEqual,
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::GreaterOrEqual,
+        Comparison::Greater,
+    ];
+
+    /// Reads the comparison as it is stored in a data map.
+    pub fn from_amount(amount: ItemAmount) -> Option<Self> {
+        Self::ALL.get(usize::try_from(amount).ok()?).cloned()
+    }
+
+    /// Gets the comparison as it is stored in a data map.
+    pub fn to_amount(self) -> ItemAmount {
+        Self::ALL.iter().position(|v| *v == self).unwrap() as ItemAmount
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "≤",
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "≠",
+            Comparison::GreaterOrEqual => "≥",
+            Comparison::Greater => ">",
+        }
+    }
+
+    pub fn compare(self, signal: ItemAmount, value: ItemAmount) -> bool {
+        match self {
+            Comparison::Less => signal < value,
+            Comparison::LessOrEqual => signal <= value,
+            Comparison::Equal => signal == value,
+            Comparison::NotEqual => signal != value,
+            Comparison::GreaterOrEqual => signal >= value,
+            Comparison::Greater => signal > value,
+        }
+    }
+}
+
+/// The enable condition of a tile. The tile only runs while the signal on the network it is linked to compares to the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub signal: Id,
+    pub comparison: Comparison,
+    pub value: ItemAmount,
+}
+
+impl Condition {
+    /// Reads the condition from a tile's data map, if it has one. A condition without a comparison or value compares with `> 0`.
+    pub fn from_data(data_ids: &DataIds, data: &DataMap) -> Option<Self> {
+        let signal = *data.get(&data_ids.condition_signal).and_then(Data::as_id)?;
+        let comparison = data
+            .get(&data_ids.condition_comparison)
+            .and_then(Data::as_amount)
+            .and_then(|v| Comparison::from_amount(*v))
+            .unwrap_or(Comparison::Greater);
+        let value = data
+            .get(&data_ids.condition_value)
+            .and_then(Data::as_amount)
+            .cloned()
+            .unwrap_or(0);
+
+        Some(Self {
+            signal,
+            comparison,
+            value,
+        })
+    }
+
+    /// Checks the condition against the signals on the tile's network. A signal nobody published is 0.
+    pub fn is_met(&self, signals: Option<&Inventory>) -> bool {
+        let signal = signals
+            .and_then(|signals| signals.deref().get(&self.signal))
+            .cloned()
+            .unwrap_or(0);
+
+        self.comparison.compare(signal, self.value)
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::{Id, IdRaw, Interner};
+use automancy_defs::log;
+use automancy_resources::data::stack::{ItemAmount, ItemStack};
+
+use crate::game::TPS;
+use crate::map::Map;
+
+/// The file in a map's folder its statistics are saved to.
+pub const STATISTICS_FILE: &str = "statistics.ron";
+/// How many buckets each window is split into, which are the points of its graph.
+pub const BUCKETS: usize = 60;
+
+/// What happened to an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ItemStat {
+    /// made by a machine
+    Produced,
+    /// taken in by a machine as an input, or voided
+    Consumed,
+    /// put into a storage, less what was taken out of one
+    Stored,
+}
+
+impl ItemStat {
+    pub const ALL: [ItemStat; 3] = [ItemStat::Produced, ItemStat::Consumed, ItemStat::Stored];
+}
+
+/// How much of an item was produced, consumed and stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemCounts {
+    pub produced: ItemAmount,
+    pub consumed: ItemAmount,
+    pub stored: ItemAmount,
+}
+
+impl ItemCounts {
+    pub fn get(&self, stat: ItemStat) -> ItemAmount {
+        match stat {
+            ItemStat::Produced => self.produced,
+            ItemStat::Consumed => self.consumed,
+            ItemStat::Stored => self.stored,
+        }
+    }
+
+    fn add(&mut self, stat: ItemStat, amount: ItemAmount) {
+        match stat {
+            ItemStat::Produced => self.produced += amount,
+            ItemStat::Consumed => self.consumed += amount,
+            ItemStat::Stored => self.stored += amount,
+        }
+    }
+
+    fn add_counts(&mut self, other: &ItemCounts) {
+        self.produced += other.produced;
+        self.consumed += other.consumed;
+        self.stored += other.stored;
+    }
+}
+
+/// A span of time the statistics are rolled over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatWindow {
+    Minute,
+    TenMinutes,
+    Hour,
+}
+
+impl StatWindow {
+    pub const ALL: [StatWindow; 3] = [StatWindow::Minute, StatWindow::TenMinutes, StatWindow::Hour];
+
+    /// How many ticks the window spans.
+    pub fn ticks(self) -> u64 {
+        let minutes = match self {
+            StatWindow::Minute => 1,
+            StatWindow::TenMinutes => 10,
+            StatWindow::Hour => 60,
+        };
+
+        TPS * 60 * minutes
+    }
+
+    /// How many ticks each of the window's buckets spans.
+    pub fn bucket_ticks(self) -> u64 {
+        self.ticks() / BUCKETS as u64
+    }
+
+    fn index(self) -> usize {
+        match self {
+            StatWindow::Minute => 0,
+            StatWindow::TenMinutes => 1,
+            StatWindow::Hour => 2,
+        }
+    }
+}
+
+type Bucket = HashMap<Id, ItemCounts>;
+
+/// Counts the items produced, consumed and stored on a map, over the last minute, ten minutes and hour.
+///
+/// Each window keeps the last [`BUCKETS`] buckets, oldest first, and the last one is being filled.
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    /// the ticks counted since the statistics were started
+    ticks: u64,
+    windows: [VecDeque<Bucket>; 3],
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self {
+            ticks: 0,
+            windows: [(); 3].map(|_| VecDeque::from([Bucket::default()])),
+        }
+    }
+}
+
+impl Statistics {
+    pub fn path(map_name: &str) -> PathBuf {
+        Map::path(map_name).join(STATISTICS_FILE)
+    }
+
+    /// Counts a stack towards the current bucket of every window. Only [`ItemStat::Stored`] can be negative, as storages count what is taken out of them.
+    pub fn record(&mut self, stat: ItemStat, stack: ItemStack) {
+        if stack.amount == 0 || (stack.amount < 0 && stat != ItemStat::Stored) {
+            return;
+        }
+
+        for window in &mut self.windows {
+            if let Some(bucket) = window.back_mut() {
+                bucket
+                    .entry(stack.item.id)
+                    .or_default()
+                    .add(stat, stack.amount);
+            }
+        }
+    }
+
+    /// Counts a tick, starting new buckets in the windows whose current bucket is full.
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+
+        for window in StatWindow::ALL {
+            if self.ticks % window.bucket_ticks() != 0 {
+                continue;
+            }
+
+            let buckets = &mut self.windows[window.index()];
+
+            buckets.push_back(Bucket::default());
+            while buckets.len() > BUCKETS {
+                buckets.pop_front();
+            }
+        }
+    }
+
+    /// Gets how much of each item was produced, consumed and stored within the window.
+    pub fn totals(&self, window: StatWindow) -> HashMap<Id, ItemCounts> {
+        let mut totals = HashMap::<Id, ItemCounts>::new();
+
+        for bucket in &self.windows[window.index()] {
+            for (id, counts) in bucket {
+                totals.entry(*id).or_default().add_counts(counts);
+            }
+        }
+
+        totals
+    }
+
+    /// Gets the counts of an item in each bucket of the window, oldest first. There are always [`BUCKETS`] of them.
+    pub fn history(&self, window: StatWindow, id: Id) -> Vec<ItemCounts> {
+        let buckets = &self.windows[window.index()];
+
+        let mut history = vec![ItemCounts::default(); BUCKETS - buckets.len()];
+        history.extend(
+            buckets
+                .iter()
+                .map(|bucket| bucket.get(&id).cloned().unwrap_or_default()),
+        );
+
+        history
+    }
+
+    pub fn to_raw(&self, interner: &Interner) -> StatisticsRaw {
+        let windows = self
+            .windows
+            .iter()
+            .map(|buckets| {
+                buckets
+                    .iter()
+                    .map(|bucket| {
+                        bucket
+                            .iter()
+                            .flat_map(|(id, counts)| {
+                                interner.resolve(*id).map(|id| (IdRaw::parse(id), *counts))
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        StatisticsRaw {
+            ticks: self.ticks,
+            windows,
+        }
+    }
+
+    /// Reads the statistics saved with a map, or starts new ones if there are none.
+    pub fn load(interner: &Interner, map_name: &str) -> Self {
+        let path = Self::path(map_name);
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match StatisticsRaw::load(&path) {
+            Ok(raw) => raw.to_statistics(interner),
+            Err(err) => {
+                log::error!("Could not read the statistics of map {map_name}: {err:#}");
+
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, interner: &Interner, map_name: &str) -> anyhow::Result<()> {
+        self.to_raw(interner).save(&Self::path(map_name))
+    }
+}
+
+/// [`Statistics`] as they are saved, with the ids as `namespace:name`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatisticsRaw {
+    pub ticks: u64,
+    pub windows: Vec<Vec<Vec<(IdRaw, ItemCounts)>>>,
+}
+
+impl StatisticsRaw {
+    pub fn to_statistics(&self, interner: &Interner) -> Statistics {
+        let mut statistics = Statistics {
+            ticks: self.ticks,
+            ..Default::default()
+        };
+
+        for (buckets, raw) in statistics.windows.iter_mut().zip(&self.windows) {
+            let loaded = raw
+                .iter()
+                .rev()
+                .take(BUCKETS)
+                .rev()
+                .map(|bucket| {
+                    bucket
+                        .iter()
+                        .flat_map(|(id, counts)| {
+                            interner.get(id.to_string()).map(|id| (id, *counts))
+                        })
+                        .collect::<Bucket>()
+                })
+                .collect::<VecDeque<_>>();
+
+            if !loaded.is_empty() {
+                *buckets = loaded;
+            }
+        }
+
+        statistics
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+
+        Ok(ron::de::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = File::create(path)?;
+        ron::ser::to_writer(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+}
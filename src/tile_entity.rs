@@ -1,20 +1,27 @@
 use std::mem;
+use std::ops::Deref;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
-use rand::{thread_rng, RngCore};
 use rhai::{CallFnOptions, Dynamic, ImmutableString, Scope, INT};
 
 use automancy_defs::coord::TileCoord;
 use automancy_defs::id::Id;
+use automancy_resources::data::inventory::Inventory;
 use automancy_resources::data::stack::{ItemAmount, ItemStack};
 use automancy_resources::data::{Data, DataMap};
 use automancy_resources::ResourceManager;
 
-use crate::game::{GameMsg, TickUnit};
+use crate::game::{mark_handled, Changes, GameMsg, PendingMessages, TickUnit, TileChange};
+use crate::logistics::Request;
+use crate::profiler::{Handler, Profiler};
+use crate::signal::{Condition, Signals};
+use crate::statistics::ItemStat;
 use crate::tile_entity::TileEntityMsg::*;
 
-fn rhai_call_options(rhai_state: &mut Dynamic) -> CallFnOptions {
+/// The options used to call the handlers of a tile's function, with `this` bound to the given state.
+pub fn rhai_call_options(rhai_state: &mut Dynamic) -> CallFnOptions {
     CallFnOptions::new()
         .eval_ast(false)
         .rewind_scope(true)
@@ -41,6 +48,10 @@ pub const RHAI_DATA_MAP_KEY: &str = "data";
 pub struct TileEntityState {
     /// A handle to the game.
     game: ActorRef<GameMsg>,
+    /// The count of messages sent to tile entities that are yet to be handled.
+    pending: PendingMessages,
+    /// The profiler the handlers' timings are reported to.
+    profiler: Arc<Profiler>,
 
     /// The rhai object map
     rhai_map: rhai::Map, // TODO deprecate the data map entirely
@@ -53,12 +64,33 @@ pub struct TileEntityState {
 
     /// Are adjacent tiles requirement fulfilled
     adjacent_fulfilled: bool,
+    /// The adjacent tile that fulfilled the requirement, if the script has one
+    adjacent: Option<TileCoord>,
+    /// How much the adjacent tile has left, less what was taken from it since, if it can run out
+    adjacent_remaining: Option<ItemAmount>,
+    /// The signals on every network, as of the last tick
+    signals: Arc<Signals>,
+    /// The seed of the map, as of the last tick
+    seed: INT,
+    /// The last tick the tile was ticked on
+    tick_count: TickUnit,
+    /// How many random numbers the handlers were given since the last tick
+    draws: u64,
+    /// Tells whatever watches the map when the data map changes
+    changes: Changes,
 }
 
 impl TileEntityState {
-    fn new(game: ActorRef<GameMsg>) -> Self {
+    fn new(
+        game: ActorRef<GameMsg>,
+        pending: PendingMessages,
+        profiler: Arc<Profiler>,
+        changes: Changes,
+    ) -> Self {
         Self {
             game,
+            pending,
+            profiler,
 
             rhai_map: rhai::Map::from([(
                 RHAI_DATA_MAP_KEY.into(),
@@ -69,6 +101,13 @@ impl TileEntityState {
             data: DataMap::default(),
 
             adjacent_fulfilled: true,
+            adjacent: None,
+            adjacent_remaining: None,
+            signals: Default::default(),
+            seed: 0,
+            tick_count: 0,
+            draws: 0,
+            changes,
         }
     }
 }
@@ -77,6 +116,9 @@ impl TileEntityState {
 pub enum TileEntityMsg {
     Tick {
         tick_count: TickUnit,
+        signals: Arc<Signals>,
+        /// the seed of the map, which the random numbers given to the handlers are derived from
+        seed: INT,
     },
     Transaction {
         stack: ItemStack,
@@ -84,9 +126,11 @@ pub enum TileEntityMsg {
         source_id: Id,
         root_coord: TileCoord,
         root_id: Id,
+        /// where the items go if the tile refuses them, before the tiles they came from are told
+        fallback: Option<TileCoord>,
     },
     TransactionResult {
-        result: Result<ItemStack, ()>,
+        result: Result<ItemStack, Refusal>,
     },
     ExtractRequest {
         requested_from_id: Id,
@@ -94,6 +138,51 @@ pub enum TileEntityMsg {
     },
     AdjacentState {
         fulfilled: bool,
+        adjacent: Option<TileCoord>,
+        /// how much the adjacent tile has left, if it can run out
+        remaining: Option<ItemAmount>,
+    },
+    /// Tells the tile at the given position how much this tile has left, as the adjacent tile it needs.
+    ReportRemaining {
+        to: TileCoord,
+    },
+    /// Takes from the tile's remaining amount, which replaces the tile with its exhausted variant once it runs out.
+    Deplete {
+        amount: ItemAmount,
+    },
+    /// Publishes the tile's buffer onto the given network, as signals.
+    Sense {
+        network: TileCoord,
+    },
+    /// Advertises what the tile stores on the logistics network of the tile providing it, if it is a storage.
+    Advertise {
+        provider: TileCoord,
+    },
+    /// Posts a request for items that are to be delivered to the tile, less what it already has of them.
+    PostRequest {
+        request: Request,
+    },
+    /// Takes the items a logistics network matched with a request out of the tile's buffer, and sends what it had of them to the requester, along the given path.
+    Deliver {
+        item: Id,
+        amount: ItemAmount,
+        requester: TileCoord,
+        path: Vec<TileCoord>,
+    },
+    /// Carries items a logistics network delivers on to the next tile of the path, or offers them to the requester at its end.
+    Carry {
+        stack: ItemStack,
+        /// the storage the items were taken out of, which takes back what is refused
+        storage: TileCoord,
+        storage_id: Id,
+        /// the tiles the items have yet to go through, ending with the requester
+        path: Vec<TileCoord>,
+    },
+    /// Takes items out of the tile's buffer, and replies with how many were taken, if it had any.
+    TakeItems {
+        item: Id,
+        amount: ItemAmount,
+        reply: Option<RpcReplyPort<Option<ItemAmount>>>,
     },
     SetData(DataMap),
     SetDataValue(Id, Data),
@@ -104,7 +193,200 @@ pub enum TileEntityMsg {
     GetDataWithCoord(RpcReplyPort<(TileCoord, DataMap)>),
 }
 
+/// A transaction the receiving tile did not take.
+#[derive(Debug, Clone, Copy)]
+pub struct Refusal {
+    pub stack: ItemStack,
+    /// The tile that refused it.
+    pub coord: TileCoord,
+    pub reason: RefusalReason,
+}
+
+/// Why a transaction was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefusalReason {
+    /// The receiving tile's condition isn't met.
+    Disabled,
+    /// The receiving tile didn't take the items, e.g. because it is full or doesn't accept them.
+    Rejected,
+    /// There is no tile to receive the items.
+    Missing,
+}
+
+impl RefusalReason {
+    /// The name the tile functions get the reason as.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RefusalReason::Disabled => "disabled",
+            RefusalReason::Rejected => "rejected",
+            RefusalReason::Missing => "missing",
+        }
+    }
+}
+
+/// The entries every handler of a tile's function gets in its input map, which the entries of each handler are added to.
+/// Tile entities build their inputs with this, as should anything else calling the handlers, so that they get the same entries.
+#[derive(Debug, Clone, Copy)]
+pub struct HandlerInput {
+    pub id: Id,
+    pub coord: TileCoord,
+    pub tile_modifier: TileModifier,
+    pub random: INT,
+}
+
+impl HandlerInput {
+    fn with(self, entries: impl IntoIterator<Item = (&'static str, Dynamic)>) -> rhai::Map {
+        let mut input = rhai::Map::from([
+            (
+                "tile_modifier".into(),
+                Dynamic::from_int(self.tile_modifier),
+            ),
+            ("coord".into(), Dynamic::from(self.coord)),
+            ("id".into(), Dynamic::from_int(self.id.into())),
+            ("random".into(), Dynamic::from_int(self.random)),
+        ]);
+        input.extend(entries.into_iter().map(|(k, v)| (k.into(), v)));
+
+        input
+    }
+
+    /// The input of `handle_tick`.
+    pub fn tick(
+        self,
+        adjacent: Option<TileCoord>,
+        remaining: Option<ItemAmount>,
+        signals: Inventory,
+    ) -> rhai::Map {
+        self.with([
+            (
+                "adjacent",
+                adjacent.map(Dynamic::from).unwrap_or(Dynamic::UNIT),
+            ),
+            (
+                "remaining",
+                remaining.map(Dynamic::from_int).unwrap_or(Dynamic::UNIT),
+            ),
+            ("signals", Dynamic::from(signals)),
+        ])
+    }
+
+    /// The input of `handle_transaction`.
+    pub fn transaction(
+        self,
+        stack: ItemStack,
+        source_coord: TileCoord,
+        source_id: Id,
+        root_coord: TileCoord,
+        root_id: Id,
+        signals: Inventory,
+    ) -> rhai::Map {
+        self.with([
+            ("source_coord", Dynamic::from(source_coord)),
+            ("source_id", Dynamic::from_int(source_id.into())),
+            ("root_coord", Dynamic::from(root_coord)),
+            ("root_id", Dynamic::from_int(root_id.into())),
+            ("stack", Dynamic::from(stack)),
+            ("signals", Dynamic::from(signals)),
+        ])
+    }
+
+    /// The input of `handle_transaction_result`, for items that were taken, or of `handle_transaction_failed`, for items that were refused.
+    pub fn transaction_result(self, result: Result<ItemStack, Refusal>) -> rhai::Map {
+        match result {
+            Ok(transferred) => self.with([("transferred", Dynamic::from(transferred))]),
+            Err(refusal) => self.with([
+                ("stack", Dynamic::from(refusal.stack)),
+                ("refused_coord", Dynamic::from(refusal.coord)),
+                ("reason", refusal.reason.name().into()),
+            ]),
+        }
+    }
+
+    /// The input of `handle_extract_request`.
+    pub fn extract_request(
+        self,
+        requested_from_coord: TileCoord,
+        requested_from_id: Id,
+    ) -> rhai::Map {
+        self.with([
+            ("requested_from_coord", Dynamic::from(requested_from_coord)),
+            (
+                "requested_from_id",
+                Dynamic::from_int(requested_from_id.into()),
+            ),
+        ])
+    }
+}
+
+impl TileEntityMsg {
+    /// Is the message a part of the game running, and so counted in the [`PendingMessages`]?
+    pub fn is_counted(&self) -> bool {
+        matches!(
+            self,
+            Tick { .. }
+                | Transaction { .. }
+                | TransactionResult { .. }
+                | ExtractRequest { .. }
+                | AdjacentState { .. }
+                | ReportRemaining { .. }
+                | Deplete { .. }
+                | Sense { .. }
+                | Advertise { .. }
+                | PostRequest { .. }
+                | Deliver { .. }
+                | Carry { .. }
+        )
+    }
+}
+
+/// Marks a counted message as handled when dropped, so that returning early still does.
+struct Handled(PendingMessages);
+
+impl Drop for Handled {
+    fn drop(&mut self) {
+        mark_handled(&self.0);
+    }
+}
+
 impl TileEntity {
+    /// Gets a random number for a handler. It is derived from the map's seed, the tick, the tile's position, and how many the tile was given this tick, so that the same map plays out the same way, in replays too.
+    fn random(&self, state: &mut TileEntityState) -> INT {
+        state.draws += 1;
+
+        let coord = ((self.coord.q() as u32 as u64) << 32) | self.coord.r() as u32 as u64;
+
+        [state.tick_count as u64, coord, state.draws]
+            .into_iter()
+            .fold(mix(state.seed as u64), |hash, value| mix(hash ^ value)) as INT
+    }
+
+    /// Gets the entries every handler is given in its input, with the given random number.
+    fn input(&self, random: INT) -> HandlerInput {
+        HandlerInput {
+            id: self.id,
+            coord: self.coord,
+            tile_modifier: self.tile_modifier,
+            random,
+        }
+    }
+
+    /// Gets the signals on the network the tile is linked to, if it is linked to one that has any.
+    fn network_signals<'a>(&self, state: &'a TileEntityState) -> Option<&'a Inventory> {
+        state
+            .data
+            .get(&self.resource_man.registry.data_ids.link)
+            .and_then(Data::as_coord)
+            .and_then(|link| state.signals.get(link))
+    }
+
+    /// Is the tile's enable condition met, or does it not have one?
+    fn condition_met(&self, state: &TileEntityState) -> bool {
+        Condition::from_data(&self.resource_man.registry.data_ids, &state.data)
+            .map_or(true, |condition| {
+                condition.is_met(self.network_signals(state))
+            })
+    }
+
     fn handle_rhai_transaction_result(
         &self,
         state: &mut TileEntityState,
@@ -113,6 +395,7 @@ impl TileEntity {
         source_id: Id,
         root_coord: TileCoord,
         root_id: Id,
+        fallback: Option<TileCoord>,
         result: rhai::Array,
     ) -> Option<GameMsg> {
         let ty: ImmutableString = result[0].clone().cast();
@@ -120,6 +403,10 @@ impl TileEntity {
         match ty.as_str() {
             "pass_on" => {
                 let coord: TileCoord = result[1].clone().cast();
+                // where the items go if that tile refuses them
+                let fallback = result
+                    .get(2)
+                    .and_then(|v| v.clone().try_cast::<TileCoord>());
 
                 send_to_tile(
                     state,
@@ -130,6 +417,7 @@ impl TileEntity {
                         source_coord: self.coord,
                         root_id,
                         root_coord,
+                        fallback,
                     },
                 );
 
@@ -147,32 +435,115 @@ impl TileEntity {
                         source_coord,
                         root_id,
                         root_coord,
+                        fallback,
                     },
                 );
             }
             "consume" => {
-                let consumed: ItemAmount = result[1].clone().cast();
+                let consumed = ItemStack {
+                    item: stack.item,
+                    amount: result[1]
+                        .clone()
+                        .cast::<ItemAmount>()
+                        .clamp(0, stack.amount),
+                };
+                // what the items were taken in for, if the function says. Machines only count their inputs once a recipe uses them up
+                let stat = match result.get(2).and_then(|v| v.clone().into_string().ok()) {
+                    Some(stat) if stat == "stored" => Some(ItemStat::Stored),
+                    Some(stat) if stat == "consumed" => Some(ItemStat::Consumed),
+                    _ => None,
+                };
 
                 send_to_tile(
                     state,
                     root_coord,
                     TransactionResult {
-                        result: Ok(ItemStack {
-                            item: stack.item,
-                            amount: consumed,
-                        }),
+                        result: Ok(consumed),
                     },
                 );
+                if let Some(stat) = stat {
+                    state
+                        .game
+                        .send_message(GameMsg::RecordItemStat(stat, consumed))
+                        .unwrap();
+                }
+
+                // what wasn't taken is refused, so that the tiles it came from don't lose it
+                if consumed.amount < stack.amount {
+                    send_refusal(
+                        state,
+                        source_coord,
+                        source_id,
+                        root_coord,
+                        root_id,
+                        fallback,
+                        Refusal {
+                            stack: ItemStack {
+                                item: stack.item,
+                                amount: stack.amount - consumed.amount,
+                            },
+                            coord: self.coord,
+                            reason: RefusalReason::Rejected,
+                        },
+                    );
+                }
 
                 return Some(GameMsg::RecordTransaction(stack, source_coord, self.coord));
             }
-            _ => (),
+            // every transaction is answered, so that the tile offering the items doesn't wait on it
+            _ => send_refusal(
+                state,
+                source_coord,
+                source_id,
+                root_coord,
+                root_id,
+                fallback,
+                Refusal {
+                    stack,
+                    coord: self.coord,
+                    reason: RefusalReason::Rejected,
+                },
+            ),
         }
 
         None
     }
 
+    /// Asks the game whether the adjacent tiles fulfill what the tile's script needs.
+    fn check_adjacent(&self, state: &mut TileEntityState) {
+        let Some(script) = state
+            .data
+            .get(&self.resource_man.registry.data_ids.script)
+            .and_then(Data::as_id)
+            .and_then(|script| self.resource_man.registry.script(*script))
+            .cloned()
+        else {
+            return;
+        };
+
+        // counts the reply to the check, which ends in an AdjacentState
+        state.pending.fetch_add(1, Ordering::SeqCst);
+
+        state
+            .game
+            .send_message(GameMsg::CheckAdjacent {
+                script,
+                coord: self.coord,
+                self_coord: self.coord,
+            })
+            .unwrap();
+    }
+
     fn handle_rhai_result(&self, state: &mut TileEntityState, result: rhai::Array) {
+        // several results can be returned at once, as an array of them
+        if result.first().map_or(false, Dynamic::is_array) {
+            for result in result {
+                self.handle_rhai_result(state, result.cast());
+            }
+
+            return;
+        }
+
         let ty: ImmutableString = result[0].clone().cast();
 
         match ty.as_str() {
@@ -181,10 +552,24 @@ impl TileEntity {
                 let source_id: Id = result[2].clone().cast::<INT>().into();
                 let source_coord: TileCoord = result[3].clone().cast();
 
-                let stacks: Vec<ItemStack> = result[4]
-                    .clone()
-                    .try_cast()
-                    .unwrap_or_else(|| result[4].clone().into_typed_array().unwrap());
+                let stacks: Vec<ItemStack> =
+                    if let Some(inventory) = result[4].clone().try_cast::<Inventory>() {
+                        inventory
+                            .iter()
+                            .filter(|(_, amount)| **amount > 0)
+                            .flat_map(|(id, amount)| {
+                                self.resource_man.registry.item(*id).map(|item| ItemStack {
+                                    item: *item,
+                                    amount: *amount,
+                                })
+                            })
+                            .collect()
+                    } else {
+                        result[4]
+                            .clone()
+                            .try_cast()
+                            .unwrap_or_else(|| result[4].clone().into_typed_array().unwrap())
+                    };
 
                 for stack in stacks {
                     send_to_tile(
@@ -196,10 +581,121 @@ impl TileEntity {
                             source_id,
                             root_coord: source_coord,
                             root_id: source_id,
+                            fallback: None,
                         },
                     );
                 }
             }
+            "produce" => {
+                let produced = result[1]
+                    .clone()
+                    .into_typed_array::<ItemStack>()
+                    .unwrap_or_default();
+
+                for stack in produced {
+                    state
+                        .game
+                        .send_message(GameMsg::RecordItemStat(ItemStat::Produced, stack))
+                        .unwrap();
+                }
+            }
+            "use" => {
+                let used = result[1]
+                    .clone()
+                    .into_typed_array::<ItemStack>()
+                    .unwrap_or_default();
+
+                for stack in used {
+                    state
+                        .game
+                        .send_message(GameMsg::RecordItemStat(ItemStat::Consumed, stack))
+                        .unwrap();
+                }
+            }
+            "unstore" => {
+                let stack: ItemStack = result[1].clone().cast();
+
+                // what leaves a storage is taken off what was stored, so that items moved between storages aren't counted again
+                state
+                    .game
+                    .send_message(GameMsg::RecordItemStat(
+                        ItemStat::Stored,
+                        ItemStack {
+                            item: stack.item,
+                            amount: -stack.amount,
+                        },
+                    ))
+                    .unwrap();
+            }
+            "deplete" => {
+                let coord: TileCoord = result[1].clone().cast();
+                let amount: ItemAmount = result[2].clone().cast();
+
+                // what was taken is counted right away, so the tile doesn't take more than is left before the game checks again
+                if let Some(remaining) = &mut state.adjacent_remaining {
+                    *remaining = (*remaining - amount).max(0);
+                }
+
+                send_to_tile(state, coord, Deplete { amount });
+            }
+            "signal" => {
+                let network: TileCoord = result[1].clone().cast();
+                let key: Id = result[2].clone().cast::<INT>().into();
+                let value: ItemAmount = result[3].clone().cast();
+
+                let mut signals = Inventory::default();
+                signals.insert(key, value);
+
+                state
+                    .game
+                    .send_message(GameMsg::PublishSignals(
+                        network,
+                        self.coord,
+                        signals,
+                        state.tick_count,
+                    ))
+                    .unwrap();
+            }
+            "sense" => {
+                let coord: TileCoord = result[1].clone().cast();
+                let network: TileCoord = result[2].clone().cast();
+
+                send_to_tile(state, coord, Sense { network });
+            }
+            "request" => {
+                let item: Id = result[1].clone().cast::<INT>().into();
+                let amount: ItemAmount = result[2].clone().cast();
+
+                let request = Request {
+                    requester: self.coord,
+                    item,
+                    amount,
+                };
+
+                // the tile the items are for takes off what it already has
+                if let Some(coord) = result
+                    .get(3)
+                    .and_then(|v| v.clone().try_cast::<TileCoord>())
+                {
+                    send_to_tile(state, coord, PostRequest { request });
+                } else {
+                    state
+                        .game
+                        .send_message(GameMsg::PostRequest(request))
+                        .unwrap();
+                }
+            }
+            "advertise" => {
+                let coord: TileCoord = result[1].clone().cast();
+
+                send_to_tile(
+                    state,
+                    coord,
+                    Advertise {
+                        provider: self.coord,
+                    },
+                );
+            }
             "make_extract_request" => {
                 let coord: TileCoord = result[1].clone().cast();
                 let requested_from_id: Id = result[2].clone().cast::<INT>().into();
@@ -226,7 +722,32 @@ impl TileEntity {
         source_id: Id,
         root_coord: TileCoord,
         root_id: Id,
+        fallback: Option<TileCoord>,
     ) -> Option<GameMsg> {
+        let refusal = Refusal {
+            stack,
+            coord: self.coord,
+            reason: RefusalReason::Rejected,
+        };
+
+        // a disabled tile takes nothing
+        if !self.condition_met(state) {
+            send_refusal(
+                state,
+                source_coord,
+                source_id,
+                root_coord,
+                root_id,
+                fallback,
+                Refusal {
+                    reason: RefusalReason::Disabled,
+                    ..refusal
+                },
+            );
+
+            return None;
+        }
+
         let tile = self.resource_man.registry.tile(self.id).unwrap();
 
         if let Some((ast, default_scope)) = tile
@@ -234,6 +755,10 @@ impl TileEntity {
             .as_ref()
             .and_then(|v| self.resource_man.functions.get(v))
         {
+            let signals = self.network_signals(state).cloned().unwrap_or_default();
+
+            let random = self.random(state);
+
             let scope = state
                 .scope
                 .get_or_insert_with(|| default_scope.clone_visible());
@@ -246,26 +771,22 @@ impl TileEntity {
 
             let options = rhai_call_options(&mut rhai_state);
 
+            let start = state.profiler.start();
             let result = self.resource_man.engine.call_fn_with_options::<Dynamic>(
                 options,
                 scope,
                 ast,
                 "handle_transaction",
-                (rhai::Map::from([
-                    (
-                        "tile_modifier".into(),
-                        Dynamic::from_int(self.tile_modifier),
-                    ),
-                    ("coord".into(), Dynamic::from(self.coord)),
-                    ("id".into(), Dynamic::from_int(self.id.into())),
-                    ("source_coord".into(), Dynamic::from(source_coord)),
-                    ("source_id".into(), Dynamic::from_int(source_id.into())),
-                    ("root_coord".into(), Dynamic::from(root_coord)),
-                    ("root_id".into(), Dynamic::from_int(root_id.into())),
-                    ("random".into(), Dynamic::from_int(random())),
-                    ("stack".into(), Dynamic::from(stack)),
-                ]),),
+                (self.input(random).transaction(
+                    stack,
+                    source_coord,
+                    source_id,
+                    root_coord,
+                    root_id,
+                    signals,
+                ),),
             );
+            state.profiler.finish(self.id, Handler::Transaction, start);
 
             state.rhai_map = rhai_state.take().cast::<rhai::Map>();
             state.data = state
@@ -283,13 +804,23 @@ impl TileEntity {
                     source_id,
                     root_coord,
                     root_id,
+                    fallback,
                     result,
                 );
-            } else {
-                send_to_tile(state, source_coord, TransactionResult { result: Err(()) });
-            };
+            }
         }
 
+        // the tile didn't take the items, or has no function to take them with
+        send_refusal(
+            state,
+            source_coord,
+            source_id,
+            root_coord,
+            root_id,
+            fallback,
+            refusal,
+        );
+
         None
     }
 }
@@ -298,14 +829,14 @@ impl TileEntity {
 impl Actor for TileEntity {
     type Msg = TileEntityMsg;
     type State = TileEntityState;
-    type Arguments = (ActorRef<GameMsg>,);
+    type Arguments = (ActorRef<GameMsg>, PendingMessages, Arc<Profiler>, Changes);
 
     async fn pre_start(
         &self,
         _myself: ActorRef<Self::Msg>,
         args: Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        Ok(TileEntityState::new(args.0))
+        Ok(TileEntityState::new(args.0, args.1, args.2, args.3))
     }
 
     async fn handle(
@@ -313,31 +844,72 @@ impl Actor for TileEntity {
         _myself: ActorRef<Self::Msg>,
         message: Self::Msg,
         state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let _handled = message.is_counted().then(|| Handled(state.pending.clone()));
+
+        // the data is only compared while something watches the map. What is taken leaves with the tile, which the game tells of itself
+        let old_data = (state.changes.receiver_count() > 0 && !matches!(message, TakeData(_)))
+            .then(|| state.data.clone());
+
+        self.handle_msg(message, state).await?;
+
+        if let Some(old_data) = old_data {
+            if old_data != state.data {
+                _ = state
+                    .changes
+                    .send(TileChange::Data(self.coord, state.data.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TileEntity {
+    /// Handles a message, apart from telling of what it changed.
+    async fn handle_msg(
+        &self,
+        message: TileEntityMsg,
+        state: &mut TileEntityState,
     ) -> Result<(), ActorProcessingErr> {
         match message {
-            Tick { tick_count } => {
+            Tick {
+                tick_count,
+                signals,
+                seed,
+            } => {
+                state.signals = signals;
+                state.seed = seed;
+                state.tick_count = tick_count;
+                state.draws = 0;
+
                 let tile = self.resource_man.registry.tile(self.id).unwrap();
 
                 if tick_count % 10 == 0 {
-                    if let Some(script) = state
+                    self.check_adjacent(state);
+
+                    // keeps the tile on the logistics network it is linked to
+                    if let Some(link) = state
                         .data
-                        .get(&self.resource_man.registry.data_ids.script)
-                        .and_then(Data::as_id)
+                        .get(&self.resource_man.registry.data_ids.link)
+                        .and_then(Data::as_coord)
+                        .filter(|_| {
+                            self.resource_man
+                                .registry
+                                .tile_data(self.id, self.resource_man.registry.data_ids.logistics)
+                                .and_then(Data::as_bool)
+                                .cloned()
+                                .unwrap_or(false)
+                        })
                     {
-                        if let Some(script) = self.resource_man.registry.script(*script).cloned() {
-                            state
-                                .game
-                                .send_message(GameMsg::CheckAdjacent {
-                                    script,
-                                    coord: self.coord,
-                                    self_coord: self.coord,
-                                })
-                                .unwrap();
-                        }
+                        state
+                            .game
+                            .send_message(GameMsg::ReportLink(self.coord, *link))
+                            .unwrap();
                     }
                 }
 
-                if !state.adjacent_fulfilled {
+                if !state.adjacent_fulfilled || !self.condition_met(state) {
                     return Ok(());
                 }
 
@@ -346,6 +918,10 @@ impl Actor for TileEntity {
                     .as_ref()
                     .and_then(|v| self.resource_man.functions.get(v))
                 {
+                    let signals = self.network_signals(state).cloned().unwrap_or_default();
+
+                    let random = self.random(state);
+
                     let scope = state
                         .scope
                         .get_or_insert_with(|| default_scope.clone_visible());
@@ -356,21 +932,19 @@ impl Actor for TileEntity {
 
                     let mut rhai_state = Dynamic::from_map(state.rhai_map.clone());
 
+                    let start = state.profiler.start();
                     let result = self.resource_man.engine.call_fn_with_options::<Dynamic>(
                         rhai_call_options(&mut rhai_state),
                         scope,
                         ast,
                         "handle_tick",
-                        (rhai::Map::from([
-                            (
-                                "tile_modifier".into(),
-                                Dynamic::from_int(self.tile_modifier),
-                            ),
-                            ("coord".into(), Dynamic::from(self.coord)),
-                            ("id".into(), Dynamic::from_int(self.id.into())),
-                            ("random".into(), Dynamic::from_int(random())),
-                        ]),),
+                        (self.input(random).tick(
+                            state.adjacent,
+                            state.adjacent_remaining,
+                            signals,
+                        ),),
                     );
+                    state.profiler.finish(self.id, Handler::Tick, start);
 
                     state.rhai_map = rhai_state.take().cast::<rhai::Map>();
                     state.data = state
@@ -391,64 +965,176 @@ impl Actor for TileEntity {
                 source_id,
                 root_coord,
                 root_id,
+                fallback,
             } => {
-                if let Some(record) =
-                    self.transaction(state, stack, source_coord, source_id, root_coord, root_id)
-                {
+                state.profiler.count_transaction();
+
+                if let Some(record) = self.transaction(
+                    state,
+                    stack,
+                    source_coord,
+                    source_id,
+                    root_coord,
+                    root_id,
+                    fallback,
+                ) {
                     state.game.send_message(record).unwrap();
                 }
             }
             TransactionResult { result } => {
-                if let Ok(transferred) = result {
-                    let tile = self.resource_man.registry.tile(self.id).unwrap();
+                // the tile hears about the transactions that were refused too, so that it can send the items elsewhere
+                let handler = match result {
+                    Ok(_) => Handler::TransactionResult,
+                    Err(_) => Handler::TransactionFailed,
+                };
 
-                    if let Some((ast, default_scope)) = tile
-                        .function
-                        .as_ref()
-                        .and_then(|v| self.resource_man.functions.get(v))
-                    {
-                        let scope = state
-                            .scope
-                            .get_or_insert_with(|| default_scope.clone_visible());
+                let tile = self.resource_man.registry.tile(self.id).unwrap();
 
-                        state
-                            .rhai_map
-                            .insert(RHAI_DATA_MAP_KEY.into(), Dynamic::from(state.data.clone()));
-
-                        let mut rhai_state = Dynamic::from_map(state.rhai_map.clone());
-
-                        _ = self.resource_man.engine.call_fn_with_options::<Dynamic>(
-                            rhai_call_options(&mut rhai_state),
-                            scope,
-                            ast,
-                            "handle_transaction_result",
-                            (rhai::Map::from([
-                                (
-                                    "tile_modifier".into(),
-                                    Dynamic::from_int(self.tile_modifier),
-                                ),
-                                ("coord".into(), Dynamic::from(self.coord)),
-                                ("id".into(), Dynamic::from_int(self.id.into())),
-                                ("random".into(), Dynamic::from_int(random())),
-                                ("transferred".into(), Dynamic::from(transferred)),
-                            ]),),
-                        );
-
-                        state.rhai_map = rhai_state.take().cast::<rhai::Map>();
-                        state.data = state
-                            .rhai_map
-                            .get(RHAI_DATA_MAP_KEY)
-                            .cloned()
-                            .unwrap()
-                            .cast();
+                if let Some((ast, default_scope)) = tile
+                    .function
+                    .as_ref()
+                    .and_then(|v| self.resource_man.functions.get(v))
+                {
+                    let random = self.random(state);
+
+                    let scope = state
+                        .scope
+                        .get_or_insert_with(|| default_scope.clone_visible());
+
+                    state
+                        .rhai_map
+                        .insert(RHAI_DATA_MAP_KEY.into(), Dynamic::from(state.data.clone()));
+
+                    let mut rhai_state = Dynamic::from_map(state.rhai_map.clone());
+
+                    let input = self.input(random).transaction_result(result);
+
+                    let start = state.profiler.start();
+                    let result = self.resource_man.engine.call_fn_with_options::<Dynamic>(
+                        rhai_call_options(&mut rhai_state),
+                        scope,
+                        ast,
+                        handler.function_name(),
+                        (input,),
+                    );
+                    state.profiler.finish(self.id, handler, start);
+
+                    state.rhai_map = rhai_state.take().cast::<rhai::Map>();
+                    state.data = state
+                        .rhai_map
+                        .get(RHAI_DATA_MAP_KEY)
+                        .cloned()
+                        .unwrap()
+                        .cast();
+
+                    if let Some(result) = result.ok().and_then(|v| v.try_cast::<rhai::Array>()) {
+                        self.handle_rhai_result(state, result);
                     }
                 }
             }
+            PostRequest { request } => {
+                let held = state
+                    .data
+                    .get(&self.resource_man.registry.data_ids.buffer)
+                    .and_then(Data::as_inventory)
+                    .and_then(|buffer| buffer.deref().get(&request.item).cloned())
+                    .unwrap_or(0);
+
+                state
+                    .game
+                    .send_message(GameMsg::PostRequest(Request {
+                        amount: request.amount - held,
+                        ..request
+                    }))
+                    .unwrap();
+            }
+            Deliver {
+                item,
+                amount,
+                requester,
+                path,
+            } => {
+                let buffer_id = self.resource_man.registry.data_ids.buffer;
+
+                let Some(item) = self.resource_man.registry.item(item).cloned() else {
+                    return Ok(());
+                };
+
+                // only what the tile still has is sent, as it may have changed since it was advertised
+                let taken = match state.data.get_mut(&buffer_id) {
+                    Some(Data::Inventory(buffer)) => buffer.take(item.id, amount.max(0)),
+                    _ => None,
+                };
+
+                let Some(amount) = taken else {
+                    return Ok(());
+                };
+
+                let path = if path.is_empty() {
+                    vec![requester]
+                } else {
+                    path
+                };
+
+                carry(state, ItemStack { item, amount }, self.coord, self.id, path);
+            }
+            Carry {
+                stack,
+                storage,
+                storage_id,
+                path,
+            } => {
+                // draws the items going along the network
+                if let Some(next) = path.first() {
+                    state
+                        .game
+                        .send_message(GameMsg::RecordTransaction(stack, self.coord, *next))
+                        .unwrap();
+                }
+
+                carry(state, stack, storage, storage_id, path);
+            }
+            TakeItems {
+                item,
+                amount,
+                reply,
+            } => {
+                let buffer_id = self.resource_man.registry.data_ids.buffer;
+
+                let taken = match state.data.get_mut(&buffer_id) {
+                    Some(Data::Inventory(buffer)) => buffer.take(item, amount.max(0)),
+                    _ => None,
+                };
+
+                if let Some((item, taken)) = self.resource_man.registry.item(item).zip(taken) {
+                    state
+                        .game
+                        .send_message(GameMsg::RecordItemStat(
+                            ItemStat::Stored,
+                            ItemStack {
+                                item: *item,
+                                amount: -taken,
+                            },
+                        ))
+                        .unwrap();
+                }
+
+                if let Some(reply) = reply {
+                    reply.send(taken).unwrap();
+                }
+            }
             SetData(data) => {
                 state.data = data;
+
+                // checks straight away, so that a tile placed with its data doesn't wait for the next check
+                self.check_adjacent(state);
             }
             SetDataValue(key, value) => {
                 state.data.insert(key, value);
+
+                if key == self.resource_man.registry.data_ids.script {
+                    self.check_adjacent(state);
+                }
             }
             TakeData(reply) => {
                 reply.send(mem::take(&mut state.data)).unwrap();
@@ -476,6 +1162,8 @@ impl Actor for TileEntity {
                     .as_ref()
                     .and_then(|v| self.resource_man.functions.get(v))
                 {
+                    let random = self.random(state);
+
                     let scope = state
                         .scope
                         .get_or_insert_with(|| default_scope.clone_visible());
@@ -486,29 +1174,19 @@ impl Actor for TileEntity {
 
                     let mut rhai_state = Dynamic::from_map(state.rhai_map.clone());
 
+                    let start = state.profiler.start();
                     let result = self.resource_man.engine.call_fn_with_options::<Dynamic>(
                         rhai_call_options(&mut rhai_state),
                         scope,
                         ast,
                         "handle_extract_request",
-                        (rhai::Map::from([
-                            (
-                                "tile_modifier".into(),
-                                Dynamic::from_int(self.tile_modifier),
-                            ),
-                            ("coord".into(), Dynamic::from(self.coord)),
-                            ("id".into(), Dynamic::from_int(self.id.into())),
-                            ("random".into(), Dynamic::from_int(random())),
-                            (
-                                "requested_from_coord".into(),
-                                Dynamic::from(requested_from_coord),
-                            ),
-                            (
-                                "requested_from_id".into(),
-                                Dynamic::from_int(requested_from_id.into()),
-                            ),
-                        ]),),
+                        (self
+                            .input(random)
+                            .extract_request(requested_from_coord, requested_from_id),),
                     );
+                    state
+                        .profiler
+                        .finish(self.id, Handler::ExtractRequest, start);
 
                     state.rhai_map = rhai_state.take().cast::<rhai::Map>();
                     state.data = state
@@ -523,8 +1201,124 @@ impl Actor for TileEntity {
                     }
                 }
             }
-            AdjacentState { fulfilled } => {
+            AdjacentState {
+                fulfilled,
+                adjacent,
+                remaining,
+            } => {
                 state.adjacent_fulfilled = fulfilled;
+                state.adjacent = adjacent;
+                state.adjacent_remaining = remaining;
+            }
+            ReportRemaining { to } => {
+                let registry = &self.resource_man.registry;
+
+                let remaining = state
+                    .data
+                    .get(&registry.data_ids.remaining)
+                    .or_else(|| registry.tile_data(self.id, registry.data_ids.remaining))
+                    .and_then(Data::as_amount)
+                    .cloned();
+
+                send_to_tile(
+                    state,
+                    to,
+                    AdjacentState {
+                        fulfilled: true,
+                        adjacent: Some(self.coord),
+                        remaining,
+                    },
+                );
+            }
+            Sense { network } => {
+                if let Some(buffer) = state
+                    .data
+                    .get(&self.resource_man.registry.data_ids.buffer)
+                    .and_then(Data::as_inventory)
+                {
+                    state
+                        .game
+                        .send_message(GameMsg::PublishSignals(
+                            network,
+                            self.coord,
+                            buffer.clone(),
+                            state.tick_count,
+                        ))
+                        .unwrap();
+                }
+            }
+            Advertise { provider } => {
+                let registry = &self.resource_man.registry;
+
+                if !registry
+                    .tile_data(self.id, registry.data_ids.storage_takeable)
+                    .and_then(Data::as_bool)
+                    .cloned()
+                    .unwrap_or(false)
+                {
+                    return Ok(());
+                }
+
+                let contents = state
+                    .data
+                    .get(&registry.data_ids.buffer)
+                    .and_then(Data::as_inventory)
+                    .cloned()
+                    .unwrap_or_default();
+
+                state
+                    .game
+                    .send_message(GameMsg::Advertise {
+                        provider,
+                        storage: self.coord,
+                        contents,
+                    })
+                    .unwrap();
+            }
+            Deplete { amount } => {
+                let registry = &self.resource_man.registry;
+
+                // tiles without a remaining amount never run out
+                let Some(remaining) = state
+                    .data
+                    .get(&registry.data_ids.remaining)
+                    .or_else(|| registry.tile_data(self.id, registry.data_ids.remaining))
+                    .and_then(Data::as_amount)
+                    .cloned()
+                else {
+                    return Ok(());
+                };
+
+                // already exhausted, and waiting to be replaced
+                if remaining <= 0 {
+                    return Ok(());
+                }
+
+                let remaining = (remaining - amount).max(0);
+
+                state
+                    .data
+                    .insert(registry.data_ids.remaining, Data::Amount(remaining));
+
+                if remaining == 0 {
+                    let exhausted = registry
+                        .tile_data(self.id, registry.data_ids.exhausted_tile)
+                        .and_then(Data::as_id)
+                        .cloned()
+                        .unwrap_or(registry.none);
+
+                    state
+                        .game
+                        .send_message(GameMsg::PlaceTile {
+                            coord: self.coord,
+                            id: exhausted,
+                            tile_modifier: 0,
+                            data: None,
+                            record: false,
+                            reply: None,
+                        })
+                        .unwrap();
+                }
             }
         }
 
@@ -532,18 +1326,113 @@ impl Actor for TileEntity {
     }
 }
 
+/// Sends the refused items on to the fallback they were given, if there is one.
+/// Otherwise tells the tile that sent them that they were refused, and the tile they were first sent from too, so that it can hold on to them.
+/// Sends items a logistics network delivers to the first tile of the path, which carries them on, or is offered them if it is the requester at the end.
+fn carry(
+    state: &mut TileEntityState,
+    stack: ItemStack,
+    storage: TileCoord,
+    storage_id: Id,
+    path: Vec<TileCoord>,
+) {
+    let Some((next, rest)) = path.split_first() else {
+        return;
+    };
+
+    let message = if rest.is_empty() {
+        Transaction {
+            stack,
+            source_coord: storage,
+            source_id: storage_id,
+            root_coord: storage,
+            root_id: storage_id,
+            fallback: None,
+        }
+    } else {
+        Carry {
+            stack,
+            storage,
+            storage_id,
+            path: rest.to_vec(),
+        }
+    };
+
+    send_to_tile(state, *next, message);
+}
+
+fn send_refusal(
+    state: &mut TileEntityState,
+    source_coord: TileCoord,
+    source_id: Id,
+    root_coord: TileCoord,
+    root_id: Id,
+    fallback: Option<TileCoord>,
+    refusal: Refusal,
+) {
+    if let Some(fallback) = fallback {
+        send_to_tile(
+            state,
+            fallback,
+            Transaction {
+                stack: refusal.stack,
+                source_coord,
+                source_id,
+                root_coord,
+                root_id,
+                fallback: None,
+            },
+        );
+
+        return;
+    }
+
+    send_to_tile(
+        state,
+        source_coord,
+        TransactionResult {
+            result: Err(refusal),
+        },
+    );
+
+    if root_coord != source_coord {
+        send_to_tile(
+            state,
+            root_coord,
+            TransactionResult {
+                result: Err(refusal),
+            },
+        );
+    }
+}
+
 fn send_to_tile(state: &mut TileEntityState, coord: TileCoord, message: TileEntityMsg) {
+    let counted = message.is_counted();
+
+    if counted {
+        state.pending.fetch_add(1, Ordering::SeqCst);
+    }
+
     match state
         .game
         .send_message(GameMsg::ForwardMsgToTile(coord, message))
     {
         Ok(_) => {}
         Err(_) => {
+            if counted {
+                mark_handled(&state.pending);
+            }
+
             state.data.clear();
         }
     }
 }
 
-fn random() -> i32 {
-    thread_rng().next_u32() as i32
+/// Scrambles the bits of a number, with the finalizer of SplitMix64.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    x ^ (x >> 31)
 }
@@ -0,0 +1,130 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhai::INT;
+
+use automancy_defs::coord::{TileCoord, TileUnit};
+use automancy_defs::hashbrown::HashSet;
+use automancy_defs::id::Id;
+use automancy_resources::types::deposit::Deposit;
+use automancy_resources::ResourceManager;
+
+/// How many times generation tries to find a free spot for a cluster, for each cluster it wants to place.
+const CLUSTER_ATTEMPTS: usize = 8;
+
+/// The parameters world generation is run with. The same parameters and resources always generate the same world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldGenSettings {
+    pub seed: INT,
+    /// How far from the center deposits can be placed.
+    pub radius: TileUnit,
+    /// Roughly how many out of every 100 tiles are deposits.
+    pub density: INT,
+}
+
+impl Default for WorldGenSettings {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            radius: 32,
+            density: 5,
+        }
+    }
+}
+
+fn distance(coord: TileCoord) -> TileUnit {
+    (coord.q().abs() + coord.r().abs() + (coord.q() + coord.r()).abs()) / 2
+}
+
+fn random_coord(rng: &mut StdRng, radius: TileUnit) -> TileCoord {
+    loop {
+        let coord = TileCoord::new(
+            rng.gen_range(-radius..=radius),
+            rng.gen_range(-radius..=radius),
+        );
+
+        if distance(coord) <= radius {
+            return coord;
+        }
+    }
+}
+
+fn pick_deposit<'a>(rng: &mut StdRng, deposits: &[&'a Deposit], total_weight: INT) -> &'a Deposit {
+    let mut roll = rng.gen_range(0..total_weight);
+
+    for deposit in deposits {
+        if roll < deposit.weight {
+            return deposit;
+        }
+
+        roll -= deposit.weight;
+    }
+
+    unreachable!()
+}
+
+/// Generates the deposits of a new map, from every deposit type in the loaded namespaces.
+///
+/// Deposits are grown in clusters from random free tiles, and never overlap each other or the tiles in `occupied`.
+pub fn generate(
+    resource_man: &ResourceManager,
+    settings: WorldGenSettings,
+    occupied: &HashSet<TileCoord>,
+) -> Vec<(TileCoord, Id)> {
+    let mut deposits = resource_man
+        .registry
+        .deposits
+        .values()
+        .filter(|deposit| deposit.weight > 0)
+        .collect::<Vec<_>>();
+    // ids are interned in load order, so they are sorted by name to stay the same across resource changes
+    deposits.sort_by_key(|deposit| resource_man.interner.resolve(deposit.id));
+
+    let total_weight = deposits.iter().map(|deposit| deposit.weight).sum::<INT>();
+    let radius = settings.radius.max(0);
+
+    if total_weight == 0 || settings.density <= 0 {
+        return vec![];
+    }
+
+    let area = 3 * radius as INT * (radius as INT + 1) + 1;
+    let target = (area * settings.density.min(100) / 100) as usize;
+
+    let mut rng = StdRng::seed_from_u64(settings.seed as u64);
+    let mut taken = occupied.clone();
+    let mut tiles = vec![];
+
+    let mut attempts = 0;
+    while tiles.len() < target && attempts < target * CLUSTER_ATTEMPTS {
+        attempts += 1;
+
+        let deposit = pick_deposit(&mut rng, &deposits, total_weight);
+        let size = rng.gen_range(deposit.size.0..=deposit.size.1) as usize;
+        let center = random_coord(&mut rng, radius);
+
+        if taken.contains(&center) {
+            continue;
+        }
+
+        let mut cluster = vec![center];
+        taken.insert(center);
+
+        // grows the cluster from random tiles in it, and gives up if it gets surrounded
+        let mut misses = 0;
+        while cluster.len() < size && misses < size * CLUSTER_ATTEMPTS {
+            let from = cluster[rng.gen_range(0..cluster.len())];
+            let next = from.neighbors()[rng.gen_range(0..6)];
+
+            if distance(next) > radius || taken.contains(&next) {
+                misses += 1;
+                continue;
+            }
+
+            cluster.push(next);
+            taken.insert(next);
+        }
+
+        tiles.extend(cluster.into_iter().map(|coord| (coord, deposit.tile)));
+    }
+
+    tiles
+}
@@ -0,0 +1,231 @@
+use automancy::tile_entity::RefusalReason;
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::stack::ItemStack;
+use automancy_resources::data::Data;
+
+use crate::support::{action, find_action, id, stack, FunctionTest};
+
+fn machine(tile: &str, script: &str) -> FunctionTest {
+    let mut test = FunctionTest::new(tile);
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data.insert(data_ids.script, Data::Id(id(script)));
+    test.data
+        .insert(data_ids.target, Data::Coord(TileCoord::RIGHT));
+
+    test
+}
+
+#[test]
+fn test_machine_without_target_does_nothing() {
+    let mut test = FunctionTest::new("automancy:basic_extractor");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data
+        .insert(data_ids.script, Data::Id(id("automancy:coal")));
+
+    for _ in 0..100 {
+        assert!(test.tick().is_none());
+    }
+    assert!(test.data.get(&data_ids.progress).is_none());
+}
+
+#[test]
+fn test_machine_outputs_after_duration() {
+    let mut test = machine("automancy:basic_extractor", "automancy:coal");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    for i in 1..15 {
+        assert!(test.tick().is_none());
+        assert_eq!(
+            test.data.get(&data_ids.progress).and_then(Data::as_amount),
+            Some(&i)
+        );
+    }
+
+    let result = test.tick().expect("The machine should output");
+
+    let produced = find_action(&result, "produce")
+        .expect("The machine should report what it produced")[1]
+        .clone()
+        .into_typed_array::<ItemStack>()
+        .unwrap();
+    assert_eq!(produced, vec![stack("automancy:coal", 8)]);
+
+    let transaction = find_action(&result, "make_transaction").expect("The machine should output");
+    assert_eq!(transaction[1].clone().cast::<TileCoord>(), TileCoord::RIGHT);
+
+    let outputs = transaction[4].clone().cast::<Inventory>();
+    assert_eq!(outputs[&id("automancy:coal")], 8);
+
+    assert_eq!(
+        test.data.get(&data_ids.progress).and_then(Data::as_amount),
+        Some(&0)
+    );
+}
+
+#[test]
+fn test_machine_retries_output_until_transferred() {
+    let mut test = machine("automancy:basic_extractor", "automancy:coal");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    for _ in 0..15 {
+        test.tick();
+    }
+
+    // the output is held until it is answered, without offering it again or starting a new batch
+    for _ in 0..20 {
+        assert!(test.tick().is_none());
+    }
+
+    test.transaction_result(stack("automancy:coal", 5));
+
+    let result = test.tick().expect("The machine should output the rest");
+    let outputs = result[4].clone().cast::<Inventory>();
+    assert_eq!(outputs[&id("automancy:coal")], 3);
+
+    test.transaction_result(stack("automancy:coal", 3));
+
+    assert!(test.tick().is_none());
+    assert_eq!(
+        test.data.get(&data_ids.progress).and_then(Data::as_amount),
+        Some(&1)
+    );
+}
+
+#[test]
+fn test_machine_offers_again_without_an_answer() {
+    let mut test = machine("automancy:basic_extractor", "automancy:coal");
+
+    for _ in 0..15 {
+        test.tick();
+    }
+
+    // e.g. the map was loaded while the output was offered
+    assert_eq!(ticks_until_offer(&mut test), 63);
+}
+
+/// Ticks the machine until it offers its output again, and gets how many ticks it waited.
+fn ticks_until_offer(test: &mut FunctionTest) -> usize {
+    for waited in 0..100 {
+        if test.tick().is_some() {
+            return waited;
+        }
+    }
+
+    panic!("The machine should offer its output again");
+}
+
+#[test]
+fn test_machine_backs_off_while_output_is_refused() {
+    let mut test = machine("automancy:basic_extractor", "automancy:coal");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    for _ in 0..15 {
+        test.tick();
+    }
+
+    // the wait doubles with each refusal, and the refusals of the same offer only count once
+    for expected in [1, 2, 4, 8, 16, 32, 32] {
+        test.transaction_failed(
+            stack("automancy:coal", 8),
+            TileCoord::RIGHT,
+            RefusalReason::Rejected,
+        );
+        test.transaction_failed(
+            stack("automancy:coal", 8),
+            TileCoord::RIGHT,
+            RefusalReason::Rejected,
+        );
+
+        assert_eq!(ticks_until_offer(&mut test), expected);
+    }
+
+    // the output is still held
+    let output_buffer = test
+        .data
+        .get(&data_ids.output_buffer)
+        .and_then(Data::as_inventory)
+        .unwrap();
+    assert_eq!(output_buffer[&id("automancy:coal")], 8);
+
+    // taking some of it resets the wait
+    test.transaction_result(stack("automancy:coal", 5));
+    test.transaction_failed(
+        stack("automancy:coal", 3),
+        TileCoord::RIGHT,
+        RefusalReason::Rejected,
+    );
+
+    assert_eq!(ticks_until_offer(&mut test), 1);
+}
+
+#[test]
+fn test_machine_waits_longest_without_a_target_tile() {
+    let mut test = machine("automancy:basic_extractor", "automancy:coal");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    for _ in 0..15 {
+        test.tick();
+    }
+
+    test.transaction_failed(
+        stack("automancy:coal", 8),
+        TileCoord::RIGHT,
+        RefusalReason::Missing,
+    );
+
+    assert_eq!(
+        test.data.get(&data_ids.retry_in).and_then(Data::as_amount),
+        Some(&32)
+    );
+    assert_eq!(ticks_until_offer(&mut test), 32);
+}
+
+#[test]
+fn test_machine_accepts_only_inputs() {
+    let mut test = machine("automancy:basic_still", "automancy:coal_to_fire");
+
+    let result = test
+        .transaction(stack("automancy:coal", 64), TileCoord::LEFT)
+        .expect("The machine should take coal");
+    assert_eq!(action(&result), "consume");
+    assert_eq!(result[1].as_int().unwrap(), 8);
+
+    assert!(test
+        .transaction(stack("automancy:coal", 64), TileCoord::LEFT)
+        .is_none());
+    assert!(test
+        .transaction(stack("automancy:sand", 1), TileCoord::LEFT)
+        .is_none());
+}
+
+#[test]
+fn test_machine_consumes_inputs() {
+    let mut test = machine("automancy:basic_still", "automancy:coal_to_fire");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    // no inputs, no progress
+    assert!(test.tick().is_none());
+    assert!(test.data.get(&data_ids.progress).is_none());
+
+    test.transaction(stack("automancy:coal", 1), TileCoord::LEFT);
+
+    for _ in 1..30 {
+        assert!(test.tick().is_none());
+    }
+
+    let result = test.tick().expect("The machine should output");
+    let outputs = find_action(&result, "make_transaction").unwrap()[4]
+        .clone()
+        .cast::<Inventory>();
+    assert_eq!(outputs[&id("automancy:fire")], 16);
+
+    let buffer = test
+        .data
+        .get(&data_ids.buffer)
+        .and_then(Data::as_inventory)
+        .unwrap();
+    assert_eq!(buffer[&id("automancy:coal")], 0);
+}
@@ -0,0 +1,6 @@
+pub mod machine;
+pub mod signal;
+pub mod slot_storage;
+pub mod sorter;
+pub mod splitter;
+pub mod storage;
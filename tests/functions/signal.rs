@@ -0,0 +1,42 @@
+use rhai::INT;
+
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::Data;
+
+use crate::support::{action, id, FunctionTest};
+
+#[test]
+fn test_sensor_senses_its_target() {
+    let mut test = FunctionTest::new("automancy:sensor");
+    let data_ids = test.resource_man.registry.data_ids;
+    let network = TileCoord::new(3, 0);
+
+    assert!(test.tick().is_none());
+
+    test.data
+        .insert(data_ids.target, Data::Coord(TileCoord::LEFT));
+    assert!(test.tick().is_none());
+
+    test.data.insert(data_ids.link, Data::Coord(network));
+    let result = test.tick().unwrap();
+    assert_eq!(action(&result), "sense");
+    assert_eq!(result[1].clone().cast::<TileCoord>(), TileCoord::LEFT);
+    assert_eq!(result[2].clone().cast::<TileCoord>(), network);
+}
+
+#[test]
+fn test_comparator_signals_on_its_own_network() {
+    let mut test = FunctionTest::new("automancy:comparator");
+    let data_ids = test.resource_man.registry.data_ids;
+    test.coord = TileCoord::new(2, -1);
+
+    assert!(test.tick().is_none());
+
+    test.data
+        .insert(data_ids.condition_signal, Data::Id(id("automancy:gold")));
+    let result = test.tick().unwrap();
+    assert_eq!(action(&result), "signal");
+    assert_eq!(result[1].clone().cast::<TileCoord>(), test.coord);
+    assert_eq!(result[2].as_int().unwrap(), INT::from(id("automancy:gold")));
+    assert_eq!(result[3].as_int().unwrap(), 1);
+}
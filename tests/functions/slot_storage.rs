@@ -0,0 +1,112 @@
+use automancy::tile_entity::RefusalReason;
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::stack::ItemStack;
+use automancy_resources::data::Data;
+
+use crate::support::{action, id, stack, FunctionTest};
+
+/// A storage with a slot for 10 coal, and one for 5 of any element.
+fn storage() -> FunctionTest {
+    let mut test = FunctionTest::new("automancy:slot_storage");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data.insert(
+        data_ids.slots,
+        Data::VecId(vec![id("automancy:coal"), id("automancy:#elements")]),
+    );
+    test.data
+        .insert(data_ids.slot_amounts, Data::VecAmount(vec![10, 5]));
+
+    test
+}
+
+fn stored(test: &FunctionTest, item: &str) -> i32 {
+    test.data[&test.resource_man.registry.data_ids.buffer]
+        .as_inventory()
+        .unwrap()
+        .clone()
+        .get(id(item))
+}
+
+#[test]
+fn test_slot_storage_fills_slots_by_filter() {
+    let mut test = storage();
+
+    let result = test
+        .transaction(stack("automancy:coal", 8), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(action(&result), "consume");
+    assert_eq!(result[1].as_int().unwrap(), 8);
+    assert_eq!(result[2].clone().into_string().unwrap(), "stored");
+
+    let result = test
+        .transaction(stack("automancy:coal", 8), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(result[1].as_int().unwrap(), 2);
+    assert!(test
+        .transaction(stack("automancy:coal", 1), TileCoord::LEFT)
+        .is_none());
+
+    let result = test
+        .transaction(stack("automancy:fire", 8), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(result[1].as_int().unwrap(), 5);
+
+    // the element slot is taken by fire
+    assert!(test
+        .transaction(stack("automancy:water", 1), TileCoord::LEFT)
+        .is_none());
+    assert!(test
+        .transaction(stack("automancy:sand", 1), TileCoord::LEFT)
+        .is_none());
+
+    assert_eq!(stored(&test, "automancy:coal"), 10);
+    assert_eq!(stored(&test, "automancy:fire"), 5);
+}
+
+#[test]
+fn test_slot_storage_without_slots_takes_nothing() {
+    let mut test = FunctionTest::new("automancy:slot_storage");
+
+    assert!(test
+        .transaction(stack("automancy:coal", 1), TileCoord::LEFT)
+        .is_none());
+    assert!(test.extract_request(TileCoord::RIGHT).is_none());
+}
+
+#[test]
+fn test_slot_storage_extracts_round_robin() {
+    let mut test = storage();
+
+    test.transaction(stack("automancy:coal", 3), TileCoord::LEFT);
+    test.transaction(stack("automancy:fire", 2), TileCoord::LEFT);
+
+    let mut extracted = vec![];
+    for _ in 0..2 {
+        let result = test.extract_request(TileCoord::RIGHT).unwrap();
+        assert_eq!(action(&result), "make_transaction");
+        assert_eq!(result[1].clone().cast::<TileCoord>(), TileCoord::RIGHT);
+
+        let stacks = result[4].clone().into_typed_array::<ItemStack>().unwrap();
+        extracted.push(stacks[0]);
+    }
+
+    assert_eq!(
+        extracted,
+        vec![stack("automancy:coal", 3), stack("automancy:fire", 2)]
+    );
+
+    // what was offered is taken out, until it is refused
+    assert!(test.extract_request(TileCoord::RIGHT).is_none());
+
+    test.transaction_failed(
+        stack("automancy:coal", 3),
+        TileCoord::RIGHT,
+        RefusalReason::Rejected,
+    );
+    let result = test.extract_request(TileCoord::RIGHT).unwrap();
+    assert_eq!(
+        result[4].clone().into_typed_array::<ItemStack>().unwrap()[0],
+        stack("automancy:coal", 3)
+    );
+}
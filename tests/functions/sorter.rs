@@ -0,0 +1,93 @@
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hexagon_tiles::traits::HexRotate;
+use automancy_resources::data::Data;
+
+use crate::support::{action, id, stack, FunctionTest};
+
+fn pass_on(test: &mut FunctionTest, item: &str) -> Option<TileCoord> {
+    test.transaction(stack(item, 1), TileCoord::LEFT)
+        .map(|result| {
+            assert_eq!(action(&result), "pass_on");
+
+            result[1].clone().cast::<TileCoord>()
+        })
+}
+
+fn sorter() -> FunctionTest {
+    let mut test = FunctionTest::new("automancy:sorter");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data.insert(
+        data_ids.sort_filters,
+        Data::VecId(vec![id("automancy:coal"), id("automancy:#elements")]),
+    );
+    test.data.insert(
+        data_ids.sort_outputs,
+        Data::VecCoord(vec![TileCoord::TOP_RIGHT, TileCoord::BOTTOM_RIGHT]),
+    );
+    test.data
+        .insert(data_ids.overflow, Data::Coord(TileCoord::RIGHT));
+
+    test
+}
+
+#[test]
+fn test_sorter_matches_items_and_tags() {
+    let mut test = sorter();
+
+    assert_eq!(
+        pass_on(&mut test, "automancy:coal"),
+        Some(TileCoord::TOP_RIGHT)
+    );
+    assert_eq!(
+        pass_on(&mut test, "automancy:fire"),
+        Some(TileCoord::BOTTOM_RIGHT)
+    );
+    assert_eq!(pass_on(&mut test, "automancy:sand"), Some(TileCoord::RIGHT));
+
+    // without an overflow, the rest is refused
+    test.data
+        .remove(&test.resource_man.registry.data_ids.overflow);
+    assert_eq!(pass_on(&mut test, "automancy:sand"), None);
+}
+
+#[test]
+fn test_sorter_overflows_refused_items() {
+    let mut test = sorter();
+
+    // what the output of an item refuses goes to the overflow
+    let result = test
+        .transaction(stack("automancy:coal", 1), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(action(&result), "pass_on");
+    assert_eq!(result[1].clone().cast::<TileCoord>(), TileCoord::TOP_RIGHT);
+    assert_eq!(result[2].clone().cast::<TileCoord>(), TileCoord::RIGHT);
+
+    // unless there is none
+    test.data
+        .remove(&test.resource_man.registry.data_ids.overflow);
+    let result = test
+        .transaction(stack("automancy:coal", 1), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(result.len(), 2);
+}
+
+#[test]
+fn test_sorter_keeps_old_item_and_target() {
+    let mut test = FunctionTest::new("automancy:sorter");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data
+        .insert(data_ids.item, Data::Id(id("automancy:coal")));
+    test.data
+        .insert(data_ids.target, Data::Coord(TileCoord::TOP_RIGHT));
+
+    assert_eq!(
+        pass_on(&mut test, "automancy:coal"),
+        Some(TileCoord::TOP_RIGHT)
+    );
+    assert_eq!(
+        pass_on(&mut test, "automancy:sand"),
+        Some(TileCoord::TOP_RIGHT.rotate_right())
+    );
+}
@@ -0,0 +1,181 @@
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::Data;
+
+use crate::support::{action, stack, FunctionTest};
+
+fn pass_on(test: &mut FunctionTest, from: TileCoord) -> Option<TileCoord> {
+    pass_on_with_fallback(test, from).map(|(output, _)| output)
+}
+
+/// Gets the output the splitter passes the items to, and where they go if it refuses them.
+fn pass_on_with_fallback(
+    test: &mut FunctionTest,
+    from: TileCoord,
+) -> Option<(TileCoord, Option<TileCoord>)> {
+    test.transaction(stack("automancy:coal", 1), from)
+        .map(|result| {
+            assert_eq!(action(&result), "pass_on");
+
+            (
+                result[1].clone().cast::<TileCoord>(),
+                result.get(2).map(|v| v.clone().cast::<TileCoord>()),
+            )
+        })
+}
+
+#[test]
+fn test_splitter_passes_to_other_sides() {
+    let mut test = FunctionTest::new("automancy:splitter");
+
+    test.random = 0;
+    assert_eq!(
+        pass_on(&mut test, TileCoord::TOP_LEFT),
+        Some(TileCoord::BOTTOM_LEFT)
+    );
+    test.random = 1;
+    assert_eq!(
+        pass_on(&mut test, TileCoord::TOP_LEFT),
+        Some(TileCoord::RIGHT)
+    );
+
+    test.random = 0;
+    assert_eq!(
+        pass_on(&mut test, TileCoord::RIGHT),
+        Some(TileCoord::TOP_LEFT)
+    );
+    test.random = 1;
+    assert_eq!(
+        pass_on(&mut test, TileCoord::RIGHT),
+        Some(TileCoord::BOTTOM_LEFT)
+    );
+}
+
+#[test]
+fn test_splitter_modifier_flips_sides() {
+    let mut test = FunctionTest::new("automancy:splitter");
+    test.tile_modifier = 1;
+
+    test.random = 0;
+    assert_eq!(
+        pass_on(&mut test, TileCoord::LEFT),
+        Some(TileCoord::TOP_RIGHT)
+    );
+
+    assert!(pass_on(&mut test, TileCoord::TOP_LEFT).is_none());
+}
+
+#[test]
+fn test_splitter_rejects_other_sides() {
+    let mut test = FunctionTest::new("automancy:splitter");
+
+    assert!(pass_on(&mut test, TileCoord::LEFT).is_none());
+    assert!(pass_on(&mut test, TileCoord::BOTTOM_RIGHT).is_none());
+}
+
+#[test]
+fn test_splitter_weights_random_split() {
+    let mut test = FunctionTest::new("automancy:splitter");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    // weighs the bottom left 3 to 1 against the right, by direction clockwise from the top right
+    test.data.insert(
+        data_ids.split_weights,
+        Data::VecAmount(vec![1, 1, 1, 3, 1, 1]),
+    );
+
+    for (random, expected) in [
+        (0, TileCoord::BOTTOM_LEFT),
+        (2, TileCoord::BOTTOM_LEFT),
+        (3, TileCoord::RIGHT),
+        (-1, TileCoord::RIGHT),
+    ] {
+        test.random = random;
+        assert_eq!(pass_on(&mut test, TileCoord::TOP_LEFT), Some(expected));
+    }
+
+    // an output weighed 0 gets nothing, not even what the other refuses
+    test.data.insert(
+        data_ids.split_weights,
+        Data::VecAmount(vec![1, 0, 1, 1, 1, 1]),
+    );
+    for random in 0..4 {
+        test.random = random;
+        assert_eq!(
+            pass_on_with_fallback(&mut test, TileCoord::TOP_LEFT),
+            Some((TileCoord::BOTTOM_LEFT, None))
+        );
+    }
+}
+
+#[test]
+fn test_splitter_falls_back_to_the_other_output() {
+    let mut test = FunctionTest::new("automancy:splitter");
+
+    test.random = 0;
+    assert_eq!(
+        pass_on_with_fallback(&mut test, TileCoord::TOP_LEFT),
+        Some((TileCoord::BOTTOM_LEFT, Some(TileCoord::RIGHT)))
+    );
+    test.random = 1;
+    assert_eq!(
+        pass_on_with_fallback(&mut test, TileCoord::TOP_LEFT),
+        Some((TileCoord::RIGHT, Some(TileCoord::BOTTOM_LEFT)))
+    );
+}
+
+#[test]
+fn test_splitter_round_robin_follows_weights() {
+    let mut test = FunctionTest::new("automancy:splitter");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data.insert(data_ids.round_robin, Data::Bool(true));
+    test.data.insert(
+        data_ids.split_weights,
+        Data::VecAmount(vec![1, 2, 1, 1, 1, 1]),
+    );
+
+    let outputs = (0..6)
+        .map(|random| {
+            // the random number is ignored
+            test.random = random;
+            pass_on(&mut test, TileCoord::TOP_LEFT).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        outputs,
+        [
+            TileCoord::BOTTOM_LEFT,
+            TileCoord::RIGHT,
+            TileCoord::RIGHT,
+            TileCoord::BOTTOM_LEFT,
+            TileCoord::RIGHT,
+            TileCoord::RIGHT,
+        ]
+    );
+}
+
+#[test]
+fn test_splitter_priority_falls_back_to_the_other_output() {
+    let mut test = FunctionTest::new("automancy:splitter");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data
+        .insert(data_ids.priority, Data::Coord(TileCoord::RIGHT));
+
+    // the priority output is always offered the items first, and what it refuses goes to the other
+    for random in 0..4 {
+        test.random = random;
+        assert_eq!(
+            pass_on_with_fallback(&mut test, TileCoord::TOP_LEFT),
+            Some((TileCoord::RIGHT, Some(TileCoord::BOTTOM_LEFT)))
+        );
+    }
+
+    // coming in from the priority side, the other two outputs are used as usual
+    test.random = 1;
+    assert_eq!(
+        pass_on(&mut test, TileCoord::RIGHT),
+        Some(TileCoord::BOTTOM_LEFT)
+    );
+}
@@ -0,0 +1,83 @@
+use automancy::tile_entity::RefusalReason;
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::stack::ItemStack;
+use automancy_resources::data::Data;
+
+use crate::support::{action, id, stack, FunctionTest};
+
+fn storage(amount: i32) -> FunctionTest {
+    let mut test = FunctionTest::new("automancy:small_storage");
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.data
+        .insert(data_ids.item, Data::Id(id("automancy:coal")));
+    test.data.insert(data_ids.amount, Data::Amount(amount));
+
+    test
+}
+
+#[test]
+fn test_storage_fills_up_to_amount() {
+    let mut test = storage(10);
+
+    let result = test
+        .transaction(stack("automancy:coal", 8), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(action(&result), "consume");
+    assert_eq!(result[1].as_int().unwrap(), 8);
+    assert_eq!(result[2].clone().into_string().unwrap(), "stored");
+
+    let result = test
+        .transaction(stack("automancy:coal", 8), TileCoord::LEFT)
+        .unwrap();
+    assert_eq!(result[1].as_int().unwrap(), 2);
+
+    assert!(test
+        .transaction(stack("automancy:coal", 8), TileCoord::LEFT)
+        .is_none());
+}
+
+#[test]
+fn test_storage_rejects_other_items() {
+    let mut test = storage(10);
+
+    assert!(test
+        .transaction(stack("automancy:sand", 8), TileCoord::LEFT)
+        .is_none());
+}
+
+#[test]
+fn test_storage_extract() {
+    let mut test = storage(10);
+
+    assert!(test.extract_request(TileCoord::RIGHT).is_none());
+
+    test.transaction(stack("automancy:coal", 6), TileCoord::LEFT);
+
+    let result = test.extract_request(TileCoord::RIGHT).unwrap();
+    assert_eq!(action(&result), "make_transaction");
+    assert_eq!(result[1].clone().cast::<TileCoord>(), TileCoord::RIGHT);
+
+    let stacks = result[4].clone().into_array().unwrap();
+    assert_eq!(
+        stacks[0].clone().cast::<ItemStack>(),
+        stack("automancy:coal", 6)
+    );
+
+    // the items are taken out when they are offered, and taken back when they are refused
+    let buffer = |test: &FunctionTest| {
+        test.data
+            .get(&test.resource_man.registry.data_ids.buffer)
+            .and_then(Data::as_inventory)
+            .unwrap()[&id("automancy:coal")]
+    };
+    assert_eq!(buffer(&test), 0);
+    assert!(test.extract_request(TileCoord::RIGHT).is_none());
+
+    test.transaction_failed(
+        stack("automancy:coal", 2),
+        TileCoord::RIGHT,
+        RefusalReason::Rejected,
+    );
+    assert_eq!(buffer(&test), 2);
+}
@@ -0,0 +1,332 @@
+use std::fs;
+
+use automancy::game::GameMsg;
+use automancy::map::Map;
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+const EXTRACTOR: TileCoord = TileCoord::ZERO;
+const MERGER: TileCoord = TileCoord::RIGHT;
+const DEPOSIT: TileCoord = TileCoord::LEFT;
+
+/// Builds a coal extractor next to a coal deposit, that outputs through a merger into a storage, which holds up to the given amount.
+async fn coal_chain(amount: i32) -> (GameTest, TileCoord) {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    let storage = MERGER + TileCoord::RIGHT;
+
+    test.place(DEPOSIT, "automancy:coal_deposit", 0).await;
+    test.place(EXTRACTOR, "automancy:basic_extractor", 0).await;
+    test.place(MERGER, "automancy:merger", 0).await;
+    test.place(storage, "automancy:small_storage", 0).await;
+
+    test.set_data(EXTRACTOR, data_ids.script, Data::Id(id("automancy:coal")));
+    test.set_data(EXTRACTOR, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(MERGER, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(storage, data_ids.item, Data::Id(id("automancy:coal")));
+    test.set_data(storage, data_ids.amount, Data::Amount(amount));
+
+    (test, storage)
+}
+
+async fn stored_coal(test: &GameTest, storage: TileCoord) -> i32 {
+    test.data(storage)
+        .await
+        .get(&test.resource_man.registry.data_ids.buffer)
+        .and_then(Data::as_inventory)
+        .cloned()
+        .map(|mut buffer| buffer.get(id("automancy:coal")))
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_extractor_fills_storage_through_merger() {
+    let (test, storage) = coal_chain(100).await;
+
+    test.tick(14).await;
+    assert_eq!(stored_coal(&test, storage).await, 0);
+
+    test.tick(1).await;
+    assert_eq!(stored_coal(&test, storage).await, 8);
+
+    test.tick(15).await;
+    assert_eq!(stored_coal(&test, storage).await, 16);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_full_storage_holds_back_extractor() {
+    let (test, storage) = coal_chain(10).await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.tick(60).await;
+    assert_eq!(stored_coal(&test, storage).await, 10);
+
+    let mut output_buffer = test
+        .data(EXTRACTOR)
+        .await
+        .get(&data_ids.output_buffer)
+        .and_then(Data::as_inventory)
+        .cloned()
+        .expect("The extractor should be holding its output");
+    assert_eq!(output_buffer.get(id("automancy:coal")), 6);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_full_storage_backs_extractor_off() {
+    let (test, _) = coal_chain(10).await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.tick(60).await;
+
+    // the storage refusing through the merger reaches the extractor, which waits longer each time
+    let delay = test
+        .data(EXTRACTOR)
+        .await
+        .get(&data_ids.retry_delay)
+        .and_then(Data::as_amount)
+        .cloned()
+        .unwrap_or(0);
+    assert!(delay >= 4, "the extractor waits {delay} ticks");
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_full_splitter_output_does_not_back_extractor_off() {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+    let coal = id("automancy:coal");
+
+    let splitter = EXTRACTOR + TileCoord::RIGHT;
+    let full = splitter + TileCoord::TOP_RIGHT;
+    let open = splitter + TileCoord::BOTTOM_RIGHT;
+
+    test.place(DEPOSIT, "automancy:coal_deposit", 0).await;
+    test.place(EXTRACTOR, "automancy:basic_extractor", 0).await;
+    test.place(splitter, "automancy:splitter", 1).await;
+    test.place(full, "automancy:small_storage", 0).await;
+    test.place(open, "automancy:small_storage", 0).await;
+
+    test.set_data(EXTRACTOR, data_ids.script, Data::Id(coal));
+    test.set_data(EXTRACTOR, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    for (storage, amount) in [(full, 0), (open, 100)] {
+        test.set_data(storage, data_ids.item, Data::Id(coal));
+        test.set_data(storage, data_ids.amount, Data::Amount(amount));
+    }
+
+    test.tick(60).await;
+
+    // what the full output refuses goes to the other, and the extractor never hears of it
+    assert_eq!(stored_coal(&test, full).await, 0);
+    assert_eq!(stored_coal(&test, open).await, 32);
+    assert!(matches!(
+        test.data(EXTRACTOR).await.get(&data_ids.retry_delay),
+        None | Some(Data::Amount(0))
+    ));
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_extractor_without_target_tile_waits() {
+    let (test, _) = coal_chain(100).await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.place(MERGER, "automancy:none", 0).await;
+
+    test.tick(30).await;
+    assert!(matches!(
+        test.data(EXTRACTOR).await.get(&data_ids.retry_delay),
+        Some(Data::Amount(32))
+    ));
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_loading_a_map_drops_what_was_pending() {
+    let (test, _) = coal_chain(100).await;
+
+    // the ticks aren't waited for, so the tiles still have messages queued when the map is replaced
+    for _ in 0..30 {
+        test.game.send_message(GameMsg::Tick).unwrap();
+    }
+    test.game
+        .send_message(GameMsg::LoadMap(
+            test.resource_man.clone(),
+            ".test_no_such_map".to_string(),
+        ))
+        .unwrap();
+
+    test.settle().await;
+    test.tick(5).await;
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_extractor_needs_adjacent_deposit() {
+    let (test, storage) = coal_chain(100).await;
+
+    test.place(DEPOSIT, "automancy:none", 0).await;
+
+    test.tick(30).await;
+    assert_eq!(stored_coal(&test, storage).await, 0);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_extractor_depletes_deposit() {
+    let (test, storage) = coal_chain(100).await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.set_data(DEPOSIT, data_ids.remaining, Data::Amount(16));
+
+    test.tick(15).await;
+    assert_eq!(stored_coal(&test, storage).await, 8);
+    assert!(matches!(
+        test.data(DEPOSIT).await.get(&data_ids.remaining),
+        Some(Data::Amount(8))
+    ));
+
+    test.tick(15).await;
+    let deposit = test
+        .game
+        .call(|reply| GameMsg::GetTile(DEPOSIT, reply), None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deposit, Some((id("automancy:exhausted_deposit"), 0)));
+
+    test.tick(60).await;
+    assert_eq!(stored_coal(&test, storage).await, 16);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_extractor_takes_no_more_than_deposit_has() {
+    let (test, storage) = coal_chain(100).await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    // less than one run produces
+    test.set_data(DEPOSIT, data_ids.remaining, Data::Amount(3));
+
+    test.tick(30).await;
+    assert_eq!(stored_coal(&test, storage).await, 3);
+    let deposit = test
+        .game
+        .call(|reply| GameMsg::GetTile(DEPOSIT, reply), None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deposit, Some((id("automancy:exhausted_deposit"), 0)));
+
+    test.tick(60).await;
+    assert_eq!(stored_coal(&test, storage).await, 3);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_adding_deposits_to_a_map_from_before_them() {
+    let name = ".test_before_deposits";
+    let _ = fs::remove_dir_all(Map::path(name));
+
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    // the map is named first, so that saving writes it where it is loaded from
+    test.game
+        .send_message(GameMsg::LoadMap(
+            test.resource_man.clone(),
+            name.to_string(),
+        ))
+        .unwrap();
+
+    // an extractor with nothing to extract from, as the maps saved before deposits existed have
+    let storage = MERGER + TileCoord::RIGHT;
+    test.place(EXTRACTOR, "automancy:basic_extractor", 0).await;
+    test.place(MERGER, "automancy:merger", 0).await;
+    test.place(storage, "automancy:small_storage", 0).await;
+    test.set_data(EXTRACTOR, data_ids.script, Data::Id(id("automancy:coal")));
+    test.set_data(EXTRACTOR, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(MERGER, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(storage, data_ids.item, Data::Id(id("automancy:coal")));
+    test.set_data(storage, data_ids.amount, Data::Amount(100));
+
+    test.game
+        .call(
+            |reply| GameMsg::SaveMap(test.resource_man.clone(), reply),
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    test.game
+        .send_message(GameMsg::LoadMap(
+            test.resource_man.clone(),
+            name.to_string(),
+        ))
+        .unwrap();
+    test.settle().await;
+
+    let _ = fs::remove_dir_all(Map::path(name));
+
+    // loading the map leaves it as it was saved
+    let tiles = test
+        .game
+        .call(GameMsg::GetTiles, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tiles.len(), 3);
+    assert!(test.map_data(data_ids.world_seed).await.is_none());
+    assert!(test
+        .game
+        .call(GameMsg::NeedsDeposits, None)
+        .await
+        .unwrap()
+        .unwrap());
+
+    test.game.send_message(GameMsg::MigrateDeposits).unwrap();
+    test.settle().await;
+
+    assert!(!test
+        .game
+        .call(GameMsg::NeedsDeposits, None)
+        .await
+        .unwrap()
+        .unwrap());
+
+    let mut deposits = 0;
+    for neighbor in EXTRACTOR.neighbors() {
+        let tile = test
+            .game
+            .call(|reply| GameMsg::GetTile(neighbor, reply), None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        if tile == Some((id("automancy:coal_deposit"), 0)) {
+            deposits += 1;
+        }
+    }
+    // world generation can place more around it
+    assert!(deposits >= 1);
+    assert!(test.map_data(data_ids.world_seed).await.is_some());
+
+    test.tick(30).await;
+    assert!(stored_coal(&test, storage).await > 0);
+
+    test.stop().await;
+}
@@ -0,0 +1,362 @@
+use automancy::game::GameMsg;
+use automancy::logistics::{Logistics, Request, REPORT_TIMEOUT, REQUEST_COOLDOWN};
+use automancy::statistics::StatWindow;
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::{Data, DataMap};
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+fn contents(item: &str, amount: i32) -> Inventory {
+    let mut inventory = Inventory::default();
+    inventory.insert(id(item), amount);
+
+    inventory
+}
+
+/// A master node at the origin with two chains of nodes going off it, one of them longer.
+fn network() -> Logistics {
+    let mut logistics = Logistics::default();
+
+    logistics.report_link(TileCoord::new(1, 0), TileCoord::ZERO);
+    logistics.report_link(TileCoord::new(2, 0), TileCoord::new(1, 0));
+    logistics.report_link(TileCoord::new(0, 1), TileCoord::ZERO);
+    logistics.report_link(TileCoord::new(0, 2), TileCoord::new(0, 1));
+    logistics.report_link(TileCoord::new(0, 3), TileCoord::new(0, 2));
+
+    logistics
+}
+
+#[test]
+fn test_path_follows_links_both_ways() {
+    let logistics = network();
+
+    assert_eq!(
+        logistics.path(TileCoord::new(2, 0), TileCoord::new(0, 2)),
+        Some(vec![
+            TileCoord::new(2, 0),
+            TileCoord::new(1, 0),
+            TileCoord::ZERO,
+            TileCoord::new(0, 1),
+            TileCoord::new(0, 2),
+        ])
+    );
+    assert_eq!(logistics.path(TileCoord::ZERO, TileCoord::new(5, 5)), None);
+}
+
+#[test]
+fn test_request_served_by_nearest_provider() {
+    let mut logistics = network();
+    let storage_far = TileCoord::new(-5, 0);
+    let storage_near = TileCoord::new(5, 0);
+
+    logistics.advertise(
+        TileCoord::new(0, 3),
+        storage_far,
+        contents("automancy:coal", 10),
+    );
+    logistics.advertise(TileCoord::ZERO, storage_near, contents("automancy:coal", 3));
+    logistics.post(Request {
+        requester: TileCoord::new(2, 0),
+        item: id("automancy:coal"),
+        amount: 5,
+    });
+
+    let deliveries = logistics.tick();
+    assert_eq!(deliveries.len(), 1);
+
+    // only as much as the storage has is sent
+    let delivery = &deliveries[0];
+    assert_eq!(delivery.storage, storage_near);
+    assert_eq!(delivery.amount, 3);
+    assert_eq!(delivery.path.len(), 3);
+}
+
+#[test]
+fn test_requests_wait_out_the_cooldown() {
+    let mut logistics = network();
+    let requester = TileCoord::new(2, 0);
+    let request = Request {
+        requester,
+        item: id("automancy:coal"),
+        amount: 1,
+    };
+
+    logistics.advertise(
+        TileCoord::ZERO,
+        TileCoord::new(5, 0),
+        contents("automancy:coal", 100),
+    );
+
+    let mut served = 0;
+    for _ in 0..REQUEST_COOLDOWN * 3 {
+        logistics.post(request);
+
+        for delivery in logistics.tick() {
+            logistics.arrived(delivery.requester, delivery.storage, delivery.item);
+            served += 1;
+        }
+
+        logistics.advertise(
+            TileCoord::ZERO,
+            TileCoord::new(5, 0),
+            contents("automancy:coal", 100),
+        );
+    }
+
+    assert_eq!(served, 3);
+}
+
+#[test]
+fn test_requests_take_off_what_is_in_flight() {
+    let mut logistics = network();
+    let requester = TileCoord::new(2, 0);
+    let storage = TileCoord::new(5, 0);
+    let coal = id("automancy:coal");
+    let request = Request {
+        requester,
+        item: coal,
+        amount: 5,
+    };
+
+    logistics.advertise(TileCoord::ZERO, storage, contents("automancy:coal", 100));
+
+    logistics.post(request);
+    assert_eq!(logistics.tick().len(), 1);
+
+    for _ in 0..REQUEST_COOLDOWN {
+        logistics.tick();
+    }
+
+    // the 5 that are on their way are enough
+    logistics.post(request);
+    assert!(logistics.tick().is_empty());
+
+    logistics.arrived(requester, storage, coal);
+    logistics.post(Request {
+        amount: 7,
+        ..request
+    });
+
+    let deliveries = logistics.tick();
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].amount, 7);
+}
+
+#[test]
+fn test_unreported_links_expire() {
+    let mut logistics = network();
+
+    for _ in 0..REPORT_TIMEOUT {
+        logistics.tick();
+    }
+    assert!(logistics
+        .path(TileCoord::ZERO, TileCoord::new(2, 0))
+        .is_some());
+
+    logistics.tick();
+    assert_eq!(logistics.path(TileCoord::ZERO, TileCoord::new(2, 0)), None);
+}
+
+#[test]
+fn test_networks_list_contents_and_requests() {
+    let mut logistics = network();
+    logistics.report_link(TileCoord::new(9, 9), TileCoord::new(9, 8));
+
+    logistics.advertise(
+        TileCoord::ZERO,
+        TileCoord::new(5, 0),
+        contents("automancy:coal", 4),
+    );
+    logistics.advertise(
+        TileCoord::new(0, 3),
+        TileCoord::new(-5, 0),
+        contents("automancy:coal", 6),
+    );
+    logistics.post(Request {
+        requester: TileCoord::new(2, 0),
+        item: id("automancy:sand"),
+        amount: 2,
+    });
+
+    let networks = logistics.networks();
+    assert_eq!(networks.len(), 2);
+
+    let network = networks
+        .iter()
+        .find(|network| network.tiles.contains(&TileCoord::ZERO))
+        .unwrap();
+    assert_eq!(network.tiles.len(), 6);
+    assert_eq!(network.contents.clone().get(id("automancy:coal")), 10);
+    assert_eq!(network.requests.len(), 1);
+
+    // removing the master node splits the network
+    logistics.remove(TileCoord::ZERO);
+    assert_eq!(logistics.networks().len(), 3);
+}
+
+/// A storage with 20 coal provided by a master node, and a node requesting 5 coal into a storage for the given amount.
+/// Gets the positions of the master node, and of the storages the coal is delivered from and to.
+async fn delivery(destination_amount: i32) -> (GameTest, TileCoord, TileCoord, TileCoord) {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+    let coal = id("automancy:coal");
+
+    let source = TileCoord::ZERO;
+    let master_node = source + TileCoord::RIGHT;
+    let node = TileCoord::new(0, 4);
+    let destination = node + TileCoord::RIGHT;
+
+    test.place(source, "automancy:small_storage", 0).await;
+    test.place(master_node, "automancy:master_node", 0).await;
+    test.place(node, "automancy:node", 0).await;
+    test.place(destination, "automancy:small_storage", 0).await;
+
+    test.set_data(source, data_ids.item, Data::Id(coal));
+    test.set_data(source, data_ids.amount, Data::Amount(100));
+    test.set_data(
+        source,
+        data_ids.buffer,
+        Data::Inventory(contents("automancy:coal", 20)),
+    );
+    test.set_data(master_node, data_ids.target, Data::Coord(TileCoord::LEFT));
+
+    test.set_data(node, data_ids.link, Data::Coord(master_node));
+    test.set_data(node, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(node, data_ids.item, Data::Id(coal));
+    test.set_data(node, data_ids.amount, Data::Amount(5));
+    test.set_data(destination, data_ids.item, Data::Id(coal));
+    test.set_data(
+        destination,
+        data_ids.amount,
+        Data::Amount(destination_amount),
+    );
+
+    (test, master_node, source, destination)
+}
+
+fn stored(test: &GameTest, data: DataMap) -> i32 {
+    data.get(&test.resource_man.registry.data_ids.buffer)
+        .and_then(Data::as_inventory)
+        .cloned()
+        .unwrap_or_default()
+        .get(id("automancy:coal"))
+}
+
+#[tokio::test]
+async fn test_delivery_between_storages_is_not_stored_again() {
+    let (test, _, source, destination) = delivery(100).await;
+
+    test.tick(40).await;
+
+    let delivered = stored(&test, test.data(destination).await);
+    assert!(delivered >= 5, "{delivered} coal was delivered");
+    assert_eq!(stored(&test, test.data(source).await) + delivered, 20);
+
+    let statistics = test
+        .game
+        .call(GameMsg::GetStatistics, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // what one storage took in, the other gave up
+    assert_eq!(
+        statistics
+            .totals(StatWindow::Minute)
+            .get(&id("automancy:coal"))
+            .map_or(0, |counts| counts.stored),
+        0
+    );
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_node_requests_from_storage_on_network() {
+    let (test, master_node, source, destination) = delivery(100).await;
+
+    test.tick(40).await;
+
+    let delivered = stored(&test, test.data(destination).await);
+    assert!(delivered >= 5, "{delivered} coal was delivered");
+    assert_eq!(stored(&test, test.data(source).await) + delivered, 20);
+
+    let networks = test
+        .game
+        .call(GameMsg::GetLogistics, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(networks.len(), 1);
+    assert_eq!(networks[0].tiles.len(), 2);
+    assert!(networks[0].tiles.contains(&master_node));
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_node_requests_only_what_the_target_is_short_of() {
+    let (test, _, source, destination) = delivery(100).await;
+
+    test.set_data(
+        destination,
+        test.resource_man.registry.data_ids.buffer,
+        Data::Inventory(contents("automancy:coal", 3)),
+    );
+
+    test.tick(60).await;
+
+    assert_eq!(stored(&test, test.data(destination).await), 5);
+    assert_eq!(stored(&test, test.data(source).await), 18);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_storage_takes_back_what_the_requester_refuses() {
+    let (test, _, source, destination) = delivery(3).await;
+
+    test.tick(40).await;
+
+    // only 3 of the 5 delivered fit, and the rest goes back
+    assert_eq!(stored(&test, test.data(destination).await), 3);
+    assert_eq!(stored(&test, test.data(source).await), 17);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_delivery_is_carried_through_the_nodes_between() {
+    let (test, master_node, source, destination) = delivery(100).await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    // the requesting node is linked through one that only carries items on
+    let node = destination + TileCoord::LEFT;
+    let relay = TileCoord::new(0, 2);
+    test.place(relay, "automancy:node", 0).await;
+    test.set_data(relay, data_ids.link, Data::Coord(master_node));
+    test.set_data(node, data_ids.link, Data::Coord(relay));
+
+    test.tick(40).await;
+
+    let delivered = stored(&test, test.data(destination).await);
+    assert!(delivered >= 5, "{delivered} coal was delivered");
+    assert_eq!(stored(&test, test.data(source).await) + delivered, 20);
+
+    let networks = test
+        .game
+        .call(GameMsg::GetLogistics, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(networks.len(), 1);
+    assert_eq!(networks[0].tiles.len(), 3);
+    assert!(networks[0]
+        .requests
+        .iter()
+        .all(|request| request.requester == node));
+
+    test.stop().await;
+}
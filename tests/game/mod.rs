@@ -0,0 +1,8 @@
+pub mod factory;
+pub mod logistics;
+pub mod profiler;
+pub mod replay;
+pub mod scenario;
+pub mod signal;
+pub mod statistics;
+pub mod worldgen;
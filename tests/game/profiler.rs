@@ -0,0 +1,127 @@
+use automancy::game::GameMsg;
+use automancy::profiler::{Handler, ProfileColumn};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::IdRaw;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+/// Builds a coal extractor next to a coal deposit, that outputs through a merger into a storage.
+async fn coal_chain() -> GameTest {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    let merger = TileCoord::RIGHT;
+    let storage = merger + TileCoord::RIGHT;
+
+    test.place(TileCoord::LEFT, "automancy:coal_deposit", 0)
+        .await;
+    test.place(TileCoord::ZERO, "automancy:basic_extractor", 0)
+        .await;
+    test.place(merger, "automancy:merger", 0).await;
+    test.place(storage, "automancy:small_storage", 0).await;
+
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.script,
+        Data::Id(id("automancy:coal")),
+    );
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+    test.set_data(merger, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(storage, data_ids.item, Data::Id(id("automancy:coal")));
+    test.set_data(storage, data_ids.amount, Data::Amount(100));
+
+    test
+}
+
+#[tokio::test]
+async fn test_profiler_measures_handlers_and_ticks() {
+    let test = coal_chain().await;
+    let profiler = test
+        .game
+        .call(GameMsg::GetProfiler, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    profiler.set_enabled(true);
+    test.tick(30).await;
+
+    let mut report = profiler.report(&test.resource_man);
+
+    let extractor_ticks = report
+        .handlers
+        .iter()
+        .find(|row| {
+            row.tile == IdRaw::parse("automancy:basic_extractor") && row.handler == Handler::Tick
+        })
+        .expect("The extractor's ticks should be measured");
+    assert_eq!(extractor_ticks.calls, 30);
+    assert!(extractor_ticks.function.is_some());
+    assert!(extractor_ticks.max_us <= extractor_ticks.total_us);
+
+    assert!(report.handlers.iter().any(|row| {
+        row.tile == IdRaw::parse("automancy:merger") && row.handler == Handler::Transaction
+    }));
+
+    // the last tick is still being measured
+    assert_eq!(report.ticks.len(), 29);
+    assert!(
+        report
+            .ticks
+            .iter()
+            .map(|tick| tick.transactions)
+            .sum::<u64>()
+            > 0
+    );
+    assert!(report.ticks.iter().all(|tick| tick.handler_calls > 0));
+
+    report.sort_handlers(ProfileColumn::Calls, false);
+    assert!(report
+        .handlers
+        .windows(2)
+        .all(|rows| rows[0].calls <= rows[1].calls));
+
+    let path = std::env::temp_dir().join("automancy_profile_test.json");
+    report.save(&path).unwrap();
+    let saved: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(
+        saved["handlers"].as_array().unwrap().len(),
+        report.handlers.len()
+    );
+    assert!(saved["handlers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|row| row["handler"] == "handle_tick"));
+
+    profiler.reset();
+    assert!(profiler.report(&test.resource_man).handlers.is_empty());
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_disabled_profiler_measures_nothing() {
+    let test = coal_chain().await;
+    let profiler = test
+        .game
+        .call(GameMsg::GetProfiler, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    test.tick(10).await;
+
+    let report = profiler.report(&test.resource_man);
+    assert!(report.handlers.is_empty());
+    assert!(report.ticks.is_empty());
+
+    test.stop().await;
+}
@@ -0,0 +1,299 @@
+use automancy::game::GameMsg;
+use automancy::replay::{self, Divergence, Replay, ReplayInput};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::IdRaw;
+use automancy_resources::data::{Data, DataRaw};
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+/// Places a tile as the player does, so that it is recorded and can be undone.
+fn place(test: &GameTest, coord: TileCoord, tile: &str) {
+    test.game
+        .send_message(GameMsg::PlaceTile {
+            coord,
+            id: id(tile),
+            tile_modifier: 0,
+            data: None,
+            record: true,
+            reply: None,
+        })
+        .unwrap();
+}
+
+async fn checksum(test: &GameTest) -> u64 {
+    test.game
+        .call(GameMsg::GetChecksum, None)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// Records building a coal chain next to a deposit, with some ticks in between, and returns the replay along with the checksum it ended with.
+async fn record() -> (Replay, u64) {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.place(TileCoord::LEFT, "automancy:coal_deposit", 0)
+        .await;
+    test.place(TileCoord::ZERO, "automancy:basic_extractor", 0)
+        .await;
+
+    test.game.send_message(GameMsg::StartRecording).unwrap();
+
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.script,
+        Data::Id(id("automancy:coal")),
+    );
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+    test.tick(10).await;
+
+    let storage = TileCoord::RIGHT;
+    place(&test, storage, "automancy:merger");
+    test.game.send_message(GameMsg::Undo).unwrap();
+    place(&test, storage, "automancy:small_storage");
+    test.set_data(storage, data_ids.item, Data::Id(id("automancy:coal")));
+    test.set_data(storage, data_ids.amount, Data::Amount(100));
+    test.game
+        .send_message(GameMsg::SetData(data_ids.world_seed, Data::Amount(7)))
+        .unwrap();
+    test.tick(40).await;
+
+    let checksum = checksum(&test).await;
+    let replay = test
+        .game
+        .call(GameMsg::StopRecording, None)
+        .await
+        .unwrap()
+        .unwrap()
+        .expect("The game should have been recording");
+
+    test.stop().await;
+
+    (replay, checksum)
+}
+
+#[tokio::test]
+async fn test_recording_captures_player_inputs() {
+    let (replay, _) = record().await;
+
+    assert_eq!(replay.ticks, 50);
+    assert_eq!(replay.start.tiles.len(), 2);
+    assert_eq!(
+        replay
+            .checksums
+            .iter()
+            .map(|(tick, _)| *tick)
+            .collect::<Vec<_>>(),
+        vec![0, 30]
+    );
+
+    let inputs = &replay.inputs;
+    assert_eq!(inputs.len(), 8);
+    assert!(inputs[..2].iter().all(|(tick, _)| *tick == 0));
+    assert!(inputs[2..].iter().all(|(tick, _)| *tick == 10));
+    assert_eq!(inputs[3].1, ReplayInput::Undo);
+    assert_eq!(
+        inputs[7].1,
+        ReplayInput::SetMapData {
+            key: IdRaw::parse("automancy:world_seed"),
+            value: Some(DataRaw::Amount(7)),
+        }
+    );
+
+    // survives being saved
+    let saved = ron::to_string(&replay).unwrap();
+    let loaded: Replay = ron::from_str(&saved).unwrap();
+    assert_eq!(loaded.inputs, replay.inputs);
+    assert_eq!(loaded.checksums, replay.checksums);
+}
+
+#[tokio::test]
+async fn test_playback_matches_recording() {
+    let (replay, expected) = record().await;
+
+    let test = GameTest::new().await;
+    let report = replay::play(test.resource_man.clone(), &test.game, &replay, None)
+        .await
+        .unwrap();
+
+    assert_eq!(report.applied, replay.inputs.len());
+    assert!(report.skipped.is_empty());
+    assert_eq!(report.checked, 2);
+    assert_eq!(report.divergences, vec![]);
+    assert_eq!(checksum(&test).await, expected);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_playback_reports_divergence() {
+    let (mut replay, _) = record().await;
+    replay.checksums[1].1 ^= 1;
+
+    let test = GameTest::new().await;
+    let report = replay::play(test.resource_man.clone(), &test.game, &replay, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.divergences,
+        vec![Divergence {
+            tick: 30,
+            expected: replay.checksums[1].1,
+            actual: replay.checksums[1].1 ^ 1,
+        }]
+    );
+
+    test.stop().await;
+}
+
+/// Builds an extractor feeding a splitter that picks between two storages at random.
+async fn random_split(test: &GameTest) {
+    let data_ids = test.resource_man.registry.data_ids;
+    let splitter = TileCoord::RIGHT;
+
+    test.place(TileCoord::LEFT, "automancy:coal_deposit", 0)
+        .await;
+    test.place(TileCoord::ZERO, "automancy:basic_extractor", 0)
+        .await;
+    test.place(splitter, "automancy:splitter", 1).await;
+
+    for output in [TileCoord::TOP_RIGHT, TileCoord::BOTTOM_RIGHT] {
+        test.place(splitter + output, "automancy:small_storage", 0)
+            .await;
+        test.set_data(
+            splitter + output,
+            data_ids.item,
+            Data::Id(id("automancy:coal")),
+        );
+        test.set_data(splitter + output, data_ids.amount, Data::Amount(1000));
+    }
+
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.script,
+        Data::Id(id("automancy:coal")),
+    );
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+    test.game
+        .send_message(GameMsg::SetData(data_ids.world_seed, Data::Amount(7)))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_same_map_plays_out_the_same() {
+    let mut checksums = vec![];
+
+    for _ in 0..2 {
+        let test = GameTest::new().await;
+
+        random_split(&test).await;
+        test.tick(150).await;
+
+        checksums.push(checksum(&test).await);
+        test.stop().await;
+    }
+
+    assert_eq!(checksums[0], checksums[1]);
+}
+
+#[tokio::test]
+async fn test_playback_matches_recording_started_mid_session() {
+    let test = GameTest::new().await;
+
+    random_split(&test).await;
+    // off the beat of the adjacency checks, which happen every 10 ticks
+    test.tick(7).await;
+
+    test.game.send_message(GameMsg::StartRecording).unwrap();
+    test.tick(150).await;
+
+    let expected = checksum(&test).await;
+    let replay = test
+        .game
+        .call(GameMsg::StopRecording, None)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    test.stop().await;
+
+    assert_eq!(replay.tick_count, 7);
+
+    let test = GameTest::new().await;
+    let report = replay::play(test.resource_man.clone(), &test.game, &replay, None)
+        .await
+        .unwrap();
+
+    assert!(report.checked >= 1);
+    assert_eq!(report.divergences, vec![]);
+    assert_eq!(checksum(&test).await, expected);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_playback_matches_recording_without_settling() {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    test.place(TileCoord::LEFT, "automancy:coal_deposit", 0)
+        .await;
+    test.place(TileCoord::ZERO, "automancy:basic_extractor", 0)
+        .await;
+    test.place(TileCoord::RIGHT, "automancy:small_storage", 0)
+        .await;
+    test.set_data(
+        TileCoord::RIGHT,
+        data_ids.item,
+        Data::Id(id("automancy:coal")),
+    );
+    test.set_data(TileCoord::RIGHT, data_ids.amount, Data::Amount(100));
+
+    test.game.send_message(GameMsg::StartRecording).unwrap();
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.script,
+        Data::Id(id("automancy:coal")),
+    );
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+
+    // the ticks come in while the tile entities are still busy with the ones before, as they do in the game
+    for _ in 0..70 {
+        test.game.send_message(GameMsg::Tick).unwrap();
+    }
+    test.settle().await;
+
+    let replay = test
+        .game
+        .call(GameMsg::StopRecording, None)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    test.stop().await;
+
+    let test = GameTest::new().await;
+    let report = replay::play(test.resource_man.clone(), &test.game, &replay, None)
+        .await
+        .unwrap();
+
+    assert!(report.checked >= 1);
+    assert_eq!(report.divergences, vec![]);
+
+    test.stop().await;
+}
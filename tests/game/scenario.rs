@@ -0,0 +1,110 @@
+use automancy::game::{GameMsg, PlaceTileResponse, TPS};
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+async fn great_work() -> GameTest {
+    let test = GameTest::new().await;
+
+    test.game
+        .send_message(GameMsg::StartScenario(id("automancy:the_great_work")))
+        .unwrap();
+
+    test
+}
+
+#[tokio::test]
+async fn test_scenario_sets_up_map() {
+    let test = great_work().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    let storage = test.data(TileCoord::ZERO).await;
+    assert_eq!(
+        storage.get(&data_ids.item).and_then(Data::as_id),
+        Some(&id("automancy:philosophers_stone"))
+    );
+
+    let mut inventory = test
+        .map_data(data_ids.player_inventory)
+        .await
+        .as_ref()
+        .and_then(Data::as_inventory)
+        .cloned()
+        .expect("The scenario should give the player an inventory");
+    assert_eq!(inventory.get(id("automancy:gold")), 16);
+
+    assert!(matches!(
+        test.map_data(data_ids.objective_progress).await,
+        Some(Data::VecAmount(progress)) if progress == vec![0, 0]
+    ));
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_scenario_completes() {
+    let test = great_work().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    for i in 1..=6 {
+        test.place(TileCoord::new(i, 0), "automancy:basic_still", 0)
+            .await;
+    }
+
+    test.tick(TPS as usize).await;
+    assert!(matches!(
+        test.map_data(data_ids.objective_progress).await,
+        Some(Data::VecAmount(progress)) if progress == vec![6, 0]
+    ));
+    assert!(matches!(
+        test.map_data(data_ids.scenario_completed).await,
+        Some(Data::Bool(false))
+    ));
+
+    let mut buffer = Inventory::default();
+    buffer.add(id("automancy:philosophers_stone"), 100);
+    test.set_data(TileCoord::ZERO, data_ids.buffer, Data::Inventory(buffer));
+
+    test.tick(TPS as usize).await;
+    assert!(matches!(
+        test.map_data(data_ids.scenario_completed).await,
+        Some(Data::Bool(true))
+    ));
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_scenario_only_allows_its_tiles() {
+    let test = great_work().await;
+
+    let place = |tile: &str| {
+        let id = id(tile);
+
+        test.game.call(
+            move |reply| GameMsg::PlaceTile {
+                coord: TileCoord::new(2, 0),
+                id,
+                tile_modifier: 0,
+                data: None,
+                record: true,
+                reply: Some(reply),
+            },
+            None,
+        )
+    };
+
+    assert!(matches!(
+        place("automancy:master_node").await.unwrap().unwrap(),
+        PlaceTileResponse::Ignored
+    ));
+    assert!(matches!(
+        place("automancy:basic_still").await.unwrap().unwrap(),
+        PlaceTileResponse::Placed
+    ));
+
+    test.stop().await;
+}
@@ -0,0 +1,205 @@
+use std::ops::Deref;
+
+use rhai::{Scope, INT};
+
+use automancy::game::GameMsg;
+use automancy::signal::{self, Comparison, Condition, Signals};
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::{Data, DataMap};
+
+use crate::support::game::GameTest;
+use crate::support::{id, resource_man};
+
+#[test]
+fn test_comparisons_survive_being_saved() {
+    for comparison in Comparison::ALL {
+        assert_eq!(
+            Comparison::from_amount(comparison.to_amount()),
+            Some(comparison)
+        );
+    }
+
+    assert_eq!(Comparison::from_amount(-1), None);
+    assert_eq!(Comparison::from_amount(6), None);
+}
+
+#[test]
+fn test_condition_compares_signal() {
+    let resource_man = resource_man();
+    let data_ids = resource_man.registry.data_ids;
+    let gold = id("automancy:gold");
+
+    let mut data = DataMap::default();
+    assert_eq!(Condition::from_data(&data_ids, &data), None);
+
+    data.insert(data_ids.condition_signal, Data::Id(gold));
+    data.insert(
+        data_ids.condition_comparison,
+        Data::Amount(Comparison::Less.to_amount()),
+    );
+    data.insert(data_ids.condition_value, Data::Amount(1000));
+    let condition = Condition::from_data(&data_ids, &data).unwrap();
+
+    let mut signals = Inventory::default();
+    signals.insert(gold, 999);
+    assert!(condition.is_met(Some(&signals)));
+    signals.insert(gold, 1000);
+    assert!(!condition.is_met(Some(&signals)));
+
+    // nothing published means 0
+    assert!(condition.is_met(None));
+}
+
+#[test]
+fn test_published_signals_add_up() {
+    let coal = id("automancy:coal");
+    let network = TileCoord::new(3, 0);
+
+    let mut published = Inventory::default();
+    published.insert(coal, 5);
+
+    let mut signals = Signals::default();
+    signal::publish(&mut signals, network, &published);
+    signal::publish(&mut signals, network, &published);
+
+    assert_eq!(signals[&network].deref().get(&coal), Some(&10));
+}
+
+#[test]
+fn test_rhai_reads_signals_by_id() {
+    let resource_man = resource_man();
+    let coal = id("automancy:coal");
+
+    let mut signals = Inventory::default();
+    signals.insert(coal, 7);
+
+    let mut scope = Scope::new();
+    scope.push("signals", signals);
+    scope.push("coal", INT::from(coal));
+
+    let amount = resource_man
+        .engine
+        .eval_with_scope::<INT>(&mut scope, "signals[coal]")
+        .unwrap();
+    assert_eq!(amount, 7);
+}
+
+#[tokio::test]
+async fn test_extractor_stops_once_storage_is_full_enough() {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+    let coal = id("automancy:coal");
+
+    let merger = TileCoord::RIGHT;
+    let storage = merger + TileCoord::RIGHT;
+    let sensor = storage + TileCoord::TOP_RIGHT;
+    let master_node = TileCoord::new(0, 3);
+
+    test.place(TileCoord::LEFT, "automancy:coal_deposit", 0)
+        .await;
+    test.place(TileCoord::ZERO, "automancy:basic_extractor", 0)
+        .await;
+    test.place(merger, "automancy:merger", 0).await;
+    test.place(storage, "automancy:small_storage", 0).await;
+    test.place(sensor, "automancy:sensor", 0).await;
+    test.place(master_node, "automancy:master_node", 0).await;
+
+    test.set_data(TileCoord::ZERO, data_ids.script, Data::Id(coal));
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+    test.set_data(merger, data_ids.target, Data::Coord(TileCoord::RIGHT));
+    test.set_data(storage, data_ids.item, Data::Id(coal));
+    test.set_data(storage, data_ids.amount, Data::Amount(100));
+    test.set_data(sensor, data_ids.target, Data::Coord(TileCoord::BOTTOM_LEFT));
+    test.set_data(sensor, data_ids.link, Data::Coord(master_node));
+
+    // only run while the storage has under 4 coal
+    test.set_data(TileCoord::ZERO, data_ids.link, Data::Coord(master_node));
+    test.set_data(TileCoord::ZERO, data_ids.condition_signal, Data::Id(coal));
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.condition_comparison,
+        Data::Amount(Comparison::Less.to_amount()),
+    );
+    test.set_data(TileCoord::ZERO, data_ids.condition_value, Data::Amount(4));
+
+    test.tick(60).await;
+
+    let stored = test.data(storage).await[&data_ids.buffer]
+        .as_inventory()
+        .unwrap()
+        .clone()
+        .get(coal);
+    // what was already on its way when the signal changed still arrives
+    assert!((4..8).contains(&stored), "{stored} coal was stored");
+
+    let signals = test
+        .game
+        .call(GameMsg::GetSignals, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(signals[&master_node].deref()[&coal] >= 4);
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_late_signals_keep_the_last_value() {
+    let test = GameTest::new().await;
+    let coal = id("automancy:coal");
+    let network = TileCoord::new(3, 0);
+    let publisher = TileCoord::new(4, 0);
+
+    let publish = |amount, tick| {
+        let mut published = Inventory::default();
+        published.insert(coal, amount);
+
+        test.game
+            .send_message(GameMsg::PublishSignals(network, publisher, published, tick))
+            .unwrap();
+    };
+    let read = || async {
+        test.game
+            .call(GameMsg::GetSignals, None)
+            .await
+            .unwrap()
+            .unwrap()
+            .get(&network)
+            .map(|signals| signals.deref().get(&coal).cloned().unwrap_or(0))
+    };
+
+    test.tick(2).await;
+
+    let tick_count = test
+        .game
+        .call(GameMsg::GetTickCount, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // published during the tick that just ran
+    publish(5, tick_count - 1);
+    test.tick(1).await;
+    assert_eq!(read().await, Some(5));
+
+    // nothing arrived in time for this tick, so the last value stays
+    test.tick(1).await;
+    assert_eq!(read().await, Some(5));
+
+    // older than the last value, which it doesn't replace, unlike a newer one
+    publish(9, tick_count - 2);
+    publish(7, tick_count + 1);
+    test.tick(1).await;
+    assert_eq!(read().await, Some(7));
+
+    // the tile stopped publishing
+    test.tick(signal::SIGNAL_HOLD_TICKS as usize).await;
+    assert_eq!(read().await, None);
+
+    test.stop().await;
+}
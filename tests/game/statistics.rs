@@ -0,0 +1,191 @@
+use automancy::game::GameMsg;
+use automancy::statistics::{ItemCounts, ItemStat, StatWindow, Statistics, StatisticsRaw};
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::{id, resource_man, stack};
+
+#[tokio::test]
+async fn test_chain_counts_production_and_storage() {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    let storage = TileCoord::RIGHT + TileCoord::RIGHT;
+
+    test.place(TileCoord::LEFT, "automancy:coal_deposit", 0)
+        .await;
+    test.place(TileCoord::ZERO, "automancy:basic_extractor", 0)
+        .await;
+    test.place(TileCoord::RIGHT, "automancy:merger", 0).await;
+    test.place(storage, "automancy:small_storage", 0).await;
+
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.script,
+        Data::Id(id("automancy:coal")),
+    );
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+    test.set_data(
+        TileCoord::RIGHT,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+    test.set_data(storage, data_ids.item, Data::Id(id("automancy:coal")));
+    test.set_data(storage, data_ids.amount, Data::Amount(100));
+
+    test.tick(30).await;
+
+    let statistics = test
+        .game
+        .call(GameMsg::GetStatistics, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // passing through the merger is neither
+    assert_eq!(
+        statistics.totals(StatWindow::Minute)[&id("automancy:coal")],
+        ItemCounts {
+            produced: 16,
+            consumed: 0,
+            stored: 16,
+        }
+    );
+
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_machine_counts_inputs_once_used() {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+    let coal = id("automancy:coal");
+
+    test.place(TileCoord::ZERO, "automancy:basic_still", 0)
+        .await;
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.script,
+        Data::Id(id("automancy:coal_to_fire")),
+    );
+    test.set_data(
+        TileCoord::ZERO,
+        data_ids.target,
+        Data::Coord(TileCoord::RIGHT),
+    );
+
+    let mut buffer = Inventory::default();
+    buffer.insert(coal, 8);
+    test.set_data(TileCoord::ZERO, data_ids.buffer, Data::Inventory(buffer));
+
+    let consumed = || async {
+        let statistics = test
+            .game
+            .call(GameMsg::GetStatistics, None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        statistics
+            .totals(StatWindow::Minute)
+            .get(&coal)
+            .map_or(0, |counts| counts.consumed)
+    };
+
+    // holding the coal in its buffer isn't using it
+    test.tick(10).await;
+    assert_eq!(consumed().await, 0);
+
+    // the output is never taken, so the recipe runs only once
+    test.tick(40).await;
+    assert_eq!(consumed().await, 1);
+
+    test.stop().await;
+}
+
+#[test]
+fn test_windows_roll_over() {
+    let coal = id("automancy:coal");
+    let mut statistics = Statistics::default();
+
+    statistics.record(ItemStat::Produced, stack("automancy:coal", 5));
+    for _ in 0..StatWindow::Minute.bucket_ticks() {
+        statistics.tick();
+    }
+    statistics.record(ItemStat::Consumed, stack("automancy:coal", 3));
+
+    let history = statistics.history(StatWindow::Minute, coal);
+    assert_eq!(history.len(), 60);
+    assert_eq!(history[58].produced, 5);
+    assert_eq!(history[59].consumed, 3);
+    assert_eq!(
+        statistics.totals(StatWindow::Minute)[&coal],
+        ItemCounts {
+            produced: 5,
+            consumed: 3,
+            stored: 0,
+        }
+    );
+
+    // a minute later, only the longer windows still hold it
+    for _ in 0..StatWindow::Minute.ticks() {
+        statistics.tick();
+    }
+
+    assert!(statistics.totals(StatWindow::Minute).is_empty());
+    assert_eq!(statistics.totals(StatWindow::TenMinutes)[&coal].produced, 5);
+    assert_eq!(statistics.totals(StatWindow::Hour)[&coal].consumed, 3);
+}
+
+#[test]
+fn test_statistics_survive_being_saved() {
+    let resource_man = resource_man();
+    let mut statistics = Statistics::default();
+
+    statistics.record(ItemStat::Stored, stack("automancy:coal", 7));
+    for _ in 0..100 {
+        statistics.tick();
+    }
+    statistics.record(ItemStat::Produced, stack("automancy:sand", 2));
+
+    let saved = ron::to_string(&statistics.to_raw(&resource_man.interner)).unwrap();
+    let loaded = ron::from_str::<StatisticsRaw>(&saved)
+        .unwrap()
+        .to_statistics(&resource_man.interner);
+
+    for window in StatWindow::ALL {
+        assert_eq!(loaded.totals(window), statistics.totals(window));
+        for item in ["automancy:coal", "automancy:sand"] {
+            assert_eq!(
+                loaded.history(window, id(item)),
+                statistics.history(window, id(item))
+            );
+        }
+    }
+}
+
+#[test]
+fn test_stored_counts_what_is_taken_out() {
+    let coal = id("automancy:coal");
+    let mut statistics = Statistics::default();
+
+    statistics.record(ItemStat::Stored, stack("automancy:coal", 7));
+    statistics.record(ItemStat::Stored, stack("automancy:coal", -5));
+    // only storages take away from their count
+    statistics.record(ItemStat::Produced, stack("automancy:coal", -5));
+
+    assert_eq!(
+        statistics.totals(StatWindow::Minute)[&coal],
+        ItemCounts {
+            produced: 0,
+            consumed: 0,
+            stored: 2,
+        }
+    );
+}
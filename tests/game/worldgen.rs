@@ -0,0 +1,109 @@
+use automancy::game::GameMsg;
+use automancy::worldgen::{generate, WorldGenSettings};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::HashSet;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::{id, resource_man};
+
+const SETTINGS: WorldGenSettings = WorldGenSettings {
+    seed: 1234,
+    radius: 16,
+    density: 10,
+};
+
+#[test]
+fn test_same_seed_generates_same_world() {
+    let resource_man = resource_man();
+
+    let a = generate(&resource_man, SETTINGS, &HashSet::new());
+    let b = generate(&resource_man, SETTINGS, &HashSet::new());
+    assert!(!a.is_empty());
+    assert_eq!(a, b);
+
+    let c = generate(
+        &resource_man,
+        WorldGenSettings {
+            seed: 4321,
+            ..SETTINGS
+        },
+        &HashSet::new(),
+    );
+    assert_ne!(a, c);
+}
+
+#[test]
+fn test_generation_avoids_occupied_tiles() {
+    let resource_man = resource_man();
+    let deposits = [id("automancy:coal_deposit"), id("automancy:gravel_deposit")];
+
+    let occupied = [TileCoord::ZERO, TileCoord::RIGHT, TileCoord::LEFT]
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let tiles = generate(&resource_man, SETTINGS, &occupied);
+
+    let mut seen = HashSet::new();
+    for (coord, tile) in tiles {
+        assert!(
+            !occupied.contains(&coord),
+            "A deposit was placed at {coord}"
+        );
+        assert!(seen.insert(coord), "Two deposits were placed at {coord}");
+        assert!(deposits.contains(&tile));
+    }
+}
+
+#[test]
+fn test_zero_density_generates_nothing() {
+    let resource_man = resource_man();
+
+    let tiles = generate(
+        &resource_man,
+        WorldGenSettings {
+            density: 0,
+            ..SETTINGS
+        },
+        &HashSet::new(),
+    );
+    assert!(tiles.is_empty());
+}
+
+#[tokio::test]
+async fn test_generate_world_only_once() {
+    let test = GameTest::new().await;
+    let data_ids = test.resource_man.registry.data_ids;
+
+    let expected = generate(&test.resource_man, SETTINGS, &HashSet::new());
+
+    test.game
+        .send_message(GameMsg::GenerateWorld(SETTINGS))
+        .unwrap();
+    assert!(matches!(
+        test.map_data(data_ids.world_seed).await,
+        Some(Data::Amount(1234))
+    ));
+
+    for (coord, tile) in &expected {
+        let placed = test
+            .game
+            .call(|reply| GameMsg::GetTile(*coord, reply), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(placed, Some((*tile, 0)));
+    }
+
+    test.game
+        .send_message(GameMsg::GenerateWorld(WorldGenSettings {
+            seed: 4321,
+            ..SETTINGS
+        }))
+        .unwrap();
+    assert!(matches!(
+        test.map_data(data_ids.world_seed).await,
+        Some(Data::Amount(1234))
+    ));
+
+    test.stop().await;
+}
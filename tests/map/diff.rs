@@ -0,0 +1,264 @@
+use std::fs;
+
+use serde_json::json;
+
+use automancy::game::GameMsg;
+use automancy::map::diff::{diff, DataChange, KeyChange, SavedMap};
+use automancy::map::{Map, MapHeader};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::hashbrown::HashMap;
+use automancy_defs::id::IdRaw;
+use automancy_resources::data::{DataMapRaw, DataRaw};
+
+use crate::support::game::GameTest;
+
+fn data(values: &[(&str, DataRaw)]) -> DataMapRaw {
+    values
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect::<HashMap<_, _>>()
+        .into()
+}
+
+fn inventory(items: &[(&str, i32)]) -> DataRaw {
+    DataRaw::Inventory(
+        items
+            .iter()
+            .map(|(id, amount)| (IdRaw::parse(id), *amount))
+            .collect::<Vec<_>>()
+            .into(),
+    )
+}
+
+fn map(tiles: &[(TileCoord, &str, i32, DataMapRaw)]) -> SavedMap {
+    SavedMap {
+        tiles: tiles
+            .iter()
+            .map(|(coord, id, tile_modifier, data)| {
+                (*coord, (IdRaw::parse(id), *tile_modifier, data.clone()))
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_same_maps_have_no_differences() {
+    let a = map(&[(
+        TileCoord::ZERO,
+        "automancy:merger",
+        0,
+        data(&[("automancy:target", DataRaw::Coord(TileCoord::RIGHT))]),
+    )]);
+
+    let diff = diff(&a, &a.clone());
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "The maps are the same.\n");
+}
+
+#[test]
+fn test_tile_changes() {
+    let old = map(&[
+        (TileCoord::ZERO, "automancy:merger", 0, data(&[])),
+        (TileCoord::RIGHT, "automancy:merger", 0, data(&[])),
+        (TileCoord::LEFT, "automancy:small_storage", 0, data(&[])),
+    ]);
+    let new = map(&[
+        (TileCoord::ZERO, "automancy:merger", 0, data(&[])),
+        (TileCoord::RIGHT, "automancy:merger", 1, data(&[])),
+        (
+            TileCoord::TOP_RIGHT,
+            "automancy:small_storage",
+            0,
+            data(&[]),
+        ),
+    ]);
+
+    let diff = diff(&old, &new);
+
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].coord, TileCoord::TOP_RIGHT);
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].coord, TileCoord::LEFT);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].coord, TileCoord::RIGHT);
+    assert_eq!(diff.changed[0].old_tile_modifier, 0);
+    assert_eq!(diff.changed[0].new_tile_modifier, 1);
+    assert!(diff.tile_data.is_empty());
+}
+
+#[test]
+fn test_data_changes() {
+    let old = map(&[(
+        TileCoord::ZERO,
+        "automancy:small_storage",
+        0,
+        data(&[
+            (
+                "automancy:buffer",
+                inventory(&[("automancy:coal", 10), ("automancy:gravel", 2)]),
+            ),
+            ("automancy:target", DataRaw::Coord(TileCoord::RIGHT)),
+            ("automancy:amount", DataRaw::Amount(100)),
+        ]),
+    )]);
+    let new = map(&[(
+        TileCoord::ZERO,
+        "automancy:small_storage",
+        0,
+        data(&[
+            (
+                "automancy:buffer",
+                inventory(&[("automancy:coal", 6), ("automancy:iron", 1)]),
+            ),
+            ("automancy:target", DataRaw::Coord(TileCoord::LEFT)),
+            ("automancy:link", DataRaw::Coord(TileCoord::TOP_RIGHT)),
+        ]),
+    )]);
+
+    let diff = diff(&old, &new);
+
+    assert_eq!(diff.tile_data.len(), 1);
+    assert_eq!(
+        diff.tile_data[0].changes,
+        vec![
+            KeyChange {
+                key: "automancy:amount".to_string(),
+                change: DataChange::Removed {
+                    value: DataRaw::Amount(100),
+                },
+            },
+            KeyChange {
+                key: "automancy:buffer".to_string(),
+                change: DataChange::Inventory {
+                    deltas: vec![
+                        (IdRaw::parse("automancy:coal"), -4),
+                        (IdRaw::parse("automancy:gravel"), -2),
+                        (IdRaw::parse("automancy:iron"), 1),
+                    ],
+                },
+            },
+            KeyChange {
+                key: "automancy:link".to_string(),
+                change: DataChange::Added {
+                    value: DataRaw::Coord(TileCoord::TOP_RIGHT),
+                },
+            },
+            KeyChange {
+                key: "automancy:target".to_string(),
+                change: DataChange::Changed {
+                    old: DataRaw::Coord(TileCoord::RIGHT),
+                    new: DataRaw::Coord(TileCoord::LEFT),
+                },
+            },
+        ]
+    );
+
+    let text = diff.to_string();
+    assert!(text
+        .contains("~ automancy:buffer: automancy:coal -4, automancy:gravel -2, automancy:iron +1"));
+    assert!(text.contains("~ automancy:target: [1, 0] -> [-1, 0]"));
+
+    let json = serde_json::to_value(&diff).unwrap();
+    assert_eq!(
+        json["tile_data"][0]["changes"][3],
+        json!({
+            "key": "automancy:target",
+            "change": "changed",
+            "old": {"Coord": [1, 0]},
+            "new": {"Coord": [-1, 0]},
+        })
+    );
+}
+
+#[test]
+fn test_set_order_is_not_a_change() {
+    let set = |ids: &[&str]| DataRaw::SetId(ids.iter().map(|id| IdRaw::parse(id)).collect());
+
+    let old = map(&[(
+        TileCoord::ZERO,
+        "automancy:sorter",
+        0,
+        data(&[(
+            "automancy:items",
+            set(&["automancy:coal", "automancy:gravel"]),
+        )]),
+    )]);
+    let new = map(&[(
+        TileCoord::ZERO,
+        "automancy:sorter",
+        0,
+        data(&[(
+            "automancy:items",
+            set(&["automancy:gravel", "automancy:coal"]),
+        )]),
+    )]);
+
+    assert!(diff(&old, &new).is_empty());
+
+    let new = map(&[(
+        TileCoord::ZERO,
+        "automancy:sorter",
+        0,
+        data(&[(
+            "automancy:items",
+            set(&["automancy:gravel", "automancy:iron"]),
+        )]),
+    )]);
+
+    let diff = diff(&old, &new);
+    assert_eq!(diff.tile_data.len(), 1);
+    assert_eq!(
+        diff.tile_data[0].changes[0].change,
+        DataChange::Changed {
+            old: set(&["automancy:coal", "automancy:gravel"]),
+            new: set(&["automancy:gravel", "automancy:iron"]),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_tiles_missing_from_header_are_reported() {
+    let name = ".test_diff_unknown";
+    let _ = fs::remove_dir_all(Map::path(name));
+
+    let test = GameTest::new().await;
+
+    // the map is named first, so that saving writes it where it is read from
+    test.game
+        .send_message(GameMsg::LoadMap(
+            test.resource_man.clone(),
+            name.to_string(),
+        ))
+        .unwrap();
+
+    test.place(TileCoord::ZERO, "automancy:merger", 0).await;
+    test.place(TileCoord::RIGHT, "automancy:small_storage", 0)
+        .await;
+    test.game
+        .call(
+            |reply| GameMsg::SaveMap(test.resource_man.clone(), reply),
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    // the merger's id is taken out of the header, as if the map was damaged
+    let path = Map::header(name);
+    let mut header: MapHeader = ron::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    header
+        .tile_map
+        .retain(|(_, id)| id.as_str() != "automancy:merger");
+    fs::write(&path, ron::to_string(&header).unwrap()).unwrap();
+
+    let saved = SavedMap::read(&test.resource_man, name);
+    let _ = fs::remove_dir_all(Map::path(name));
+
+    let saved = saved.unwrap();
+    assert_eq!(saved.unknown, vec![TileCoord::ZERO]);
+    assert_eq!(saved.tiles.len(), 1);
+    assert!(saved.tiles.contains_key(&TileCoord::RIGHT));
+
+    test.stop().await;
+}
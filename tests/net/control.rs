@@ -0,0 +1,236 @@
+use std::env;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+
+use automancy::game::{GameMsg, TickUnit};
+use automancy::net::control::{ControlResponse, ControlServer, MAX_STEP_TICKS};
+use automancy_defs::coord::TileCoord;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+/// A connection to the control server, the way a script would talk to it.
+struct Connection {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl Connection {
+    async fn request(&mut self, request: Value) -> ControlResponse {
+        self.send_line(&request.to_string()).await
+    }
+
+    async fn send_line(&mut self, line: &str) -> ControlResponse {
+        self.writer
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .unwrap();
+
+        let response = self.lines.next_line().await.unwrap().unwrap();
+
+        serde_json::from_str(&response).unwrap()
+    }
+
+    async fn ok(&mut self, request: Value) -> Value {
+        match self.request(request).await {
+            ControlResponse::Ok(value) => value,
+            ControlResponse::Error(err) => panic!("The request failed: {err}"),
+        }
+    }
+}
+
+/// Starts a control server for an empty map, on a socket only the test uses.
+async fn control(name: &str) -> (GameTest, ControlServer, Connection) {
+    let test = GameTest::new().await;
+
+    let path = env::temp_dir().join(format!(
+        "automancy-control-{}-{name}.sock",
+        std::process::id()
+    ));
+    let server = ControlServer::start(path, test.resource_man.clone(), test.game.clone()).unwrap();
+
+    let (reader, writer) = UnixStream::connect(server.path())
+        .await
+        .unwrap()
+        .into_split();
+    let connection = Connection {
+        lines: BufReader::new(reader).lines(),
+        writer,
+    };
+
+    (test, server, connection)
+}
+
+async fn tick_count(test: &GameTest) -> TickUnit {
+    test.game
+        .call(GameMsg::GetTickCount, None)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_place_and_remove_tiles() {
+    let (test, server, mut connection) = control("place").await;
+
+    let placed = connection
+        .ok(json!({"request": "place_tile", "coord": [1, 0], "id": "automancy:merger"}))
+        .await;
+    assert_eq!(placed, json!("placed"));
+
+    let tile = connection
+        .ok(json!({"request": "get_tile", "coord": [1, 0]}))
+        .await;
+    assert_eq!(
+        tile,
+        json!({"coord": [1, 0], "id": "automancy:merger", "tile_modifier": 0})
+    );
+
+    connection
+        .ok(json!({"request": "place_tile", "coord": [5, 5], "id": "automancy:merger"}))
+        .await;
+    let tiles = connection
+        .ok(json!({"request": "list_tiles", "start": [0, 0], "end": [2, 2]}))
+        .await;
+    assert_eq!(
+        tiles,
+        json!([{"coord": [1, 0], "id": "automancy:merger", "tile_modifier": 0}])
+    );
+
+    let removed = connection
+        .ok(json!({"request": "remove_tile", "coord": [1, 0]}))
+        .await;
+    assert_eq!(removed, json!("removed"));
+    assert_eq!(
+        connection
+            .ok(json!({"request": "get_tile", "coord": [1, 0]}))
+            .await,
+        Value::Null
+    );
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_tile_data() {
+    let (test, server, mut connection) = control("data").await;
+
+    test.place(TileCoord::ZERO, "automancy:small_storage", 0)
+        .await;
+
+    connection
+        .ok(json!({
+            "request": "set_tile_data",
+            "coord": [0, 0],
+            "key": "automancy:item",
+            "value": {"Id": "automancy:coal"},
+        }))
+        .await;
+
+    let data = connection
+        .ok(json!({"request": "get_tile_data", "coord": [0, 0]}))
+        .await;
+    assert_eq!(data["automancy:item"], json!({"Id": "automancy:coal"}));
+    assert!(matches!(
+        test.data(TileCoord::ZERO)
+            .await
+            .get(&test.resource_man.registry.data_ids.item),
+        Some(Data::Id(item)) if *item == id("automancy:coal")
+    ));
+
+    test.game
+        .send_message(GameMsg::SetData(
+            test.resource_man.registry.data_ids.world_seed,
+            Data::Amount(42),
+        ))
+        .unwrap();
+    let map_data = connection.ok(json!({"request": "get_map_data"})).await;
+    assert_eq!(map_data["automancy:world_seed"], json!({"Amount": 42}));
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_map_data_while_taken() {
+    let (test, server, mut connection) = control("taken").await;
+    let world_seed = test.resource_man.registry.data_ids.world_seed;
+
+    test.game
+        .send_message(GameMsg::SetData(world_seed, Data::Amount(42)))
+        .unwrap();
+
+    // the GUI takes the map's data map every frame, and puts it back afterwards
+    let taken = test
+        .game
+        .call(GameMsg::TakeDataMap, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let map_data = connection.ok(json!({"request": "get_map_data"})).await;
+    assert_eq!(map_data["automancy:world_seed"], json!({"Amount": 42}));
+
+    test.game.send_message(GameMsg::SetDataMap(taken)).unwrap();
+    let map_data = connection.ok(json!({"request": "get_map_data"})).await;
+    assert_eq!(map_data["automancy:world_seed"], json!({"Amount": 42}));
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_pause_and_step() {
+    let (test, server, mut connection) = control("tick").await;
+
+    connection.ok(json!({"request": "pause"})).await;
+    test.tick(5).await;
+    assert_eq!(tick_count(&test).await, 0);
+
+    let stepped = connection.ok(json!({"request": "step", "ticks": 3})).await;
+    assert_eq!(stepped, json!({"tick_count": 3}));
+
+    connection.ok(json!({"request": "resume"})).await;
+    test.tick(2).await;
+    assert_eq!(tick_count(&test).await, 5);
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_bad_requests_are_answered_with_errors() {
+    let (test, server, mut connection) = control("errors").await;
+
+    for request in [
+        json!({"request": "place_tile", "coord": [0, 0], "id": "automancy:nothing"}),
+        json!({"request": "get_tile_data", "coord": [3, 3]}),
+        json!({"request": "explode"}),
+        json!({"request": "step", "ticks": MAX_STEP_TICKS + 1}),
+    ] {
+        assert!(matches!(
+            connection.request(request).await,
+            ControlResponse::Error(_)
+        ));
+    }
+    assert!(matches!(
+        connection.send_line("not json").await,
+        ControlResponse::Error(_)
+    ));
+
+    // the connection is still usable afterwards
+    assert_eq!(
+        connection
+            .ok(json!({"request": "get_tile", "coord": [0, 0]}))
+            .await,
+        Value::Null
+    );
+
+    server.stop().await;
+    test.stop().await;
+}
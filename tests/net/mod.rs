@@ -0,0 +1,3 @@
+#[cfg(unix)]
+pub mod control;
+pub mod multiplayer;
@@ -0,0 +1,401 @@
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+
+use automancy::game::GameMsg;
+use automancy::net::client::{self, Client, World};
+use automancy::net::server::Server;
+use automancy::net::{ClientMsg, ServerMsg};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::{Id, IdRaw};
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::stack::ItemAmount;
+use automancy_resources::data::Data;
+
+use crate::support::game::GameTest;
+use crate::support::id;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hosts an empty map on a loopback port.
+async fn host() -> (GameTest, Server) {
+    let test = GameTest::new().await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server = Server::start(listener, test.resource_man.clone(), test.game.clone())
+        .await
+        .unwrap();
+
+    (test, server)
+}
+
+fn has_tile(world: &World, coord: TileCoord, tile: &str) -> bool {
+    world.tiles.get(&coord) == Some(&(IdRaw::parse(tile), 0))
+}
+
+fn held(world: &World, item: &str) -> ItemAmount {
+    world
+        .inventory
+        .clone()
+        .into_inner()
+        .into_iter()
+        .find(|(id, _)| *id == IdRaw::parse(item))
+        .map(|(_, amount)| amount)
+        .unwrap_or(0)
+}
+
+/// Joins the server with a game of its own that mirrors the map, as the game does with `--join`.
+async fn join(server: &Server, name: &str) -> GameTest {
+    let joined = GameTest::new().await;
+    let client = Client::connect(server.addr(), name).await.unwrap();
+
+    // stops along with the game
+    tokio::spawn(client::mirror(
+        joined.resource_man.clone(),
+        joined.game.clone(),
+        client,
+    ));
+
+    joined
+}
+
+/// Waits until the game has the tile at the given position, as what changes on the server takes a while to get to it.
+async fn wait_for_tile(test: &GameTest, coord: TileCoord, tile: Option<&str>) {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let current = test
+                .game
+                .call(|reply| GameMsg::GetTile(coord, reply), None)
+                .await
+                .unwrap()
+                .unwrap();
+
+            if current == tile.map(|tile| (id(tile), 0)) {
+                return;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{tile:?} did not get to {coord} in time"));
+}
+
+/// Waits until the tile at the given position has the value in its data map.
+async fn wait_for_data(test: &GameTest, coord: TileCoord, key: Id, value: Data) {
+    tokio::time::timeout(TIMEOUT, async {
+        while test.data(coord).await.get(&key) != Some(&value) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("The data of the tile at {coord} did not change in time"));
+}
+
+/// Places a storage at the origin, holding some coal to take.
+async fn coal_storage(test: &GameTest) {
+    let mut buffer = Inventory::default();
+    buffer.add(id("automancy:coal"), 10);
+
+    test.place(TileCoord::ZERO, "automancy:small_storage", 0)
+        .await;
+    test.set_data(
+        TileCoord::ZERO,
+        test.resource_man.registry.data_ids.buffer,
+        Data::Inventory(buffer),
+    );
+}
+
+#[tokio::test]
+async fn test_clients_see_each_others_changes() {
+    let (test, server) = host().await;
+
+    let mut alice = Client::connect(server.addr(), "alice").await.unwrap();
+    let mut bob = Client::connect(server.addr(), "bob").await.unwrap();
+    assert_ne!(alice.player, bob.player);
+
+    alice
+        .send(&ClientMsg::PlaceTile {
+            coord: TileCoord::ZERO,
+            id: IdRaw::parse("automancy:merger"),
+            tile_modifier: 0,
+        })
+        .await
+        .unwrap();
+
+    for client in [&mut alice, &mut bob] {
+        client
+            .wait_for(TIMEOUT, |world| {
+                has_tile(world, TileCoord::ZERO, "automancy:merger")
+            })
+            .await
+            .unwrap();
+    }
+
+    bob.send(&ClientMsg::PlaceTile {
+        coord: TileCoord::ZERO,
+        id: IdRaw::parse("automancy:none"),
+        tile_modifier: 0,
+    })
+    .await
+    .unwrap();
+
+    alice
+        .wait_for(TIMEOUT, |world| world.tiles.is_empty())
+        .await
+        .unwrap();
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_undo_is_rejected() {
+    let (test, server) = host().await;
+
+    let mut alice = Client::connect(server.addr(), "alice").await.unwrap();
+    let mut bob = Client::connect(server.addr(), "bob").await.unwrap();
+
+    alice
+        .send(&ClientMsg::PlaceTile {
+            coord: TileCoord::ZERO,
+            id: IdRaw::parse("automancy:merger"),
+            tile_modifier: 0,
+        })
+        .await
+        .unwrap();
+    bob.wait_for(TIMEOUT, |world| {
+        has_tile(world, TileCoord::ZERO, "automancy:merger")
+    })
+    .await
+    .unwrap();
+
+    bob.send(&ClientMsg::Undo).await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(bob.recv().await.unwrap(), ServerMsg::Rejected(_)) {}
+    })
+    .await
+    .unwrap();
+
+    // alice's tile is still there
+    let tile = test
+        .game
+        .call(|reply| GameMsg::GetTile(TileCoord::ZERO, reply), None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tile, Some((id("automancy:merger"), 0)));
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_players_have_their_own_inventories() {
+    let (test, server) = host().await;
+    coal_storage(&test).await;
+
+    let mut alice = Client::connect(server.addr(), "alice").await.unwrap();
+    let mut bob = Client::connect(server.addr(), "bob").await.unwrap();
+
+    alice
+        .send(&ClientMsg::TakeItems {
+            coord: TileCoord::ZERO,
+            item: IdRaw::parse("automancy:coal"),
+            amount: 4,
+        })
+        .await
+        .unwrap();
+    alice
+        .wait_for(TIMEOUT, |world| held(world, "automancy:coal") == 4)
+        .await
+        .unwrap();
+
+    bob.wait_for(TIMEOUT, |world| !world.tiles.is_empty())
+        .await
+        .unwrap();
+    assert_eq!(held(&bob.world, "automancy:coal"), 0);
+
+    let mut buffer = test
+        .data(TileCoord::ZERO)
+        .await
+        .get(&test.resource_man.registry.data_ids.buffer)
+        .and_then(Data::as_inventory)
+        .cloned()
+        .unwrap();
+    assert_eq!(buffer.get(id("automancy:coal")), 6);
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_players_taking_at_once_share_what_there_is() {
+    let (test, server) = host().await;
+    coal_storage(&test).await;
+
+    let mut alice = Client::connect(server.addr(), "alice").await.unwrap();
+    let mut bob = Client::connect(server.addr(), "bob").await.unwrap();
+
+    for client in [&mut alice, &mut bob] {
+        client
+            .send(&ClientMsg::TakeItems {
+                coord: TileCoord::ZERO,
+                item: IdRaw::parse("automancy:coal"),
+                amount: 8,
+            })
+            .await
+            .unwrap();
+    }
+    for client in [&mut alice, &mut bob] {
+        client
+            .wait_for(TIMEOUT, |world| held(world, "automancy:coal") > 0)
+            .await
+            .unwrap();
+    }
+
+    // nothing is taken twice, or lost
+    assert_eq!(
+        held(&alice.world, "automancy:coal") + held(&bob.world, "automancy:coal"),
+        10
+    );
+
+    let mut buffer = test
+        .data(TileCoord::ZERO)
+        .await
+        .get(&test.resource_man.registry.data_ids.buffer)
+        .and_then(Data::as_inventory)
+        .cloned()
+        .unwrap();
+    assert_eq!(buffer.get(id("automancy:coal")), 0);
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_reconnecting_keeps_the_player() {
+    let (test, server) = host().await;
+    coal_storage(&test).await;
+
+    let mut alice = Client::connect(server.addr(), "alice").await.unwrap();
+    let player = alice.player;
+
+    alice
+        .send(&ClientMsg::TakeItems {
+            coord: TileCoord::ZERO,
+            item: IdRaw::parse("automancy:coal"),
+            amount: 4,
+        })
+        .await
+        .unwrap();
+    alice
+        .wait_for(TIMEOUT, |world| held(world, "automancy:coal") == 4)
+        .await
+        .unwrap();
+
+    alice.reconnect().await.unwrap();
+    assert_eq!(alice.player, player);
+    assert_eq!(held(&alice.world, "automancy:coal"), 0);
+
+    alice
+        .wait_for(TIMEOUT, |world| {
+            held(world, "automancy:coal") == 4
+                && has_tile(world, TileCoord::ZERO, "automancy:small_storage")
+        })
+        .await
+        .unwrap();
+
+    let players = server.players();
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].name, "alice");
+
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_joined_game_mirrors_the_map() {
+    let (test, server) = host().await;
+    let data_ids = test.resource_man.registry.data_ids;
+    coal_storage(&test).await;
+
+    let joined = join(&server, "alice").await;
+    let mut bob = Client::connect(server.addr(), "bob").await.unwrap();
+
+    // what was on the map before joining
+    wait_for_tile(&joined, TileCoord::ZERO, Some("automancy:small_storage")).await;
+    let mut buffer = Inventory::default();
+    buffer.add(id("automancy:coal"), 10);
+    wait_for_data(
+        &joined,
+        TileCoord::ZERO,
+        data_ids.buffer,
+        Data::Inventory(buffer),
+    )
+    .await;
+
+    // what the player builds goes to the server, and only then to the mirror
+    joined
+        .game
+        .send_message(GameMsg::PlaceTile {
+            coord: TileCoord::RIGHT,
+            id: id("automancy:merger"),
+            tile_modifier: 0,
+            data: None,
+            record: true,
+            reply: None,
+        })
+        .unwrap();
+    bob.wait_for(TIMEOUT, |world| {
+        has_tile(world, TileCoord::RIGHT, "automancy:merger")
+    })
+    .await
+    .unwrap();
+    wait_for_tile(&test, TileCoord::RIGHT, Some("automancy:merger")).await;
+    wait_for_tile(&joined, TileCoord::RIGHT, Some("automancy:merger")).await;
+
+    // and so does what the player sets up
+    joined.set_data(TileCoord::ZERO, data_ids.amount, Data::Amount(50));
+    wait_for_data(&test, TileCoord::ZERO, data_ids.amount, Data::Amount(50)).await;
+    wait_for_data(&joined, TileCoord::ZERO, data_ids.amount, Data::Amount(50)).await;
+
+    // the server runs the map, not the mirror
+    joined.game.send_message(GameMsg::Tick).unwrap();
+    let tick_count = joined
+        .game
+        .call(GameMsg::GetTickCount, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tick_count, 0);
+
+    joined.stop().await;
+    server.stop().await;
+    test.stop().await;
+}
+
+#[tokio::test]
+async fn test_joined_game_hears_of_removed_tiles() {
+    let (test, server) = host().await;
+    coal_storage(&test).await;
+
+    let joined = join(&server, "alice").await;
+    wait_for_tile(&joined, TileCoord::ZERO, Some("automancy:small_storage")).await;
+
+    test.game
+        .send_message(GameMsg::PlaceTile {
+            coord: TileCoord::ZERO,
+            id: id("automancy:none"),
+            tile_modifier: 0,
+            data: None,
+            record: true,
+            reply: None,
+        })
+        .unwrap();
+    wait_for_tile(&joined, TileCoord::ZERO, None).await;
+
+    joined.stop().await;
+    server.stop().await;
+    test.stop().await;
+}
@@ -0,0 +1,31 @@
+use rhai::{Dynamic, INT};
+
+use automancy_resources::data::item::Item;
+use automancy_resources::ResourceManager;
+
+use crate::support::{empty_resource_man, id, resource_man};
+
+/// Looks up an item with the `as_item` function of a resource manager's engine.
+fn as_item(resource_man: &ResourceManager, item: &str) -> Dynamic {
+    let id: INT = id(item).into();
+
+    resource_man
+        .engine
+        .eval::<Dynamic>(&format!("as_item({id})"))
+        .unwrap()
+}
+
+#[test]
+fn test_engine_uses_its_own_resource_manager() {
+    let loaded = resource_man();
+    let empty = empty_resource_man();
+
+    let item = as_item(&loaded, "automancy:coal").try_cast::<Item>();
+    assert_eq!(item.map(|v| v.id), Some(id("automancy:coal")));
+
+    assert!(as_item(&empty, "automancy:coal").is_unit());
+
+    // the loaded resource manager still works after the other one is gone
+    drop(empty);
+    assert!(!as_item(&loaded, "automancy:coal").is_unit());
+}
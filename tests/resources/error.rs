@@ -0,0 +1,28 @@
+use automancy_resources::error::ErrorManager;
+use automancy_resources::format::FormatArgs;
+
+use crate::support::resource_man;
+
+#[test]
+fn test_errors_are_shown_in_the_order_they_happened() {
+    let resource_man = resource_man();
+    let err_ids = &resource_man.registry.err_ids;
+    let error_man = ErrorManager::default();
+
+    error_man.push((err_ids.invalid_resource, FormatArgs::new()), &resource_man);
+    error_man.push((err_ids.invalid_map_data, FormatArgs::new()), &resource_man);
+
+    assert_eq!(
+        error_man.peek().map(|(id, _)| id),
+        Some(err_ids.invalid_resource)
+    );
+    assert_eq!(
+        error_man.pop().map(|(id, _)| id),
+        Some(err_ids.invalid_resource)
+    );
+    assert_eq!(
+        error_man.pop().map(|(id, _)| id),
+        Some(err_ids.invalid_map_data)
+    );
+    assert!(!error_man.has_errors());
+}
@@ -0,0 +1,85 @@
+use automancy_resources::format::{format, format_number, plural_category, FormatArgs};
+
+const FILES: &str = "{count, plural, one {# file} few {# pliki} many {# plików} other {# files}}";
+
+#[test]
+fn test_english_plurals() {
+    assert_eq!(plural_category("en_US", 0), "other");
+    assert_eq!(plural_category("en_US", 1), "one");
+    assert_eq!(plural_category("en_GB", 2), "other");
+    assert_eq!(plural_category("en_US", -1), "one");
+}
+
+#[test]
+fn test_plurals_by_language() {
+    // french counts 0 as one
+    assert_eq!(plural_category("fr_FR", 0), "one");
+    assert_eq!(plural_category("fr_FR", 1), "one");
+    assert_eq!(plural_category("fr_FR", 2), "other");
+
+    assert_eq!(plural_category("pl_PL", 1), "one");
+    assert_eq!(plural_category("pl_PL", 3), "few");
+    assert_eq!(plural_category("pl_PL", 5), "many");
+    assert_eq!(plural_category("pl_PL", 13), "many");
+    assert_eq!(plural_category("pl_PL", 22), "few");
+
+    assert_eq!(plural_category("ru_RU", 21), "one");
+    assert_eq!(plural_category("ru_RU", 11), "many");
+    assert_eq!(plural_category("ru_RU", 24), "few");
+
+    assert_eq!(plural_category("ja_JP", 1), "other");
+
+    // languages it doesn't know of use the english rules
+    assert_eq!(plural_category("xx_XX", 1), "one");
+    assert_eq!(plural_category("xx_XX", 2), "other");
+}
+
+#[test]
+fn test_number_separators_by_language() {
+    assert_eq!(format_number("en_US", 1234567), "1,234,567");
+    assert_eq!(format_number("en_US", -1234), "-1,234");
+    assert_eq!(format_number("en_US", 123), "123");
+    assert_eq!(format_number("de_DE", 1234567), "1.234.567");
+    assert_eq!(format_number("de_CH", 1234), "1'234");
+    assert_eq!(format_number("fr_FR", 1234), "1\u{202f}234");
+    assert_eq!(format_number("ru_RU", 1234), "1\u{a0}234");
+    assert_eq!(format_number("xx_XX", 1234), "1,234");
+}
+
+#[test]
+fn test_format_picks_plural_of_language() {
+    let args = |count: i64| FormatArgs::new().with("count", count);
+
+    assert_eq!(format("en_US", FILES, &args(1)), "1 file");
+    assert_eq!(format("en_US", FILES, &args(3)), "3 files");
+    assert_eq!(format("pl_PL", FILES, &args(3)), "3 pliki");
+    assert_eq!(format("pl_PL", FILES, &args(5)), "5 plików");
+    assert_eq!(format("pl_PL", FILES, &args(1000)), "1\u{a0}000 plików");
+
+    // an exact match comes before the category
+    assert_eq!(
+        format(
+            "fr_FR",
+            "{count, plural, =0 {aucun} one {# fichier} other {# fichiers}}",
+            &args(0)
+        ),
+        "aucun"
+    );
+    assert_eq!(
+        format(
+            "fr_FR",
+            "{count, plural, one {# fichier} other {# fichiers}}",
+            &args(0)
+        ),
+        "0 fichier"
+    );
+}
+
+#[test]
+fn test_number_placeholder_uses_separator_of_language() {
+    let args = FormatArgs::new().with("amount", 12345);
+
+    assert_eq!(format("en_US", "{amount, number}", &args), "12,345");
+    assert_eq!(format("de_DE", "{amount, number}", &args), "12.345");
+    assert_eq!(format("en_US", "{amount}", &args), "12345");
+}
@@ -0,0 +1,8 @@
+pub mod context;
+pub mod error;
+pub mod format;
+pub mod scenario;
+pub mod slots;
+pub mod tag;
+pub mod translate;
+pub mod validate;
@@ -0,0 +1,57 @@
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::{Data, DataMap};
+use automancy_resources::types::scenario::Objective;
+
+use crate::support::{id, resource_man};
+
+#[test]
+fn test_scenario_loaded() {
+    let resource_man = resource_man();
+
+    let scenario = resource_man
+        .registry
+        .scenario(id("automancy:the_great_work"))
+        .expect("The scenario should be loaded");
+
+    assert_eq!(scenario.tiles.len(), 1);
+    assert_eq!(scenario.objectives.len(), 2);
+
+    assert!(scenario.allows(&resource_man, id("automancy:basic_still")));
+    assert!(scenario.allows(&resource_man, id("automancy:none")));
+    assert!(!scenario.allows(&resource_man, id("automancy:node")));
+}
+
+#[test]
+fn test_objective_progress() {
+    let resource_man = resource_man();
+    let data_ids = resource_man.registry.data_ids;
+
+    let mut buffer = Inventory::default();
+    buffer.add(id("automancy:philosophers_stone"), 30);
+    buffer.add(id("automancy:coal"), 5);
+
+    let mut storage = DataMap::default();
+    storage.insert(data_ids.buffer, Data::Inventory(buffer));
+    let empty = DataMap::default();
+
+    let tiles = [
+        (id("automancy:small_storage"), &storage),
+        (id("automancy:small_storage"), &storage),
+        (id("automancy:basic_still"), &storage),
+        (id("automancy:basic_still"), &empty),
+    ];
+
+    let deliver = Objective::Deliver {
+        item: id("automancy:philosophers_stone"),
+        amount: 100,
+        to: id("automancy:small_storage"),
+    };
+    assert_eq!(deliver.progress(&resource_man, tiles.iter().cloned()), 60);
+
+    let place = Objective::Place {
+        tile: id("automancy:#machines"),
+        amount: 6,
+    };
+    assert_eq!(place.progress(&resource_man, tiles.iter().cloned()), 2);
+    assert_eq!(place.required(), 6);
+}
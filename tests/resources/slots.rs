@@ -0,0 +1,75 @@
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::slots::{allocate, room, Slot};
+
+use crate::support::{id, resource_man};
+
+/// A slot for any element, one for coal, and one for anything.
+fn slots() -> Vec<Slot> {
+    Slot::from_lists(
+        &[
+            id("automancy:#elements"),
+            id("automancy:coal"),
+            resource_man().registry.any,
+        ],
+        &[10, 5, 3],
+    )
+}
+
+fn buffer() -> Inventory {
+    let mut buffer = Inventory::default();
+    buffer.insert(id("automancy:coal"), 7);
+    buffer.insert(id("automancy:fire"), 4);
+
+    buffer
+}
+
+#[test]
+fn test_exact_slots_are_filled_first() {
+    let allocation = allocate(&resource_man(), &slots(), &buffer());
+
+    assert_eq!(
+        allocation.slots,
+        vec![
+            Some((id("automancy:fire"), 4)),
+            Some((id("automancy:coal"), 5)),
+            Some((id("automancy:coal"), 2)),
+        ]
+    );
+    assert!(allocation.overflow.is_empty());
+}
+
+#[test]
+fn test_removed_slots_overflow() {
+    let allocation = allocate(&resource_man(), &slots()[..2], &buffer());
+
+    assert_eq!(allocation.slots[1], Some((id("automancy:coal"), 5)));
+    assert_eq!(allocation.overflow.clone().get(id("automancy:coal")), 2);
+}
+
+#[test]
+fn test_room_keeps_one_item_per_slot() {
+    let resource_man = resource_man();
+
+    assert_eq!(
+        room(&resource_man, &slots(), &buffer(), id("automancy:coal")),
+        1
+    );
+    assert_eq!(
+        room(&resource_man, &slots(), &buffer(), id("automancy:fire")),
+        6
+    );
+    // both slots it could go into already hold something else
+    assert_eq!(
+        room(&resource_man, &slots(), &buffer(), id("automancy:water")),
+        0
+    );
+    assert_eq!(
+        room(
+            &resource_man,
+            &slots(),
+            &Inventory::default(),
+            id("automancy:sand")
+        ),
+        3
+    );
+}
@@ -0,0 +1,152 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use automancy_defs::hashbrown::{HashMap, HashSet};
+use automancy_defs::id::Id;
+use automancy_resources::{LoadResourceError, ResourceManager};
+
+use crate::support::new_resource_man;
+
+/// Writes a namespace with the given tags, and loads it.
+fn load_tags(name: &str, tags: &[(&str, &[&str])]) -> (ResourceManager, PathBuf) {
+    let dir = env::temp_dir().join(format!("automancy_tag_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("tags")).unwrap();
+
+    for (i, (id, entries)) in tags.iter().enumerate() {
+        let entries = entries
+            .iter()
+            .map(|entry| format!("\"{entry}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        fs::write(
+            dir.join("tags").join(format!("{i}.ron")),
+            format!("(id: \"{id}\", entries: [{entries}])"),
+        )
+        .unwrap();
+    }
+
+    let mut resource_man = new_resource_man();
+    resource_man.load_tags(&dir).unwrap();
+
+    (resource_man, dir)
+}
+
+fn id(resource_man: &ResourceManager, id: &str) -> Id {
+    resource_man.interner.get(id).unwrap()
+}
+
+fn flatten(resource_man: &ResourceManager, tag: &str) -> Result<HashSet<Id>, LoadResourceError> {
+    resource_man.flatten_tag(id(resource_man, tag), &mut vec![], &mut HashMap::new())
+}
+
+#[test]
+fn test_nested_tag_is_flattened() {
+    let (mut resource_man, dir) = load_tags(
+        "nested",
+        &[
+            ("test:#fuel", &["test:coal", "test:#wood"]),
+            ("test:#wood", &["test:log", "test:plank"]),
+        ],
+    );
+    let _ = fs::remove_dir_all(&dir);
+
+    let expected = ["test:coal", "test:log", "test:plank"]
+        .map(|v| id(&resource_man, v))
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert_eq!(flatten(&resource_man, "test:#fuel").unwrap(), expected);
+
+    resource_man.compile_tags();
+    assert!(resource_man.load_errors.is_empty());
+
+    let fuel = resource_man
+        .registry
+        .tag(id(&resource_man, "test:#fuel"))
+        .unwrap();
+    assert_eq!(fuel.entries, expected);
+}
+
+#[test]
+fn test_tag_including_itself_is_a_cycle() {
+    let (resource_man, dir) = load_tags("self", &[("test:#loop", &["test:coal", "test:#loop"])]);
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(matches!(
+        flatten(&resource_man, "test:#loop"),
+        Err(LoadResourceError::TagCycle(cycle)) if cycle == "test:#loop -> test:#loop"
+    ));
+}
+
+#[test]
+fn test_two_tags_including_each_other_are_a_cycle() {
+    let (mut resource_man, dir) = load_tags(
+        "two",
+        &[
+            ("test:#a", &["test:coal", "test:#b"]),
+            ("test:#b", &["test:log", "test:#a"]),
+        ],
+    );
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(matches!(
+        flatten(&resource_man, "test:#a"),
+        Err(LoadResourceError::TagCycle(cycle)) if cycle == "test:#a -> test:#b -> test:#a"
+    ));
+    assert!(matches!(
+        flatten(&resource_man, "test:#b"),
+        Err(LoadResourceError::TagCycle(cycle)) if cycle == "test:#b -> test:#a -> test:#b"
+    ));
+
+    // the cycle is reported once when compiled, and both tags match everything either does
+    resource_man.compile_tags();
+    assert_eq!(resource_man.load_errors.len(), 1);
+    assert_eq!(
+        resource_man.load_errors[0].message,
+        "the tag includes itself: test:#a -> test:#b -> test:#a"
+    );
+
+    let expected = ["test:coal", "test:log"]
+        .map(|v| id(&resource_man, v))
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    for tag in ["test:#a", "test:#b"] {
+        let tag = resource_man.registry.tag(id(&resource_man, tag)).unwrap();
+        assert_eq!(tag.entries, expected);
+    }
+}
+
+#[test]
+fn test_tag_including_a_cycle_is_flattened() {
+    let (mut resource_man, dir) = load_tags(
+        "around",
+        &[
+            ("test:#a", &["test:coal", "test:#b"]),
+            ("test:#b", &["test:log", "test:#c"]),
+            ("test:#c", &["test:#a"]),
+            ("test:#fuel", &["test:plank", "test:#b"]),
+        ],
+    );
+    let _ = fs::remove_dir_all(&dir);
+
+    resource_man.compile_tags();
+    assert_eq!(resource_man.load_errors.len(), 1);
+    assert_eq!(
+        resource_man.load_errors[0].message,
+        "the tag includes itself: test:#a -> test:#b -> test:#c -> test:#a"
+    );
+
+    let expected = ["test:coal", "test:log", "test:plank"]
+        .map(|v| id(&resource_man, v))
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let fuel = resource_man
+        .registry
+        .tag(id(&resource_man, "test:#fuel"))
+        .unwrap();
+    assert_eq!(fuel.entries, expected);
+}
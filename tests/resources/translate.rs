@@ -0,0 +1,108 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use automancy_resources::types::translate::{language_chain, DEFAULT_LANGUAGE};
+use automancy_resources::ResourceManager;
+
+use crate::support::new_resource_man;
+
+/// Writes a namespace with a translation of `test:greeting` and `test:farewell` for each of the given languages, where the farewell is left out of the ones without one.
+fn namespace(name: &str, translates: &[(&str, &str, Option<&str>)]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("automancy_translate_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("translates")).unwrap();
+
+    for (language, greeting, farewell) in translates {
+        let farewell = farewell
+            .map(|farewell| format!("\"test:farewell\": \"{farewell}\","))
+            .unwrap_or_default();
+
+        fs::write(
+            dir.join("translates").join(format!("{language}.ron")),
+            format!("(gui: {{ \"test:greeting\": \"{greeting}\", {farewell} }})"),
+        )
+        .unwrap();
+    }
+
+    dir
+}
+
+fn gui_str(resource_man: &ResourceManager, key: &str) -> String {
+    resource_man
+        .gui_str(&resource_man.interner.get(key).unwrap())
+        .to_string()
+}
+
+#[test]
+fn test_chain_of_loaded_language() {
+    assert_eq!(
+        language_chain("fr_CA", ["fr_FR", "fr_CA", DEFAULT_LANGUAGE]),
+        vec!["fr_CA", "fr_FR", DEFAULT_LANGUAGE]
+    );
+}
+
+#[test]
+fn test_chain_falls_back_to_same_prefix() {
+    // there is no en_EN, so the other english translations are used
+    assert_eq!(
+        language_chain("en_GB", ["en_AU", DEFAULT_LANGUAGE, "de_DE"]),
+        vec!["en_GB", "en_AU", DEFAULT_LANGUAGE]
+    );
+
+    // the one whose region is the prefix comes before the others
+    assert_eq!(
+        language_chain("de_AT", ["de_CH", "de_DE", DEFAULT_LANGUAGE]),
+        vec!["de_AT", "de_DE", "de_CH", DEFAULT_LANGUAGE]
+    );
+
+    assert_eq!(
+        language_chain("pt", ["pt_BR", "pt_PT"]),
+        vec!["pt", "pt_PT", "pt_BR", DEFAULT_LANGUAGE]
+    );
+}
+
+#[test]
+fn test_chain_without_related_languages() {
+    assert_eq!(
+        language_chain("ja_JP", ["fr_FR", DEFAULT_LANGUAGE]),
+        vec!["ja_JP", DEFAULT_LANGUAGE]
+    );
+    assert_eq!(
+        language_chain(DEFAULT_LANGUAGE, ["en_GB", DEFAULT_LANGUAGE]),
+        vec![DEFAULT_LANGUAGE, "en_GB"]
+    );
+}
+
+#[test]
+fn test_missing_keys_fall_back_along_chain() {
+    let dir = namespace(
+        "fallback",
+        &[
+            (DEFAULT_LANGUAGE, "Hello", Some("Goodbye")),
+            ("en_AU", "G'day", None),
+        ],
+    );
+
+    let mut resource_man = new_resource_man();
+    resource_man.load_translates(&dir, "en_GB").unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    // en_GB isn't there, so the greeting is from en_AU, and the farewell it doesn't have is from the default
+    assert_eq!(gui_str(&resource_man, "test:greeting"), "G'day");
+    assert_eq!(gui_str(&resource_man, "test:farewell"), "Goodbye");
+}
+
+#[test]
+fn test_keys_missing_everywhere_are_shown_as_is() {
+    let dir = namespace("missing", &[(DEFAULT_LANGUAGE, "Hello", None)]);
+
+    let mut resource_man = new_resource_man();
+    resource_man
+        .load_translates(&dir, DEFAULT_LANGUAGE)
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    let farewell = resource_man.interner.get_or_intern("test:farewell");
+    assert_eq!(resource_man.gui_str(&farewell), "test:farewell");
+}
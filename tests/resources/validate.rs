@@ -0,0 +1,195 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
+use automancy_resources::validate::{constants_used, load_and_validate, validate, Issue, Severity};
+use automancy_resources::RESOURCES_PATH;
+
+use crate::support::new_resource_man;
+
+/// Writes a namespace with a tile that points at an item that doesn't exist, and a function that uses a key it doesn't declare.
+fn broken_namespace() -> PathBuf {
+    let dir = env::temp_dir().join("automancy_validate_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("tiles")).unwrap();
+    fs::create_dir_all(dir.join("functions")).unwrap();
+
+    fs::write(
+        dir.join("tiles").join("probe.ron"),
+        r#"(
+    id: "test:probe",
+    function: Some("test:probe"),
+    models: [
+        "automancy:none",
+    ],
+    data: ({
+        "item": Id("test:missing_item"),
+    }),
+)"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("functions").join("probe.rhai"),
+        r#"fn id_deps() {
+    [
+        ["buffer", "BUFFER"],
+    ]
+}
+
+fn function_id() {
+    "test:probe"
+}
+
+fn handle_tick(input) {
+    // the AMOUNT is only read in the comment
+    let buffer = this.data[BUFFER];
+
+    return ["signal", input.coord, UNDECLARED, 1];
+}
+"#,
+    )
+    .unwrap();
+
+    dir
+}
+
+fn errors(issues: &[Issue]) -> Vec<&Issue> {
+    issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .collect()
+}
+
+#[test]
+fn test_shipped_resources_are_valid() {
+    let mut resource_man = new_resource_man();
+
+    let issues = load_and_validate(
+        &mut resource_man,
+        Path::new(RESOURCES_PATH),
+        DEFAULT_LANGUAGE,
+    );
+
+    assert!(errors(&issues).is_empty(), "{:#?}", errors(&issues));
+}
+
+#[test]
+fn test_broken_namespace_is_reported() {
+    let dir = broken_namespace();
+
+    let mut resource_man = new_resource_man();
+    resource_man
+        .load_all(Path::new(RESOURCES_PATH), DEFAULT_LANGUAGE)
+        .unwrap();
+    resource_man.load_namespace(&dir, DEFAULT_LANGUAGE);
+    resource_man.compile();
+
+    let issues = validate(&resource_man);
+    let _ = fs::remove_dir_all(&dir);
+
+    let tile = dir.join("tiles").join("probe.ron");
+    let function = dir.join("functions").join("probe.rhai");
+    let messages = |severity: Severity, path: &Path| {
+        issues
+            .iter()
+            .filter(|issue| issue.severity == severity && issue.path.as_deref() == Some(path))
+            .map(|issue| issue.message.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // a dangling id is an error, a missing translation a warning
+    let tile_errors = messages(Severity::Error, &tile);
+    assert_eq!(tile_errors.len(), 1, "{tile_errors:?}");
+    assert!(tile_errors[0].contains("test:missing_item does not exist"));
+    assert_eq!(
+        messages(Severity::Warning, &tile),
+        vec!["tile test:probe has no translation"]
+    );
+
+    // the constant in the comment isn't counted
+    assert_eq!(
+        messages(Severity::Error, &function),
+        vec!["UNDECLARED is used but not declared in id_deps"]
+    );
+
+    let issue = issues
+        .iter()
+        .find(|issue| {
+            issue.path.as_deref() == Some(tile.as_path()) && issue.severity == Severity::Warning
+        })
+        .unwrap();
+    assert_eq!(
+        issue.to_string(),
+        format!(
+            "warning: {}: tile test:probe has no translation",
+            tile.display()
+        )
+    );
+}
+
+#[test]
+fn test_files_that_fail_to_load_are_errors() {
+    let dir = env::temp_dir().join("automancy_validate_load_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("test").join("tiles")).unwrap();
+    fs::write(dir.join("test").join("tiles").join("broken.ron"), "(id: ").unwrap();
+
+    let mut resource_man = new_resource_man();
+    let issues = load_and_validate(&mut resource_man, &dir, DEFAULT_LANGUAGE);
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(issues.iter().any(|issue| issue.severity == Severity::Error
+        && issue.path.as_deref()
+            == Some(dir.join("test").join("tiles").join("broken.ron").as_path())));
+}
+
+#[test]
+fn test_constants_in_code() {
+    let used = constants_used(
+        "let buffer = this.data[BUFFER];\nlet left = TileCoord::LEFT;\nlet x = 1 + A;\nthis.data[OUTPUT_BUFFER_2] = buffer;",
+    );
+
+    let mut used = used.into_iter().collect::<Vec<_>>();
+    used.sort();
+    assert_eq!(used, vec!["BUFFER", "OUTPUT_BUFFER_2"]);
+}
+
+#[test]
+fn test_constants_declared_in_code_are_ignored() {
+    let used = constants_used(
+        "const MAX = 4;\nfn f(LIMIT, b) { let COUNT = LIMIT + MAX; for I in 0..COUNT { this.data[BUFFER] = I; } }\nlet x = OTHER;",
+    );
+
+    let mut used = used.into_iter().collect::<Vec<_>>();
+    used.sort();
+    assert_eq!(used, vec!["BUFFER", "OTHER"]);
+}
+
+#[test]
+fn test_constants_in_comments_are_ignored() {
+    let used = constants_used(
+        "// LINE_COMMENT\nlet a = B_1; /* BLOCK /* NESTED */ STILL_COMMENT */ let c = C_2;\n/// DOC_COMMENT",
+    );
+
+    let mut used = used.into_iter().collect::<Vec<_>>();
+    used.sort();
+    assert_eq!(used, vec!["B_1", "C_2"]);
+}
+
+#[test]
+fn test_constants_in_strings_are_ignored() {
+    let used = constants_used(
+        r#"let a = "IN_STRING \"ESCAPED\" // NOT_COMMENT"; let b = AFTER; let c = 'Q';"#,
+    );
+    assert_eq!(used.into_iter().collect::<Vec<_>>(), vec!["AFTER"]);
+
+    // the code interpolated into a template string is still code
+    let used = constants_used(
+        "let a = `TEMPLATE ${this.data[INSIDE]} {BRACES} ${ #{ x: 1 }.x }`; let b = AFTER;",
+    );
+
+    let mut used = used.into_iter().collect::<Vec<_>>();
+    used.sort();
+    assert_eq!(used, vec!["AFTER", "INSIDE"]);
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ractor::{Actor, ActorRef};
+use tokio::task::JoinHandle;
+
+use automancy::game::{Game, GameMsg, PlaceTileResponse};
+use automancy::tile_entity::{TileEntityMsg, TileModifier};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::Id;
+use automancy_resources::data::{Data, DataMap};
+use automancy_resources::ResourceManager;
+
+use crate::support::{id, resource_man};
+
+/// How long [`GameTest::settle`] waits for the tile entities before giving up.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs a game without a window, and builds its map in code.
+pub struct GameTest {
+    pub resource_man: Arc<ResourceManager>,
+    pub game: ActorRef<GameMsg>,
+    handle: JoinHandle<()>,
+}
+
+impl GameTest {
+    /// Starts a game with an empty map, which is never saved.
+    pub async fn new() -> Self {
+        let resource_man = resource_man();
+
+        let (game, handle) = Actor::spawn(
+            None,
+            Game {
+                resource_man: resource_man.clone(),
+            },
+            (),
+        )
+        .await
+        .expect("Could not start the game");
+
+        Self {
+            resource_man,
+            game,
+            handle,
+        }
+    }
+
+    /// Places a tile, replacing whatever was there.
+    pub async fn place(&self, coord: TileCoord, tile: &str, tile_modifier: TileModifier) {
+        let response = self
+            .game
+            .call(
+                |reply| GameMsg::PlaceTile {
+                    coord,
+                    id: id(tile),
+                    tile_modifier,
+                    data: None,
+                    record: false,
+                    reply: Some(reply),
+                },
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(
+            matches!(response, PlaceTileResponse::Placed),
+            "{tile} was not placed at {coord}"
+        );
+    }
+
+    /// Sets a value in the data map of the tile at the given position, the same way the GUI does.
+    pub fn set_data(&self, coord: TileCoord, key: Id, value: Data) {
+        self.game
+            .send_message(GameMsg::SetTileData {
+                coord,
+                key,
+                value: Some(value),
+            })
+            .unwrap();
+    }
+
+    /// Gets the data map of the tile at the given position.
+    pub async fn data(&self, coord: TileCoord) -> DataMap {
+        let tile_entity = self
+            .game
+            .call(|reply| GameMsg::GetTileEntity(coord, reply), None)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_or_else(|| panic!("There is no tile at {coord}"));
+
+        tile_entity
+            .call(TileEntityMsg::GetData, None)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Gets a value in the data map of the map.
+    pub async fn map_data(&self, key: Id) -> Option<Data> {
+        self.game
+            .call(|reply| GameMsg::GetDataValue(key, reply), None)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Waits until every message the tile entities sent each other has been handled.
+    pub async fn settle(&self) {
+        let start = tokio::time::Instant::now();
+
+        loop {
+            let pending = self
+                .game
+                .call(GameMsg::GetPendingMessages, None)
+                .await
+                .unwrap()
+                .unwrap();
+
+            if pending == 0 {
+                return;
+            }
+
+            assert!(
+                start.elapsed() < SETTLE_TIMEOUT,
+                "The game did not settle, {pending} messages are still pending"
+            );
+
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// Runs the given number of ticks, letting each one settle before the next.
+    pub async fn tick(&self, ticks: usize) {
+        for _ in 0..ticks {
+            self.game.send_message(GameMsg::Tick).unwrap();
+            self.settle().await;
+        }
+    }
+
+    /// Stops the game and all of its tile entities.
+    pub async fn stop(self) {
+        self.game.stop(None);
+        self.handle.await.unwrap();
+    }
+}
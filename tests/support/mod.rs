@@ -0,0 +1,249 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use rhai::{Dynamic, Scope, INT};
+
+use automancy::tile_entity::{
+    rhai_call_options, HandlerInput, Refusal, RefusalReason, RHAI_DATA_MAP_KEY,
+};
+use automancy_defs::coord::TileCoord;
+use automancy_defs::id::Id;
+use automancy_resources::data::inventory::Inventory;
+use automancy_resources::data::stack::{ItemAmount, ItemStack};
+use automancy_resources::data::DataMap;
+use automancy_resources::kira::manager::backend::mock::MockBackend;
+use automancy_resources::kira::manager::{AudioManager, AudioManagerSettings};
+use automancy_resources::kira::track::TrackBuilder;
+use automancy_resources::types::translate::DEFAULT_LANGUAGE;
+use automancy_resources::{ResourceManager, RESOURCES_PATH};
+
+pub mod game;
+
+/// Creates a resource manager that things can still be loaded into.
+pub fn new_resource_man() -> ResourceManager {
+    let mut audio_man = AudioManager::<MockBackend>::new(AudioManagerSettings::default())
+        .expect("Could not create the mock audio backend");
+    let track = audio_man
+        .add_sub_track(TrackBuilder::new())
+        .expect("Could not create the audio track");
+
+    ResourceManager::new(track)
+}
+
+fn load_resource_man() -> Arc<ResourceManager> {
+    let mut resource_man = new_resource_man();
+
+    resource_man
+        .load_all(Path::new(RESOURCES_PATH), DEFAULT_LANGUAGE)
+        .expect("Could not read the resources folder");
+    resource_man.compile();
+
+    assert!(
+        resource_man.load_errors.is_empty(),
+        "Resources failed to load: {:?}",
+        resource_man.load_errors
+    );
+
+    resource_man.into_shared()
+}
+
+lazy_static! {
+    static ref TEST_RESOURCE_MAN: Arc<ResourceManager> = load_resource_man();
+}
+
+/// Gets the resource manager shared by every test, with all the namespaces loaded. No audio device or GPU is needed.
+pub fn resource_man() -> Arc<ResourceManager> {
+    TEST_RESOURCE_MAN.clone()
+}
+
+/// Creates a resource manager of its own, with nothing loaded into it.
+pub fn empty_resource_man() -> Arc<ResourceManager> {
+    new_resource_man().into_shared()
+}
+
+/// Gets the ID of a resource, panicking if it was never loaded.
+pub fn id(id: &str) -> Id {
+    resource_man()
+        .interner
+        .get(id)
+        .unwrap_or_else(|| panic!("{id} was never loaded"))
+}
+
+pub fn stack(item: &str, amount: ItemAmount) -> ItemStack {
+    let resource_man = resource_man();
+
+    ItemStack {
+        item: *resource_man
+            .registry
+            .item(id(item))
+            .unwrap_or_else(|| panic!("{item} is not an item")),
+        amount,
+    }
+}
+
+/// Calls the handlers of a tile's function the way a tile entity would, without an actor system.
+pub struct FunctionTest {
+    pub resource_man: Arc<ResourceManager>,
+    /// The ID of the tile the function belongs to.
+    pub tile: Id,
+    pub coord: TileCoord,
+    pub tile_modifier: INT,
+    pub random: INT,
+    /// The data map given to the function as `this.data`.
+    pub data: DataMap,
+    /// The signals on the network the tile is linked to, given to the tick and transaction handlers.
+    pub signals: Inventory,
+    /// The adjacent tile that fulfills the tile's script, given to the tick handler.
+    pub adjacent: Option<TileCoord>,
+    /// How much is left in the adjacent deposit, given to the tick handler.
+    pub remaining: Option<ItemAmount>,
+    scope: Scope<'static>,
+}
+
+impl FunctionTest {
+    /// Creates a test for the function of the given tile, which is placed at the origin.
+    pub fn new(tile: &str) -> Self {
+        let resource_man = resource_man();
+        let tile = id(tile);
+
+        let function = resource_man
+            .registry
+            .tile(tile)
+            .and_then(|v| v.function)
+            .expect("The tile has no function");
+        let (_, scope) = resource_man
+            .functions
+            .get(&function)
+            .expect("The function was never loaded");
+        let scope = scope.clone_visible();
+
+        Self {
+            resource_man,
+            tile,
+            coord: TileCoord::ZERO,
+            tile_modifier: 0,
+            random: 0,
+            data: DataMap::default(),
+            signals: Inventory::default(),
+            adjacent: None,
+            remaining: None,
+            scope,
+        }
+    }
+
+    /// Gets the entries every handler is given, built the same way the tile entity builds them.
+    pub fn input(&self) -> HandlerInput {
+        HandlerInput {
+            id: self.tile,
+            coord: self.coord,
+            tile_modifier: self.tile_modifier,
+            random: self.random,
+        }
+    }
+
+    /// Calls a handler with the given input, and returns the array it returned, if any. `self.data` is updated with the changes the handler made.
+    pub fn call(&mut self, handler: &str, input: rhai::Map) -> Option<rhai::Array> {
+        let function = self.resource_man.registry.tile(self.tile).unwrap().function;
+        let (ast, _) = self.resource_man.functions.get(&function.unwrap()).unwrap();
+
+        let mut rhai_state = Dynamic::from_map(rhai::Map::from([(
+            RHAI_DATA_MAP_KEY.into(),
+            Dynamic::from(self.data.clone()),
+        )]));
+
+        let result = self
+            .resource_man
+            .engine
+            .call_fn_with_options::<Dynamic>(
+                rhai_call_options(&mut rhai_state),
+                &mut self.scope,
+                ast,
+                handler,
+                (input,),
+            )
+            .unwrap_or_else(|err| panic!("{handler} failed: {err}"));
+
+        self.data = rhai_state
+            .take()
+            .cast::<rhai::Map>()
+            .get(RHAI_DATA_MAP_KEY)
+            .cloned()
+            .unwrap()
+            .cast();
+
+        result.try_cast::<rhai::Array>()
+    }
+
+    pub fn tick(&mut self) -> Option<rhai::Array> {
+        let input = self
+            .input()
+            .tick(self.adjacent, self.remaining, self.signals.clone());
+
+        self.call("handle_tick", input)
+    }
+
+    pub fn transaction(
+        &mut self,
+        stack: ItemStack,
+        source_coord: TileCoord,
+    ) -> Option<rhai::Array> {
+        let input = self.input().transaction(
+            stack,
+            source_coord,
+            self.tile,
+            source_coord,
+            self.tile,
+            self.signals.clone(),
+        );
+
+        self.call("handle_transaction", input)
+    }
+
+    pub fn transaction_result(&mut self, transferred: ItemStack) -> Option<rhai::Array> {
+        let input = self.input().transaction_result(Ok(transferred));
+
+        self.call("handle_transaction_result", input)
+    }
+
+    /// Tells the function that a transaction it made was refused by the tile at the given position, for the given reason.
+    pub fn transaction_failed(
+        &mut self,
+        stack: ItemStack,
+        refused_coord: TileCoord,
+        reason: RefusalReason,
+    ) -> Option<rhai::Array> {
+        let input = self.input().transaction_result(Err(Refusal {
+            stack,
+            coord: refused_coord,
+            reason,
+        }));
+
+        self.call("handle_transaction_failed", input)
+    }
+
+    pub fn extract_request(&mut self, requested_from_coord: TileCoord) -> Option<rhai::Array> {
+        let input = self
+            .input()
+            .extract_request(requested_from_coord, self.tile);
+
+        self.call("handle_extract_request", input)
+    }
+}
+
+/// Gets the name of the action a handler returned, e.g. `make_transaction`.
+pub fn action(result: &rhai::Array) -> String {
+    result[0].clone().into_string().expect("Not an action")
+}
+
+/// Finds the action with the given name in what a handler returned, which may be several actions at once.
+pub fn find_action(result: &rhai::Array, name: &str) -> Option<rhai::Array> {
+    if result.first().map_or(false, Dynamic::is_array) {
+        result
+            .iter()
+            .flat_map(|result| result.clone().try_cast::<rhai::Array>())
+            .find(|result| action(result) == name)
+    } else {
+        (action(result) == name).then(|| result.clone())
+    }
+}
@@ -1,6 +1,12 @@
 use automancy_defs::coord::TileCoord;
 
+pub mod functions;
+pub mod game;
 pub mod macros;
+pub mod map;
+pub mod net;
+pub mod resources;
+pub mod support;
 
 #[test]
 fn test_tile_coord_serde() {